@@ -1,3 +1,5 @@
 fn main() {
+    // new migration files are embedded by sqlx::migrate!
+    println!("cargo:rerun-if-changed=migrations");
     tauri_build::build()
 }
@@ -1,7 +1,8 @@
 -- ------------------------------------------- --
 -- -------------- INSTRUCTIONS --------------- --
 -- ------------------------------------------- --
--- Run this script to setup a local database with a basic Entity Attribute Value (EAV) system
+-- Applied automatically by the app on connect, sets up a basic Entity Attribute Value (EAV) system
+-- Safe to run against a database created by the old setup.sql, existing data is kept
 -- Includes utility stored procedures for ease of use
 -- 
 -- Adding entries:
@@ -25,27 +26,20 @@
 -- ------------------------------------------- --
 -- ------------------------------------------- --
 -- ------------------------------------------- --
-use localdb;
 
 -- -------------------------- --
 -- ------- EAV TABLES ------- --
 -- -------------------------- --
 
--- delete old tables
-drop table if exists eav_values;
-drop table if exists eav_entities;
-drop table if exists eav_attrs;
-drop table if exists eav_entity_types;
-
 -- create base tables
-create table eav_entity_types (
+create table if not exists eav_entity_types (
 	id int unsigned not null auto_increment,
 	created_at datetime not null default NOW(),
 	entity_type varchar(255) unique not null,
 	primary key (id)
 );
 
-create table eav_entities (
+create table if not exists eav_entities (
 	id int unsigned not null auto_increment,
 	created_at datetime not null default NOW(),
 	entity varchar(255) not null,
@@ -54,7 +48,7 @@ create table eav_entities (
 	foreign key (entity_type_id) references eav_entity_types(id)
 );
 
-create table eav_attrs (
+create table if not exists eav_attrs (
 	id int unsigned not null auto_increment,
 	created_at datetime not null default NOW(),
 	attr varchar(255) not null,
@@ -65,7 +59,7 @@ create table eav_attrs (
 	foreign key (entity_type_id) references eav_entity_types(id)
 );
 
-create table eav_values (
+create table if not exists eav_values (
 	id int unsigned not null auto_increment,
 	created_at datetime not null default NOW(),
 	entity_id int unsigned not null,
@@ -84,7 +78,7 @@ create table eav_values (
 -- ------- PROCEDURES ------- --
 -- -------------------------- --
 
--- delete old procedures
+-- replace procedures from previous setups
 drop procedure if exists create_eav_entity;
 drop procedure if exists create_eav_attr;
 drop procedure if exists create_eav_value;
@@ -92,7 +86,6 @@ drop procedure if exists delete_eav_entity;
 
 
 -- helper for creating new entities
-DELIMITER //
 create procedure create_eav_entity(entity_type_name varchar(255), entity_name varchar(255))
 begin
 	-- grab existing entity type
@@ -112,12 +105,10 @@ begin
 	end if;
 	-- create entity
 	insert into eav_entities (entity, entity_type_id) values(entity_name, et_id);
-end //
-DELIMITER ;
+end;
 
 
 -- helper for creating new attributes
-DELIMITER //
 create procedure create_eav_attr(attr_name varchar(255), attr_type varchar(255), attr_entity_type_id int unsigned, attr_allow_multiple bool)
 begin
 	-- validate inputs
@@ -142,12 +133,10 @@ begin
 		signal sqlstate '45000'
 		set message_text = 'ERR: Invalid value type';
 	end if;
-end //
-DELIMITER ;
+end;
 
 
 -- helper for creating new values
-DELIMITER //
 create procedure create_eav_value(entity_id int unsigned, attr_id int unsigned, v1 varchar(255), v2 int, v3 float, v4 datetime, v5 bool)
 begin
 	-- define variables
@@ -210,33 +199,24 @@ begin
 		end if;
 		insert into eav_values (entity_id, attr_id, value_bool) values (entity_id, attr_id, v5);
 	end if;
-end //
-DELIMITER ;
+end;
 
 
 -- helper for removing entities + all associated values
-DELIMITER //
 create procedure delete_eav_entity(entity_id int unsigned)
 begin
 	-- remove all entity values
 	delete from eav_values ev where ev.entity_id = entity_id;
 	-- remove entity
 	delete from eav_entities where id = entity_id;
-end //
-DELIMITER ;
+end;
 
 -- -------------------------- --
 -- ---------- VIEWS --------- --
 -- -------------------------- --
 
--- delete old views
-drop view if exists eav_schema_definitions;
-drop view if exists all_possible_eav_data;
-drop view if exists all_existing_eav_data;
-
-
 -- all schema definitions
-create view eav_schema_definitions as 
+create or replace view eav_schema_definitions as 
 select eet.id as entity_type_id, eet.entity_type, ea.id as attr_id, ea.attr, ea.value_type, ea.created_at, ea.allow_multiple
 from eav_entity_types eet 
 left join eav_attrs ea on eet.id = ea.entity_type_id
@@ -244,7 +224,7 @@ order by eet.id, ea.id;
 
 
 -- all data that can potentially be filled
-create view all_possible_eav_data as 
+create or replace view all_possible_eav_data as 
 select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
 ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple,
 ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool 
@@ -256,7 +236,7 @@ order by eet.id, ee.id;
 
 
 -- all existing EAV entries
-create view all_existing_eav_data as
+create or replace view all_existing_eav_data as
 select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
 ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple,
 ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool 
@@ -0,0 +1,81 @@
+-- ------------------------------------------- --
+-- soft delete: rows with deleted_at set are in the trash bin
+-- every query and view skips them until they are restored or purged
+-- ------------------------------------------- --
+
+alter table eav_entity_types add column deleted_at datetime(6) null;
+alter table eav_entities add column deleted_at datetime(6) null;
+alter table eav_attrs add column deleted_at datetime(6) null;
+alter table eav_values add column deleted_at datetime(6) null;
+
+-- -------------------------- --
+-- -------- TRIGGERS -------- --
+-- -------------------------- --
+
+-- nothing can be added below a trashed entity type, entity or attribute
+drop trigger if exists eav_entities_trashed_parent;
+drop trigger if exists eav_attrs_trashed_parent;
+drop trigger if exists eav_values_trashed_parent;
+
+create trigger eav_entities_trashed_parent before insert on eav_entities for each row
+begin
+	if exists (select * from eav_entity_types where id = new.entity_type_id and deleted_at is not null) then
+		signal sqlstate '45000'
+		set message_text = 'ERR: Parent is in the trash';
+	end if;
+end;
+
+create trigger eav_attrs_trashed_parent before insert on eav_attrs for each row
+begin
+	if exists (select * from eav_entity_types where id = new.entity_type_id and deleted_at is not null) then
+		signal sqlstate '45000'
+		set message_text = 'ERR: Parent is in the trash';
+	end if;
+end;
+
+create trigger eav_values_trashed_parent before insert on eav_values for each row
+begin
+	if exists (select * from eav_entities where id = new.entity_id and deleted_at is not null)
+	or exists (select * from eav_attrs where id = new.attr_id and deleted_at is not null) then
+		signal sqlstate '45000'
+		set message_text = 'ERR: Parent is in the trash';
+	end if;
+end;
+
+-- -------------------------- --
+-- ---------- VIEWS --------- --
+-- -------------------------- --
+
+-- all schema definitions
+create or replace view eav_schema_definitions as 
+select eet.id as entity_type_id, eet.entity_type, ea.id as attr_id, ea.attr, ea.value_type, ea.created_at, ea.allow_multiple
+from eav_entity_types eet 
+left join eav_attrs ea on eet.id = ea.entity_type_id and ea.deleted_at is null
+where eet.deleted_at is null
+order by eet.id, ea.id;
+
+
+-- all data that can potentially be filled
+create or replace view all_possible_eav_data as 
+select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
+ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple,
+ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool 
+from eav_entity_types eet
+left join eav_entities ee on eet.id = ee.entity_type_id and ee.deleted_at is null
+left join eav_attrs ea on ea.entity_type_id = eet.id and ea.deleted_at is null
+left join eav_values ev on ee.id = ev.entity_id and ea.id = ev.attr_id and ev.deleted_at is null
+where eet.deleted_at is null
+order by eet.id, ee.id;
+
+
+-- all existing EAV entries
+create or replace view all_existing_eav_data as
+select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
+ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple,
+ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool 
+from eav_values ev
+left join eav_entities ee on ev.entity_id = ee.id
+left join eav_entity_types eet on eet.id = ee.entity_type_id
+left join eav_attrs ea on ea.id = ev.attr_id
+where ev.deleted_at is null and ee.deleted_at is null and ea.deleted_at is null
+order by eet.id, ev.attr_id;
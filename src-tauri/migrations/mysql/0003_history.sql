@@ -0,0 +1,19 @@
+-- ------------------------------------------- --
+-- append-only change log, written by the app in the same transaction as the change
+-- old_data/new_data hold the row as json before and after the change, old_data is null on create and new_data on purge
+-- ------------------------------------------- --
+
+create table if not exists eav_history (
+	id int unsigned not null auto_increment,
+	changed_at datetime(6) not null,
+	action varchar(16) not null, -- create, update, delete, restore, purge
+	target varchar(16) not null, -- entity_type, entity, attr, value
+	target_id int unsigned not null,
+	entity_type_id int unsigned null,
+	entity_id int unsigned null,
+	old_data text null,
+	new_data text null,
+	primary key (id),
+	index (entity_id, changed_at),
+	index (entity_type_id, changed_at)
+);
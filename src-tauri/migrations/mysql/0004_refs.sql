@@ -0,0 +1,153 @@
+-- ------------------------------------------- --
+-- ref attributes link entities: a ref value keeps the id of the entity it points to in value_int
+-- ref_entity_type_id optionally limits the entities it can point to, on_delete says what happens
+-- to the value when that entity is deleted: block (the default), nullify or cascade
+-- ------------------------------------------- --
+
+alter table eav_attrs add column ref_entity_type_id int unsigned null;
+alter table eav_attrs add column on_delete varchar(16) null;
+alter table eav_attrs add foreign key (ref_entity_type_id) references eav_entity_types(id);
+
+-- backlinks look values up by the entity they point to
+create index eav_values_ref on eav_values (value_int, attr_id);
+
+-- -------------------------- --
+-- ------- PROCEDURES ------- --
+-- -------------------------- --
+
+drop procedure if exists create_eav_attr;
+drop procedure if exists create_eav_value;
+
+
+-- helper for creating new attributes
+create procedure create_eav_attr(attr_name varchar(255), attr_type varchar(255), attr_entity_type_id int unsigned, attr_allow_multiple bool)
+begin
+	-- validate inputs
+	if attr_entity_type_id is null then
+		signal sqlstate '45000'
+		set message_text = 'ERR: No entity type selected';
+	end if;
+
+	-- validate unique attribute
+	if exists (select * from eav_attrs where attr = attr_name and entity_type_id = attr_entity_type_id) then 
+		signal sqlstate '45000'
+		set message_text = 'ERR: Attribute already exists';
+	end if;
+	
+	-- validate attr value type
+	if attr_type in ('str', 'int', 'float', 'time', 'bool', 'ref')
+	then
+		-- perform action
+		insert into eav_attrs (attr, value_type, entity_type_id, allow_multiple)
+		values (attr_name, attr_type, attr_entity_type_id, attr_allow_multiple);
+	else
+		signal sqlstate '45000'
+		set message_text = 'ERR: Invalid value type';
+	end if;
+end;
+
+
+-- helper for creating new values, refs take the id of the entity they point to as v2
+create procedure create_eav_value(entity_id int unsigned, attr_id int unsigned, v1 varchar(255), v2 int, v3 float, v4 datetime, v5 bool)
+begin
+	-- define variables
+	declare et_id int unsigned;
+	declare allow_mul bool;
+	declare v_type varchar(255);
+	declare ref_et_id int unsigned;
+
+	-- select values into vars
+	select entity_type_id into et_id from eav_entities ee where ee.id = entity_id limit 1;
+	select value_type, allow_multiple, ref_entity_type_id into v_type, allow_mul, ref_et_id
+	from eav_attrs ea where ea.id = attr_id and ea.entity_type_id = et_id limit 1;
+
+	-- error checking
+	if et_id is null then
+		signal sqlstate '45000'
+		set message_text = 'ERR: Entity not found';
+	end if;
+	if v_type is null then
+		signal sqlstate '45000'
+		set message_text = 'ERR: Attribute not found';
+	end if;
+
+	if (allow_mul is null or allow_mul = 0) then
+		if exists (select * from eav_values ev where ev.entity_id = entity_id and ev.attr_id = attr_id and ev.deleted_at is null) then 
+			signal sqlstate '45000'
+			set message_text = 'ERR: Attribute does not allow multiple entries';
+		end if;
+	end if;
+
+	-- insert value into value table
+	if v_type = 'str' then
+		if v1 is null then
+			signal sqlstate '45000'
+			set message_text = 'ERR: String value not provided';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_str) values (entity_id, attr_id, v1);
+	elseif v_type = 'int' then
+		if v2 is null then
+			signal sqlstate '45000'
+			set message_text = 'ERR: int value not provided';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_int) values (entity_id, attr_id, v2);
+	elseif v_type = 'float' then
+		if v3 is null then
+			signal sqlstate '45000'
+			set message_text = 'ERR: float value not provided';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_float) values (entity_id, attr_id, v3);
+	elseif v_type = 'time' then
+		if v4 is null then
+			signal sqlstate '45000'
+			set message_text = 'ERR: time value not provided';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_time) values (entity_id, attr_id, v4);
+	elseif v_type = 'bool' then
+		if v5 is null then
+			signal sqlstate '45000'
+			set message_text = 'ERR: bool value not provided';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_bool) values (entity_id, attr_id, v5);
+	elseif v_type = 'ref' then
+		if not exists (select * from eav_entities ee where ee.id = v2 and ee.deleted_at is null
+			and (ref_et_id is null or ee.entity_type_id = ref_et_id)) then
+			signal sqlstate '45000'
+			set message_text = 'ERR: Referenced entity not found';
+		end if;
+		insert into eav_values (entity_id, attr_id, value_int) values (entity_id, attr_id, v2);
+	end if;
+end;
+
+-- -------------------------- --
+-- ---------- VIEWS --------- --
+-- -------------------------- --
+
+-- all data that can potentially be filled
+create or replace view all_possible_eav_data as 
+select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
+ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple, ea.ref_entity_type_id,
+ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool,
+er.entity as ref_entity
+from eav_entity_types eet
+left join eav_entities ee on eet.id = ee.entity_type_id and ee.deleted_at is null
+left join eav_attrs ea on ea.entity_type_id = eet.id and ea.deleted_at is null
+left join eav_values ev on ee.id = ev.entity_id and ea.id = ev.attr_id and ev.deleted_at is null
+left join eav_entities er on ea.value_type = 'ref' and er.id = ev.value_int and er.deleted_at is null
+where eet.deleted_at is null
+order by eet.id, ee.id;
+
+
+-- all existing EAV entries
+create or replace view all_existing_eav_data as
+select eet.id as entity_type_id, eet.entity_type, ee.id as entity_id, ee.entity,
+ea.id as attr_id, ea.attr, ea.value_type, ea.allow_multiple, ea.ref_entity_type_id,
+ev.id as value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool,
+er.entity as ref_entity
+from eav_values ev
+left join eav_entities ee on ev.entity_id = ee.id
+left join eav_entity_types eet on eet.id = ee.entity_type_id
+left join eav_attrs ea on ea.id = ev.attr_id
+left join eav_entities er on ea.value_type = 'ref' and er.id = ev.value_int and er.deleted_at is null
+where ev.deleted_at is null and ee.deleted_at is null and ea.deleted_at is null
+order by eet.id, ev.attr_id;
@@ -0,0 +1,68 @@
+-- base EAV tables, triggers stand in for the validation in the mysql procedures
+CREATE TABLE IF NOT EXISTS eav_entity_types (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	entity_type VARCHAR(255) UNIQUE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS eav_entities (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	entity VARCHAR(255) NOT NULL,
+	entity_type_id INTEGER NOT NULL REFERENCES eav_entity_types(id)
+);
+
+CREATE TABLE IF NOT EXISTS eav_attrs (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	attr VARCHAR(255) NOT NULL,
+	value_type VARCHAR(255) NOT NULL,
+	entity_type_id INTEGER NOT NULL REFERENCES eav_entity_types(id),
+	allow_multiple BOOLEAN NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS eav_values (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	entity_id INTEGER NOT NULL REFERENCES eav_entities(id),
+	attr_id INTEGER NOT NULL REFERENCES eav_attrs(id),
+	value_str VARCHAR(255),
+	value_int INT,
+	value_float FLOAT,
+	value_time DATETIME,
+	value_bool BOOLEAN
+);
+
+CREATE TRIGGER IF NOT EXISTS eav_entities_unique BEFORE INSERT ON eav_entities
+WHEN EXISTS (SELECT 1 FROM eav_entities WHERE entity = NEW.entity AND entity_type_id = NEW.entity_type_id)
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Entity already exists');
+END;
+
+CREATE TRIGGER IF NOT EXISTS eav_attrs_unique BEFORE INSERT ON eav_attrs
+WHEN EXISTS (SELECT 1 FROM eav_attrs WHERE attr = NEW.attr AND entity_type_id = NEW.entity_type_id)
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Attribute already exists');
+END;
+
+CREATE TRIGGER IF NOT EXISTS eav_attrs_value_type BEFORE INSERT ON eav_attrs
+WHEN NEW.value_type NOT IN ('str', 'int', 'float', 'time', 'bool')
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Invalid value type');
+END;
+
+CREATE VIEW IF NOT EXISTS eav_schema_definitions AS
+SELECT eet.id AS entity_type_id, eet.entity_type, ea.id AS attr_id, ea.attr, ea.value_type, ea.created_at, ea.allow_multiple
+FROM eav_entity_types eet
+LEFT JOIN eav_attrs ea ON eet.id = ea.entity_type_id
+ORDER BY eet.id, ea.id;
+
+CREATE VIEW IF NOT EXISTS all_existing_eav_data AS
+SELECT eet.id AS entity_type_id, eet.entity_type, ee.id AS entity_id, ee.entity,
+ea.id AS attr_id, ea.attr, ea.value_type, ea.allow_multiple,
+ev.id AS value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool
+FROM eav_values ev
+LEFT JOIN eav_entities ee ON ev.entity_id = ee.id
+LEFT JOIN eav_entity_types eet ON eet.id = ee.entity_type_id
+LEFT JOIN eav_attrs ea ON ea.id = ev.attr_id
+ORDER BY eet.id, ev.attr_id;
@@ -0,0 +1,46 @@
+-- soft delete: rows with deleted_at set are in the trash bin,
+-- every query and view skips them until they are restored or purged
+ALTER TABLE eav_entity_types ADD COLUMN deleted_at DATETIME;
+ALTER TABLE eav_entities ADD COLUMN deleted_at DATETIME;
+ALTER TABLE eav_attrs ADD COLUMN deleted_at DATETIME;
+ALTER TABLE eav_values ADD COLUMN deleted_at DATETIME;
+
+-- nothing can be added below a trashed entity type, entity or attribute
+CREATE TRIGGER IF NOT EXISTS eav_entities_trashed_parent BEFORE INSERT ON eav_entities
+WHEN EXISTS (SELECT 1 FROM eav_entity_types WHERE id = NEW.entity_type_id AND deleted_at IS NOT NULL)
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Parent is in the trash');
+END;
+
+CREATE TRIGGER IF NOT EXISTS eav_attrs_trashed_parent BEFORE INSERT ON eav_attrs
+WHEN EXISTS (SELECT 1 FROM eav_entity_types WHERE id = NEW.entity_type_id AND deleted_at IS NOT NULL)
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Parent is in the trash');
+END;
+
+CREATE TRIGGER IF NOT EXISTS eav_values_trashed_parent BEFORE INSERT ON eav_values
+WHEN EXISTS (SELECT 1 FROM eav_entities WHERE id = NEW.entity_id AND deleted_at IS NOT NULL)
+OR EXISTS (SELECT 1 FROM eav_attrs WHERE id = NEW.attr_id AND deleted_at IS NOT NULL)
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Parent is in the trash');
+END;
+
+DROP VIEW IF EXISTS eav_schema_definitions;
+CREATE VIEW eav_schema_definitions AS
+SELECT eet.id AS entity_type_id, eet.entity_type, ea.id AS attr_id, ea.attr, ea.value_type, ea.created_at, ea.allow_multiple
+FROM eav_entity_types eet
+LEFT JOIN eav_attrs ea ON eet.id = ea.entity_type_id AND ea.deleted_at IS NULL
+WHERE eet.deleted_at IS NULL
+ORDER BY eet.id, ea.id;
+
+DROP VIEW IF EXISTS all_existing_eav_data;
+CREATE VIEW all_existing_eav_data AS
+SELECT eet.id AS entity_type_id, eet.entity_type, ee.id AS entity_id, ee.entity,
+ea.id AS attr_id, ea.attr, ea.value_type, ea.allow_multiple,
+ev.id AS value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool
+FROM eav_values ev
+LEFT JOIN eav_entities ee ON ev.entity_id = ee.id
+LEFT JOIN eav_entity_types eet ON eet.id = ee.entity_type_id
+LEFT JOIN eav_attrs ea ON ea.id = ev.attr_id
+WHERE ev.deleted_at IS NULL AND ee.deleted_at IS NULL AND ea.deleted_at IS NULL
+ORDER BY eet.id, ev.attr_id;
@@ -0,0 +1,16 @@
+-- append-only change log, written by the app in the same transaction as the change
+-- old_data/new_data hold the row as json before and after the change, old_data is null on create and new_data on purge
+CREATE TABLE IF NOT EXISTS eav_history (
+	id INTEGER PRIMARY KEY AUTOINCREMENT,
+	changed_at DATETIME NOT NULL,
+	action VARCHAR(16) NOT NULL,
+	target VARCHAR(16) NOT NULL,
+	target_id INTEGER NOT NULL,
+	entity_type_id INTEGER,
+	entity_id INTEGER,
+	old_data TEXT,
+	new_data TEXT
+);
+
+CREATE INDEX IF NOT EXISTS eav_history_entity ON eav_history (entity_id, changed_at);
+CREATE INDEX IF NOT EXISTS eav_history_entity_type ON eav_history (entity_type_id, changed_at);
@@ -0,0 +1,29 @@
+-- ref attributes link entities: a ref value keeps the id of the entity it points to in value_int.
+-- ref_entity_type_id optionally limits the entities it can point to, on_delete says what happens
+-- to the value when that entity is deleted: block (the default), nullify or cascade
+ALTER TABLE eav_attrs ADD COLUMN ref_entity_type_id INTEGER REFERENCES eav_entity_types(id);
+ALTER TABLE eav_attrs ADD COLUMN on_delete VARCHAR(16);
+
+-- backlinks look values up by the entity they point to
+CREATE INDEX IF NOT EXISTS eav_values_ref ON eav_values (value_int, attr_id);
+
+DROP TRIGGER IF EXISTS eav_attrs_value_type;
+CREATE TRIGGER eav_attrs_value_type BEFORE INSERT ON eav_attrs
+WHEN NEW.value_type NOT IN ('str', 'int', 'float', 'time', 'bool', 'ref')
+BEGIN
+	SELECT RAISE(ABORT, 'ERR: Invalid value type');
+END;
+
+DROP VIEW IF EXISTS all_existing_eav_data;
+CREATE VIEW all_existing_eav_data AS
+SELECT eet.id AS entity_type_id, eet.entity_type, ee.id AS entity_id, ee.entity,
+ea.id AS attr_id, ea.attr, ea.value_type, ea.allow_multiple, ea.ref_entity_type_id,
+ev.id AS value_id, ev.created_at, ev.value_str, ev.value_int, ev.value_float, ev.value_time, ev.value_bool,
+er.entity AS ref_entity
+FROM eav_values ev
+LEFT JOIN eav_entities ee ON ev.entity_id = ee.id
+LEFT JOIN eav_entity_types eet ON eet.id = ee.entity_type_id
+LEFT JOIN eav_attrs ea ON ea.id = ev.attr_id
+LEFT JOIN eav_entities er ON ea.value_type = 'ref' AND er.id = ev.value_int AND er.deleted_at IS NULL
+WHERE ev.deleted_at IS NULL AND ee.deleted_at IS NULL AND ea.deleted_at IS NULL
+ORDER BY eet.id, ev.attr_id;
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::{migrate::Migrator, mysql::{MySqlConnection, MySqlPoolOptions}, MySql};
+
+use crate::db_store::{run_migrations, Dialect, Int, SqlStore};
+use crate::eav_error::EavError;
+
+pub type MySqlStore = SqlStore<MySql>;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+
+pub async fn connect(database_url: &str) -> Result<MySqlStore, sqlx::Error> {
+	let pool = MySqlPoolOptions::new()
+		.max_connections(4)
+		.acquire_timeout(Duration::from_secs(2))
+		.connect(database_url)
+		.await?;
+	run_migrations(&MIGRATOR, &pool).await?;
+	Ok(SqlStore::new(pool))
+}
+
+// relies on the procedures from migrations/mysql
+#[async_trait]
+impl Dialect for MySql {
+	fn rows_affected(res: &Self::QueryResult) -> u64 {
+		res.rows_affected()
+	}
+
+	fn last_insert_id(res: &Self::QueryResult) -> u32 {
+		res.last_insert_id() as u32
+	}
+
+	// REGEXP is already case insensitive with the default collation,
+	// invalid patterns are reported by the server
+	fn regex_pattern(regex: &str) -> Result<String, EavError> {
+		Ok(regex.to_owned())
+	}
+
+	fn regexp(col: &str) -> String {
+		col.to_owned() + " REGEXP ?"
+	}
+
+	fn datetime(expr: &str) -> String {
+		expr.to_owned()
+	}
+
+	fn null_safe_eq(a: &str, b: &str) -> String {
+		a.to_owned() + " <=> " + b
+	}
+
+	async fn insert_entity(conn: &mut Self::Connection, entity_type: &str, entity: &str) -> Result<u32, sqlx::Error> {
+		sqlx::query("CALL create_eav_entity(?, ?)").bind(entity_type).bind(entity).execute(&mut *conn).await?;
+		let id = procedure_insert_id(conn).await?;
+		println!("insert_entity: {}", id);
+		Ok(id)
+	}
+
+	async fn insert_attr(
+		conn: &mut Self::Connection, entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool
+	) -> Result<u32, sqlx::Error> {
+		sqlx::query("CALL create_eav_attr(?, ?, ?, ?)")
+			.bind(attr_name).bind(attr_type).bind(entity_type_id).bind(allow_multiple)
+			.execute(&mut *conn).await?;
+		let id = procedure_insert_id(conn).await?;
+		println!("insert_attr: {}", id);
+		Ok(id)
+	}
+}
+
+// the OK packet of a CALL doesn't carry the ids generated inside the procedure,
+// but last_insert_id() is per connection so it is safe on the same connection
+async fn procedure_insert_id(conn: &mut MySqlConnection) -> Result<u32, sqlx::Error> {
+	let id = sqlx::query_as::<_, Int>("SELECT last_insert_id()").fetch_one(&mut *conn).await?;
+	Ok(id.0)
+}
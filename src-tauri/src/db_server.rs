@@ -0,0 +1,128 @@
+use std::fs::{self, OpenOptions};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use sqlx::{Connection, MySqlConnection};
+
+use crate::config::ServerConfig;
+use crate::eav_error::{EavError, ErrorCode};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// mysqld process owned by the app, only set when we launched it ourselves
+#[derive(Debug)]
+pub struct DbServer {
+	config: ServerConfig,
+	database_url: String,
+	child: Option<Child>,
+}
+
+impl DbServer {
+	// launches mysqld unless it is unmanaged or something already listens on the port
+	pub fn start(config: ServerConfig, database_url: &str) -> Result<Self, EavError> {
+		let mut server = DbServer { config, database_url: database_url.to_owned(), child: None };
+		if !server.config.managed {
+			println!("mysqld is not managed by the app - skipping launch");
+			return Ok(server);
+		}
+		if server.is_reachable() {
+			println!("mysqld already reachable on {}:{} - skipping launch", server.config.host, server.config.port);
+			return Ok(server);
+		}
+		// send server output to a log file instead of a console window
+		if let Some(dir) = server.config.log_file.parent() {
+			let _ = fs::create_dir_all(dir);
+		}
+		let log = OpenOptions::new().create(true).append(true).open(&server.config.log_file)
+			.map_err(|e| server_error(&format!("Failed to open server log: {}", e)))?;
+		let log_err = log.try_clone()
+			.map_err(|e| server_error(&format!("Failed to open server log: {}", e)))?;
+
+		let mut cmd = Command::new(&server.config.mysqld_path);
+		// windows mysqld only writes to stderr with --console, otherwise it logs to the data dir
+		#[cfg(windows)]
+		cmd.arg("--console");
+		cmd.arg(format!("--port={}", server.config.port));
+		if let Some(dir) = &server.config.data_dir {
+			cmd.arg(format!("--datadir={}", dir.display()));
+		}
+		let child = cmd.args(&server.config.args)
+			.stdin(Stdio::null())
+			.stdout(Stdio::from(log))
+			.stderr(Stdio::from(log_err))
+			.spawn()
+			.map_err(|e| server_error(&format!("Failed to launch {}: {}", server.config.mysqld_path.display(), e)))?;
+		println!("Launched mysqld (pid {}), logging to {}", child.id(), server.config.log_file.display());
+		server.child = Some(child);
+		Ok(server)
+	}
+
+	pub fn is_reachable(&self) -> bool {
+		let addrs = match (self.config.host.as_str(), self.config.port).to_socket_addrs() {
+			Ok(addrs) => addrs,
+			Err(_) => return false
+		};
+		addrs.into_iter().any(|addr| TcpStream::connect_timeout(&addr, POLL_INTERVAL).is_ok())
+	}
+
+	// polls the port until the server accepts connections, fails early if mysqld exits
+	pub async fn wait_ready(&mut self) -> Result<(), EavError> {
+		let deadline = Instant::now() + self.config.startup_timeout;
+		loop {
+			if self.is_reachable() {
+				return Ok(());
+			}
+			if let Some(child) = self.child.as_mut() {
+				if let Ok(Some(status)) = child.try_wait() {
+					self.child = None;
+					return Err(server_error(&format!(
+						"mysqld exited during startup ({}), see {}", status, self.config.log_file.display()
+					)));
+				}
+			}
+			if Instant::now() >= deadline {
+				return Err(server_error(&format!(
+					"mysqld not reachable on {}:{} after {}s",
+					self.config.host, self.config.port, self.config.startup_timeout.as_secs()
+				)));
+			}
+			async_std::task::sleep(POLL_INTERVAL).await;
+		}
+	}
+
+	// asks the server to shut down cleanly and only kills it if it does not exit in time
+	pub async fn shutdown(&mut self) {
+		let mut child = match self.child.take() {
+			Some(child) => child,
+			None => return
+		};
+		if let Err(e) = self.request_shutdown().await {
+			println!("SHUTDOWN statement failed: {}", e);
+			#[cfg(unix)]
+			let _ = Command::new("kill").arg("-TERM").arg(child.id().to_string()).status();
+		}
+		let deadline = Instant::now() + self.config.startup_timeout;
+		while Instant::now() < deadline {
+			if let Ok(Some(status)) = child.try_wait() {
+				println!("mysqld stopped ({})", status);
+				return;
+			}
+			async_std::task::sleep(POLL_INTERVAL).await;
+		}
+		println!("mysqld did not stop in time - killing it");
+		if let Err(e) = child.kill().and_then(|_| child.wait()) {
+			println!("Failed to kill mysqld: {}", e);
+		}
+	}
+
+	async fn request_shutdown(&self) -> Result<(), sqlx::Error> {
+		let mut conn = MySqlConnection::connect(&self.database_url).await?;
+		sqlx::query("SHUTDOWN").execute(&mut conn).await?;
+		Ok(())
+	}
+}
+
+fn server_error(msg: &str) -> EavError {
+	EavError::new(ErrorCode::ServerUnavailable, msg)
+}
@@ -0,0 +1,79 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use regex::Regex;
+use sqlx::{migrate::Migrator, sqlite::{SqliteConnectOptions, SqlitePoolOptions}, Sqlite};
+
+use crate::db_store::{run_migrations, Dialect, SqlStore};
+use crate::eav_error::{EavError, ErrorCode};
+
+pub type SqliteStore = SqlStore<Sqlite>;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+pub async fn connect(database_url: &str) -> Result<SqliteStore, sqlx::Error> {
+	let options = SqliteConnectOptions::from_str(database_url)?
+		.create_if_missing(true)
+		.with_regexp();
+	let pool = SqlitePoolOptions::new()
+		.max_connections(1)
+		.acquire_timeout(Duration::from_secs(2))
+		.connect_with(options)
+		.await?;
+	run_migrations(&MIGRATOR, &pool).await?;
+	Ok(SqlStore::new(pool))
+}
+
+#[async_trait]
+impl Dialect for Sqlite {
+	fn rows_affected(res: &Self::QueryResult) -> u64 {
+		res.rows_affected()
+	}
+
+	fn last_insert_id(res: &Self::QueryResult) -> u32 {
+		res.last_insert_rowid() as u32
+	}
+
+	// regexp() from sqlx is case sensitive by default and skips invalid patterns silently,
+	// so compile the pattern here to report errors
+	fn regex_pattern(regex: &str) -> Result<String, EavError> {
+		let pattern = "(?i)".to_owned() + regex;
+		match Regex::new(&pattern) {
+			Ok(_) => Ok(pattern),
+			Err(e) => Err(EavError::new(ErrorCode::InvalidRegex, &e.to_string()).with_field("regex"))
+		}
+	}
+
+	// regexp() only matches text values
+	fn regexp(col: &str) -> String {
+		"CAST(".to_owned() + col + " AS TEXT) REGEXP ?"
+	}
+
+	// timestamps are stored as text, CURRENT_TIMESTAMP and bound chrono values use different formats
+	fn datetime(expr: &str) -> String {
+		"datetime(".to_owned() + expr + ")"
+	}
+
+	fn null_safe_eq(a: &str, b: &str) -> String {
+		a.to_owned() + " IS " + b
+	}
+
+	async fn insert_entity(conn: &mut Self::Connection, entity_type: &str, entity: &str) -> Result<u32, sqlx::Error> {
+		// create entity type if not exist
+		sqlx::query("INSERT OR IGNORE INTO eav_entity_types (entity_type) VALUES (?)")
+			.bind(entity_type).execute(&mut *conn).await?;
+		let res = sqlx::query("INSERT INTO eav_entities (entity, entity_type_id) SELECT ?, id FROM eav_entity_types WHERE entity_type = ?")
+			.bind(entity).bind(entity_type).execute(&mut *conn).await?;
+		Ok(Self::last_insert_id(&res))
+	}
+
+	async fn insert_attr(
+		conn: &mut Self::Connection, entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool
+	) -> Result<u32, sqlx::Error> {
+		let res = sqlx::query("INSERT INTO eav_attrs (attr, value_type, entity_type_id, allow_multiple) VALUES (?, ?, ?, ?)")
+			.bind(attr_name).bind(attr_type).bind(entity_type_id).bind(allow_multiple)
+			.execute(&mut *conn).await?;
+		Ok(Self::last_insert_id(&res))
+	}
+}
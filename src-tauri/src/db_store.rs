@@ -0,0 +1,2632 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{database::HasArguments, query::QueryAs, Database, Encode, Executor, FromRow, IntoArguments, Pool, Type};
+use sqlx::migrate::{Migrate, Migrator};
+
+use crate::db_interface::TrashKind;
+use crate::eav_archive::{self, ArchiveLine, ArchiveRecord};
+use crate::eav_error::{EavError, ErrorCode};
+use crate::eav_import::{self, ImportPlan, VALUE_TYPES};
+use crate::eav_query::{check_comparison, parse_bool, DateSpan, Expr, Operand, Operator};
+use crate::eav_structs::{
+	ArchiveReport, ArchiveSummary, AttrMapping, AttrMove, AttrStrategy, CloneOptions, ConvertFailure, ConvertPolicy,
+	ConvertReport, DeleteSummary, DroppedValue, EavAttribute, EavCell, EavEntityType, EavEntity, EavHistory, EavTable,
+	EavValue, EavView, ImportError, ImportReport, KeepValue, MergeConflict, MergeReport, MergeStrategy, MoveReport, OnDelete,
+	Page, PageRequest, SortBy, SortKey, TableCell, TableRow, Tracked, TrashItem
+};
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Int(pub u32);
+
+#[derive(Debug, sqlx::FromRow)]
+pub struct Count(pub i64);
+
+// storage operations for the EAV tables, one implementation per database backend
+#[async_trait]
+pub trait EavStore: Send + Sync + std::fmt::Debug {
+	// -- ENTITY TYPES --
+	async fn fetch_entity_types(&self) -> Result<Vec<EavEntityType>, EavError>;
+	async fn create_entity_type(&self, name: &str) -> Result<EavEntityType, EavError>;
+	async fn rename_entity_type(&self, id: u32, name: &str) -> Result<EavEntityType, EavError>;
+	// trashes the entity type with everything in it, its entities go like in delete_entity
+	async fn delete_entity_type(&self, id: u32) -> Result<DeleteSummary, EavError>;
+
+	// -- ENTITIES --
+	async fn fetch_entities(
+		&self, entity_type_id: u32, sort: &[SortKey], page: &PageRequest
+	) -> Result<Page<EavEntity>, EavError>;
+	async fn fetch_entity_by_id(&self, id: u32) -> Result<EavEntity, EavError>;
+	async fn create_entity(&self, entity_type: &str, entity: &str) -> Result<EavEntity, EavError>;
+	async fn rename_entity(&self, id: u32, name: &str) -> Result<EavEntity, EavError>;
+	// copies the entity and its live values into the same entity type
+	async fn clone_entity(&self, id: u32, options: &CloneOptions) -> Result<EavEntity, EavError>;
+	// moves the entity and its live values to another entity type. each source attr goes to the target attr given
+	// in `mapping`, else to the one with the same name, which `create_missing` adds when there is none. values without
+	// a target, that don't convert to its type or past the first of a single value target are trashed
+	async fn move_entity(
+		&self, id: u32, entity_type_id: u32, mapping: &[AttrMapping], create_missing: bool, dry_run: bool
+	) -> Result<MoveReport, EavError>;
+	// trashes the entity with its values, ref values pointing at it follow the on_delete rule of their attr
+	async fn delete_entity(&self, id: u32) -> Result<DeleteSummary, EavError>;
+	// folds the source entity into the target of the same entity type and trashes it. values of multi value attrs
+	// are added unless the target has the same one, single value attrs follow `strategies` (keep target by default).
+	// ref values pointing at the source point at the target afterwards
+	async fn merge_entities(
+		&self, source_id: u32, target_id: u32, strategies: &[AttrStrategy], dry_run: bool
+	) -> Result<MergeReport, EavError>;
+	async fn search(&self, query: &Expr, sort: &[SortKey], page: &PageRequest) -> Result<Page<EavEntity>, EavError>;
+	// one row per entity of the type with a cell per attribute, `filter` narrows the rows like a search
+	async fn fetch_table(
+		&self, entity_type_id: u32, filter: Option<&Expr>, sort: &[SortKey], page: &PageRequest
+	) -> Result<EavTable, EavError>;
+
+	// -- ATTRIBUTES --
+	async fn fetch_attrs(&self, entity_type_id: u32, multi_only: bool) -> Result<Vec<EavAttribute>, EavError>;
+	async fn fetch_attr_by_id(&self, id: u32) -> Result<EavAttribute, EavError>;
+	// ref attrs get their target category and delete rule right away, other types take neither
+	async fn create_attr(&self,
+		entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool,
+		ref_entity_type_id: Option<u32>, on_delete: Option<OnDelete>
+	) -> Result<EavAttribute, EavError>;
+	async fn rename_attr(&self, id: u32, name: &str) -> Result<EavAttribute, EavError>;
+	// turning allow_multiple off fails while an entity holds several values, unless `keep` says which one stays
+	async fn set_attr_allow_multiple(&self, id: u32, allow_multiple: bool, keep: Option<KeepValue>) -> Result<EavAttribute, EavError>;
+	// category the entities of a ref attr have to be in (any with None) and what deleting one of them does
+	async fn set_attr_ref(&self, id: u32, ref_entity_type_id: Option<u32>, on_delete: OnDelete) -> Result<EavAttribute, EavError>;
+	// rewrites the values in the columns of `value_type`, trashed ones too so they still fit when restored.
+	// values that don't convert are handled by `on_failure`, rolled back on a dry run or when aborted
+	async fn convert_attr_type(
+		&self, id: u32, value_type: &str, on_failure: ConvertPolicy, dry_run: bool
+	) -> Result<ConvertReport, EavError>;
+	async fn delete_attr(&self, id: u32) -> Result<DeleteSummary, EavError>;
+
+	// -- VALUES --
+	async fn fetch_value_by_id(&self, id: u32) -> Result<EavValue, EavError>;
+	async fn create_value(&self, input: EavValue) -> Result<EavValue, EavError>;
+	async fn update_value(&self, input: EavValue) -> Result<EavValue, EavError>;
+	async fn delete_value(&self, id: u32) -> Result<DeleteSummary, EavError>;
+
+	// -- VIEWS --
+	async fn fetch_views_by_entity_id(&self, entity_id: u32, page: u32) -> Result<Vec<EavView>, EavError>;
+	// ref values pointing at the entity, each with the entity holding it
+	async fn fetch_backlinks(&self, entity_id: u32, page: u32) -> Result<Vec<EavView>, EavError>;
+
+	// -- HISTORY --
+	async fn fetch_entity_history(&self, entity_id: u32, page: &PageRequest) -> Result<Page<EavHistory>, EavError>;
+	async fn fetch_views_at(&self, entity_id: u32, at: DateTime<Utc>) -> Result<Vec<EavView>, EavError>;
+	async fn last_history_id(&self) -> Result<u32, EavError>;
+	// writes back the rows changed by the history entries after_id < id <= to_id,
+	// their old contents when undoing and their new contents when redoing
+	async fn revert_history(&self, after_id: u32, to_id: u32, undo: bool) -> Result<Vec<EavHistory>, EavError>;
+
+	// -- TRASH --
+	async fn fetch_trash(&self, page: &PageRequest) -> Result<Page<TrashItem>, EavError>;
+	async fn restore_from_trash(&self, kind: TrashKind, id: u32) -> Result<DeleteSummary, EavError>;
+	async fn purge_trash(&self, max_age_days: u32) -> Result<DeleteSummary, EavError>;
+
+	// -- IMPORT --
+	// creates the planned attrs, entities and values in one transaction,
+	// which is rolled back on a dry run or when any row failed
+	async fn import_rows(&self, entity_type_id: u32, plan: ImportPlan, dry_run: bool) -> Result<ImportReport, EavError>;
+
+	// -- ARCHIVE --
+	// writes every row, trashed ones included, to `out` as archive lines
+	async fn export_archive(&self, out: &mut (dyn Write + Send)) -> Result<ArchiveSummary, EavError>;
+	// adds the archived rows in one transaction with new ids, which is rolled back on a dry run.
+	// with `replace` every row and the whole history are deleted first, so the database ends up as archived
+	async fn import_archive(
+		&self, records: &mut (dyn Iterator<Item = Result<ArchiveLine, EavError>> + Send), dry_run: bool, replace: bool
+	) -> Result<ArchiveReport, EavError>;
+}
+
+// backend specific sql, everything else in SqlStore is shared between databases
+#[async_trait]
+pub trait Dialect: Database {
+	fn rows_affected(res: &Self::QueryResult) -> u64;
+
+	// id generated by an INSERT, read from the result so it can't be mixed up between connections
+	fn last_insert_id(res: &Self::QueryResult) -> u32;
+
+	// REGEXP should be case insensitive on every backend, fails on patterns the backend can't compile
+	fn regex_pattern(regex: &str) -> Result<String, EavError>;
+
+	// expression matching `col` against a bound regex pattern
+	fn regexp(col: &str) -> String;
+
+	// wraps a datetime column or parameter so they compare as datetimes
+	fn datetime(expr: &str) -> String;
+
+	// equality that also holds when both sides are NULL
+	fn null_safe_eq(a: &str, b: &str) -> String;
+
+	// creates the entity (and its entity type if missing), returns the new entity id
+	async fn insert_entity(conn: &mut Self::Connection, entity_type: &str, entity: &str) -> Result<u32, sqlx::Error>;
+
+	// creates the attribute after validating name and value type, returns the new attr id
+	async fn insert_attr(
+		conn: &mut Self::Connection, entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool
+	) -> Result<u32, sqlx::Error>;
+}
+
+pub struct SqlStore<DB: Database> {
+	pool: Pool<DB>
+}
+
+impl<DB: Database> std::fmt::Debug for SqlStore<DB> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SqlStore").field("pool", &self.pool).finish()
+	}
+}
+
+impl<DB: Database> SqlStore<DB> {
+	pub fn new(pool: Pool<DB>) -> Self {
+		SqlStore { pool }
+	}
+}
+
+// brings the schema up to date, migrations that already ran are never repeated
+pub async fn run_migrations<DB>(migrator: &Migrator, pool: &Pool<DB>) -> Result<(), sqlx::Error>
+where
+	DB: Database,
+	DB::Connection: Migrate,
+{
+	let version = {
+		let mut conn = pool.acquire().await?;
+		conn.ensure_migrations_table().await?;
+		let applied = conn.list_applied_migrations().await?;
+		applied.iter().map(|m| m.version).max().unwrap_or(0)
+	};
+	let latest = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+	if version < latest {
+		println!("Migrating schema from version {} to {}", version, latest);
+	}
+	migrator.run(pool).await?;
+	println!("Schema version: {}", latest);
+	Ok(())
+}
+
+fn page_to_limit(page: u32, per_page: Option<u32>) -> String {
+	let limit = per_page.unwrap_or(100);
+	let skip = match page {
+		0 => 0, // handle page 0 exception
+		_ => (page - 1) * limit
+	};
+	" LIMIT ".to_owned() + &limit.to_string() + " OFFSET " + &skip.to_string()
+}
+
+// parameters of a compiled search query, bound in the order they were pushed
+#[derive(Clone)]
+enum QueryParam {
+	Str(String),
+	Int(i64),
+	Float(f64),
+	Bool(bool),
+	Time(DateTime<Utc>),
+}
+
+// one ORDER BY term, `{row}` stands for the table alias so the term can also be evaluated for the cursor row
+struct OrderTerm {
+	sql: String,
+	desc: bool,
+	params: Vec<QueryParam>,
+}
+
+impl OrderTerm {
+	fn new(sql: &str, desc: bool) -> Self {
+		OrderTerm { sql: sql.to_owned(), desc, params: Vec::new() }
+	}
+}
+
+// how the rows of a page are ordered. keyset orders end in the unique id of `table` and
+// continue right after the row a cursor points at, other orders only page by offset
+enum PageOrder<'a> {
+	Keyset { table: &'a str, alias: &'a str, terms: Vec<OrderTerm> },
+	By(&'a str),
+}
+
+// most sort keys an entity list can be ordered by
+const MAX_SORT_KEYS: usize = 5;
+
+// values of the sorted attribute for the entity in `{row}`
+const SORT_VALUES: &str = " FROM eav_values v JOIN eav_attrs a ON a.id = v.attr_id \
+	WHERE v.entity_id = {row}.id AND v.deleted_at IS NULL AND a.deleted_at IS NULL AND a.attr = ?";
+
+// order of eav_entities for a sort spec, ties and unsorted lists fall back to the id.
+// attribute values are ranked numbers, times, text (refs by the name of their entity) and then bools so every rank compares one column,
+// entities without a value come last in both directions. multiple values sort by the first one in sort order
+fn sort_terms<DB: Dialect>(sort: &[SortKey]) -> Result<Vec<OrderTerm>, EavError> {
+	if sort.len() > MAX_SORT_KEYS {
+		return Err(EavError::new(ErrorCode::InvalidArgument, &format!("At most {} sort keys are allowed", MAX_SORT_KEYS)).with_field("sort"));
+	}
+	let mut terms = Vec::new();
+	for key in sort {
+		match &key.by {
+			SortBy::Entity => terms.push(OrderTerm::new("LOWER({row}.entity)", key.desc)),
+			SortBy::CreatedAt => terms.push(OrderTerm::new(&DB::datetime("{row}.created_at"), key.desc)),
+			SortBy::Attr(attr) => {
+				if attr.trim().is_empty() {
+					return Err(EavError::new(ErrorCode::InvalidArgument, "Sort attribute is empty").with_field("sort"));
+				}
+				let agg = if key.desc { "MAX(" } else { "MIN(" };
+				let time = "CASE WHEN a.value_type = 'time' THEN ".to_owned() + &DB::datetime("v.value_time") + " END";
+				let ranked = [
+					("MIN(CASE a.value_type WHEN 'int' THEN 0 WHEN 'float' THEN 0 WHEN 'time' THEN 1 WHEN 'str' THEN 2 WHEN 'ref' THEN 2 ELSE 3 END)", false),
+					(&(agg.to_owned() + "CASE a.value_type WHEN 'int' THEN v.value_int WHEN 'float' THEN v.value_float END)"), key.desc),
+					(&(agg.to_owned() + &time + ")"), key.desc),
+					(&(agg.to_owned() + "CASE a.value_type WHEN 'str' THEN LOWER(v.value_str) " +
+						"WHEN 'ref' THEN (SELECT LOWER(r.entity) FROM eav_entities r WHERE r.id = v.value_int) END)"), key.desc),
+					(&(agg.to_owned() + "CASE WHEN a.value_type = 'bool' THEN v.value_bool END)"), key.desc),
+				];
+				for (i, (col, desc)) in ranked.into_iter().enumerate() {
+					let sub = "(SELECT ".to_owned() + col + SORT_VALUES + ")";
+					// the rank of entities without a value is 4
+					let sql = if i == 0 { "COALESCE(".to_owned() + &sub + ", 4)" } else { sub };
+					terms.push(OrderTerm { sql, desc, params: vec![QueryParam::Str(attr.clone())] });
+				}
+			}
+		}
+	}
+	terms.push(OrderTerm::new("{row}.id", false));
+	Ok(terms)
+}
+
+// rows after the cursor row `cur` in keyset order: t1 > c1 OR (t1 = c1 AND (t2 > c2 OR ...))
+fn keyset_cond<DB: Dialect>(table: &str, alias: &str, terms: &[OrderTerm], cursor: u32, params: &mut Vec<QueryParam>) -> String {
+	let (term, rest) = match terms.split_first() {
+		Some(split) => split,
+		None => return "1 = 0".to_owned()
+	};
+	let row = term.sql.replace("{row}", alias);
+	let cur = "(SELECT ".to_owned() + &term.sql.replace("{row}", "cur") + " FROM " + table + " cur WHERE cur.id = ?)";
+	let bind = |params: &mut Vec<QueryParam>| {
+		params.extend(term.params.iter().cloned());
+		params.extend(term.params.iter().cloned());
+		params.push(QueryParam::Int(cursor as i64));
+	};
+	bind(params);
+	let after = row.clone() + if term.desc { " < " } else { " > " } + &cur;
+	if rest.is_empty() { return after; }
+	bind(params);
+	let same = DB::null_safe_eq(&row, &cur);
+	"(".to_owned() + &after + " OR (" + &same + " AND " + &keyset_cond::<DB>(table, alias, rest, cursor, params) + "))"
+}
+
+const SEARCH_VALUES: &str = "EXISTS (SELECT 1 FROM eav_values v JOIN eav_attrs a ON a.id = v.attr_id \
+	WHERE v.entity_id = e.id AND v.deleted_at IS NULL AND a.deleted_at IS NULL AND a.attr = ?";
+
+// turns a parsed search query into a condition on eav_entities `e` joined with eav_entity_types `t`
+fn compile_query<DB: Dialect>(expr: &Expr, params: &mut Vec<QueryParam>) -> Result<String, EavError> {
+	let sql = match expr {
+		Expr::And(a, b) => "(".to_owned() + &compile_query::<DB>(a, params)? + " AND " + &compile_query::<DB>(b, params)? + ")",
+		Expr::Or(a, b) => "(".to_owned() + &compile_query::<DB>(a, params)? + " OR " + &compile_query::<DB>(b, params)? + ")",
+		Expr::Not(a) => "NOT ".to_owned() + &compile_query::<DB>(a, params)?,
+		// entity name or its alt_title
+		Expr::Text { text, literal, pos } => {
+			let pattern = if *literal { regex::escape(text) } else { text.clone() };
+			let pattern = DB::regex_pattern(&pattern).map_err(|e| e.with_field("query").with_position(*pos))?;
+			params.push(QueryParam::Str(pattern.clone()));
+			params.push(QueryParam::Str("alt_title".to_owned()));
+			params.push(QueryParam::Str(pattern));
+			"(".to_owned() + &DB::regexp("e.entity") + " OR " + SEARCH_VALUES + " AND " + &DB::regexp("v.value_str") + "))"
+		}
+		Expr::Attr { attr, op: Operator::Ne, value, literal, pos, .. } => {
+			params.push(QueryParam::Str(attr.clone()));
+			let has = SEARCH_VALUES.to_owned() + ")";
+			params.push(QueryParam::Str(attr.clone()));
+			let equal = compile_value::<DB>(Operator::Eq, value, None, *literal, *pos, params)?;
+			"(".to_owned() + &has + " AND NOT " + SEARCH_VALUES + " AND " + &equal + "))"
+		}
+		Expr::Attr { attr, op, value, upper, literal, pos } => {
+			params.push(QueryParam::Str(attr.clone()));
+			let cond = compile_value::<DB>(*op, value, upper.as_deref(), *literal, *pos, params)?;
+			SEARCH_VALUES.to_owned() + " AND " + &cond + ")"
+		}
+		Expr::Has { attr } => {
+			params.push(QueryParam::Str(attr.clone()));
+			SEARCH_VALUES.to_owned() + ")"
+		}
+		Expr::Type { name } => {
+			params.push(QueryParam::Str(name.clone()));
+			"LOWER(t.entity_type) = LOWER(?)".to_owned()
+		}
+		Expr::Created { op, span, upper } => time_cond::<DB>("e.created_at", *op, *span, *upper, params),
+	};
+	Ok(sql)
+}
+
+// compares a datetime column against a span, `>` means after the whole span and `<=` up to its end
+fn time_cond<DB: Dialect>(
+	col: &str, op: Operator, span: DateSpan, upper: Option<DateSpan>, params: &mut Vec<QueryParam>
+) -> String {
+	let col = DB::datetime(col);
+	let (cmp, at) = match op {
+		Operator::Gt => (" >= ", span.end),
+		Operator::Ge => (" >= ", span.start),
+		Operator::Lt => (" < ", span.start),
+		Operator::Le => (" < ", span.end),
+		Operator::Match | Operator::Eq | Operator::Ne | Operator::Between => {
+			let end = match (op, upper) {
+				(Operator::Between, Some(upper)) => upper.end,
+				_ => span.end
+			};
+			params.push(QueryParam::Time(span.start));
+			params.push(QueryParam::Time(end));
+			let within = "(".to_owned() + &col + " >= " + &DB::datetime("?") + " AND " + &col + " < " + &DB::datetime("?") + ")";
+			return if op == Operator::Ne { "NOT ".to_owned() + &within } else { within };
+		}
+	};
+	params.push(QueryParam::Time(at));
+	col + cmp + &DB::datetime("?")
+}
+
+// live entity `r` a ref value `v` points at, to be completed with a condition on it and a closing parenthesis
+const REF_ENTITY: &str = "EXISTS (SELECT 1 FROM eav_entities r WHERE r.id = v.value_int AND r.deleted_at IS NULL AND ";
+
+// live ref values pointing at an entity, the ? is its id
+const REF_VALUES: &str = "value_int = ? AND deleted_at IS NULL \
+	AND attr_id IN (SELECT id FROM eav_attrs WHERE value_type = 'ref' AND deleted_at IS NULL)";
+
+// condition on the value `v` of attr `a`, only the columns that fit the attr type are compared
+fn compile_value<DB: Dialect>(
+	op: Operator, value: &str, upper: Option<&str>, literal: bool, pos: usize, params: &mut Vec<QueryParam>
+) -> Result<String, EavError> {
+	if let Err(message) = check_comparison(op, value, upper) {
+		return Err(EavError::new(ErrorCode::InvalidQuery, message).with_field("query").with_position(pos));
+	}
+	let mut conds: Vec<String> = Vec::new();
+	let lower = Operand::parse(value);
+	let number = lower.number;
+	match op {
+		Operator::Between => {
+			let upper = Operand::parse(upper.unwrap_or_default());
+			if let (Some(lo), Some(hi)) = (number, upper.number) {
+				conds.push("(a.value_type = 'int' AND v.value_int >= ? AND v.value_int <= ?)".to_owned());
+				params.push(QueryParam::Float(lo));
+				params.push(QueryParam::Float(hi));
+				conds.push("(a.value_type = 'float' AND v.value_float >= ? AND v.value_float <= ?)".to_owned());
+				params.push(QueryParam::Float(lo));
+				params.push(QueryParam::Float(hi));
+			}
+			if let (Some(lo), Some(hi)) = (lower.time, upper.time) {
+				conds.push("(a.value_type = 'time' AND ".to_owned() + &time_cond::<DB>("v.value_time", op, lo, Some(hi), params) + ")");
+			}
+		}
+		Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le => {
+			let cmp = match op { Operator::Gt => " > ", Operator::Ge => " >= ", Operator::Lt => " < ", _ => " <= " };
+			if let Some(n) = number {
+				conds.push("(a.value_type = 'int' AND v.value_int".to_owned() + cmp + "?)");
+				params.push(QueryParam::Float(n));
+				conds.push("(a.value_type = 'float' AND v.value_float".to_owned() + cmp + "?)");
+				params.push(QueryParam::Float(n));
+			}
+			if let Some(span) = lower.time {
+				conds.push("(a.value_type = 'time' AND ".to_owned() + &time_cond::<DB>("v.value_time", op, span, None, params) + ")");
+			}
+		}
+		_ => {
+			// ':' matches text like a search term, '=' only matches the exact text
+			// refs match by the name of the entity they point at
+			if op == Operator::Match {
+				let pattern = if literal { regex::escape(value) } else { value.to_owned() };
+				let pattern = DB::regex_pattern(&pattern).map_err(|e| e.with_field("query").with_position(pos))?;
+				conds.push("(a.value_type = 'str' AND ".to_owned() + &DB::regexp("v.value_str") + ")");
+				params.push(QueryParam::Str(pattern.clone()));
+				conds.push("(a.value_type = 'ref' AND ".to_owned() + REF_ENTITY + &DB::regexp("r.entity") + "))");
+				params.push(QueryParam::Str(pattern));
+			} else {
+				conds.push("(a.value_type = 'str' AND v.value_str = ?)".to_owned());
+				params.push(QueryParam::Str(value.to_owned()));
+				conds.push("(a.value_type = 'ref' AND ".to_owned() + REF_ENTITY + "r.entity = ?))");
+				params.push(QueryParam::Str(value.to_owned()));
+			}
+			if let Some(n) = number {
+				if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 {
+					conds.push("(a.value_type = 'int' AND v.value_int = ?)".to_owned());
+					params.push(QueryParam::Int(n as i64));
+				}
+				// floats are stored in single precision
+				conds.push("(a.value_type = 'float' AND ABS(v.value_float - ?) <= ?)".to_owned());
+				params.push(QueryParam::Float(n));
+				params.push(QueryParam::Float((n.abs() * 1e-6).max(1e-4)));
+			}
+			if let Some(b) = parse_bool(value) {
+				conds.push("(a.value_type = 'bool' AND v.value_bool = ?)".to_owned());
+				params.push(QueryParam::Bool(b));
+			}
+			// a date matches any time within it, relative dates are too exact to be useful here
+			if let Some(span) = lower.time.filter(|_| !value.starts_with('-')) {
+				conds.push("(a.value_type = 'time' AND ".to_owned() + &time_cond::<DB>("v.value_time", Operator::Eq, span, None, params) + ")");
+			}
+		}
+	}
+	Ok("(".to_owned() + &conds.join(" OR ") + ")")
+}
+
+impl<DB> SqlStore<DB>
+where
+	DB: Dialect,
+	for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+	for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+	for<'q> u32: Encode<'q, DB> + Type<DB>,
+	for<'q> String: Encode<'q, DB> + Type<DB>,
+	for<'q> &'q str: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<u32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<String>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<i32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<f32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<DateTime<Utc>>: Encode<'q, DB> + Type<DB>,
+	for<'q> DateTime<Utc>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<bool>: Encode<'q, DB> + Type<DB>,
+	for<'q> i64: Encode<'q, DB> + Type<DB>,
+	for<'q> f64: Encode<'q, DB> + Type<DB>,
+	for<'q> bool: Encode<'q, DB> + Type<DB>,
+	for<'r> Int: FromRow<'r, DB::Row>,
+	for<'r> Count: FromRow<'r, DB::Row>,
+	for<'r> EavEntityType: FromRow<'r, DB::Row>,
+	for<'r> EavEntity: FromRow<'r, DB::Row>,
+	for<'r> EavAttribute: FromRow<'r, DB::Row>,
+	for<'r> EavValue: FromRow<'r, DB::Row>,
+	for<'r> EavView: FromRow<'r, DB::Row>,
+	for<'r> EavHistory: FromRow<'r, DB::Row>,
+	for<'r> TrashItem: FromRow<'r, DB::Row>,
+{
+	// binds compiled query parameters in the order they were pushed
+	fn bind_params<'q, T>(
+		mut q: QueryAs<'q, DB, T, <DB as HasArguments<'q>>::Arguments>, params: Vec<QueryParam>
+	) -> QueryAs<'q, DB, T, <DB as HasArguments<'q>>::Arguments> {
+		for param in params {
+			q = match param {
+				QueryParam::Str(s) => q.bind(s),
+				QueryParam::Int(i) => q.bind(i),
+				QueryParam::Float(f) => q.bind(f),
+				QueryParam::Bool(b) => q.bind(b),
+				QueryParam::Time(t) => q.bind(t),
+			};
+		}
+		q
+	}
+
+	// one page of `select` rows out of `from`, which has to end in a WHERE clause the cursor condition is appended to.
+	// one row more than requested is fetched to tell if another page follows
+	async fn fetch_page<T>(
+		&self, select: &str, from: &str, params: Vec<QueryParam>, order: PageOrder<'_>, id_of: impl Fn(&T) -> u32, page: &PageRequest
+	) -> Result<Page<T>, EavError>
+	where
+		T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+	{
+		let count = "SELECT COUNT(*) ".to_owned() + from;
+		let total = Self::bind_params(sqlx::query_as::<_, Count>(&count), params.clone())
+			.fetch_one(&self.pool)
+			.await?.0 as u64;
+		let mut params = params;
+		let mut query = select.to_owned() + " " + from;
+		let mut skip = (page.page - 1) * page.per_page;
+		match (&order, &page.cursor) {
+			(PageOrder::Keyset { table, alias, terms }, Some(cursor)) => {
+				let after: u32 = cursor.parse()
+					.map_err(|_| EavError::new(ErrorCode::InvalidArgument, "Invalid cursor").with_field("cursor"))?;
+				query = query + " AND " + &keyset_cond::<DB>(table, alias, terms, after, &mut params);
+				skip = 0;
+			}
+			(PageOrder::By(_), Some(_)) => {
+				return Err(EavError::new(ErrorCode::InvalidArgument, "This list cannot be paged with a cursor").with_field("cursor"));
+			}
+			_ => ()
+		}
+		query += &match &order {
+			PageOrder::Keyset { alias, terms, .. } => {
+				let cols: Vec<String> = terms.iter().map(|t| {
+					params.extend(t.params.iter().cloned());
+					t.sql.replace("{row}", alias) + if t.desc { " DESC" } else { "" }
+				}).collect();
+				" ORDER BY ".to_owned() + &cols.join(", ")
+			}
+			PageOrder::By(order) => " ORDER BY ".to_owned() + order,
+		};
+		query = query + " LIMIT " + &(page.per_page + 1).to_string() + " OFFSET " + &skip.to_string();
+		let mut items = Self::bind_params(sqlx::query_as::<_, T>(&query), params)
+			.fetch_all(&self.pool)
+			.await?;
+		let has_more = items.len() > page.per_page as usize;
+		items.truncate(page.per_page as usize);
+		let next_cursor = match order {
+			PageOrder::Keyset { .. } if has_more => items.last().map(|i| id_of(i).to_string()),
+			_ => None
+		};
+		Ok(Page { items, total, page: page.page, per_page: page.per_page, has_more, next_cursor })
+	}
+
+	// entities matching a condition on eav_entities `e` joined with eav_entity_types `t`
+	async fn fetch_entity_page(
+		&self, filter: &str, params: Vec<QueryParam>, sort: &[SortKey], page: &PageRequest
+	) -> Result<Page<EavEntity>, EavError> {
+		let from = "FROM eav_entities e JOIN eav_entity_types t ON t.id = e.entity_type_id ".to_owned() +
+			"WHERE e.deleted_at IS NULL AND t.deleted_at IS NULL AND " + filter;
+		let order = PageOrder::Keyset { table: "eav_entities", alias: "e", terms: sort_terms::<DB>(sort)? };
+		self.fetch_page("SELECT e.*", &from, params, order, |e: &EavEntity| e.id, page).await
+	}
+
+	// rows of `table` matching `filter`, every ? in the filter is bound to `param`
+	async fn fetch_rows<T, P>(conn: &mut DB::Connection, table: &str, filter: &str, param: P) -> Result<Vec<T>, sqlx::Error>
+	where
+		T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+		P: for<'q> Encode<'q, DB> + Type<DB> + Copy + Send,
+	{
+		let query = "SELECT * FROM ".to_owned() + table + " WHERE " + filter;
+		let mut q = sqlx::query_as::<_, T>(&query);
+		for _ in 0..filter.matches('?').count() { q = q.bind(param); }
+		q.fetch_all(&mut *conn).await
+	}
+
+	// appends a change to eav_history, old/new are the row before and after the change
+	async fn record<T: Tracked>(
+		conn: &mut DB::Connection, at: DateTime<Utc>, action: &str, old: Option<&T>, new: Option<&T>
+	) -> Result<(), sqlx::Error> {
+		let row = match new.or(old) {
+			Some(row) => row,
+			None => return Ok(())
+		};
+		let query = "INSERT INTO eav_history ".to_owned() +
+			"(changed_at, action, target, target_id, entity_type_id, entity_id, old_data, new_data) " +
+			"VALUES (?, ?, ?, ?, ?, ?, ?, ?)";
+		let old_data = old.and_then(|o| serde_json::to_string(o).ok());
+		let new_data = new.and_then(|n| serde_json::to_string(n).ok());
+		// a row moved to another entity is logged under both, so the history of either one has it
+		let mut owners = vec![row.entity_id()];
+		if let Some(prev) = old.map(|o| o.entity_id()).filter(|e| !owners.contains(e)) {
+			owners.push(prev);
+		}
+		for owner in owners {
+			sqlx::query(&query)
+				.bind(at).bind(action).bind(T::TARGET).bind(row.id()).bind(row.entity_type_id()).bind(owner)
+				.bind(&old_data).bind(&new_data)
+				.execute(&mut *conn).await?;
+		}
+		Ok(())
+	}
+
+	// inserts a value as given, type checks are up to the caller
+	async fn insert_value(conn: &mut DB::Connection, at: DateTime<Utc>, value: &EavValue) -> Result<EavValue, sqlx::Error> {
+		let query = "INSERT INTO eav_values ".to_owned() +
+			"(entity_id, attr_id, value_str, value_int, value_float, value_time, value_bool) " +
+			"VALUES (?, ?, ?, ?, ?, ?, ?)";
+		let res = sqlx::query(&query)
+			.bind(value.entity_id).bind(value.attr_id).bind(&value.value_str).bind(value.value_int)
+			.bind(value.value_float).bind(value.value_time).bind(value.value_bool)
+			.execute(&mut *conn).await?;
+		let id = DB::last_insert_id(&res);
+		let res = sqlx::query_as::<_, EavValue>("SELECT * FROM eav_values WHERE id = ?")
+			.bind(id).fetch_one(&mut *conn).await?;
+		Self::record(&mut *conn, at, "create", None, Some(&res)).await?;
+		Ok(res)
+	}
+
+	// every row of `table` in id order, fetched a page at a time and handed to `each`
+	async fn for_each_row<T>(
+		conn: &mut DB::Connection, table: &str, mut each: impl FnMut(T) -> Result<(), EavError> + Send
+	) -> Result<(), EavError>
+	where
+		T: Tracked + for<'r> FromRow<'r, DB::Row> + Unpin,
+	{
+		let mut after = 0;
+		loop {
+			let rows: Vec<T> = Self::fetch_rows(&mut *conn, table, "id > ? ORDER BY id LIMIT 1000", after).await?;
+			match rows.last() {
+				Some(last) => after = last.id(),
+				None => return Ok(())
+			}
+			for row in rows { each(row)?; }
+		}
+	}
+
+	// sets deleted_at on the rows matching `filter` (every ? bound to `id`) and records each change
+	async fn set_deleted_at<T>(
+		conn: &mut DB::Connection, table: &str, filter: &str, id: u32, at: DateTime<Utc>, deleted_at: Option<DateTime<Utc>>
+	) -> Result<u64, sqlx::Error>
+	where
+		T: Tracked + Clone + for<'r> FromRow<'r, DB::Row> + Unpin,
+	{
+		let rows: Vec<T> = Self::fetch_rows(&mut *conn, table, filter, id).await?;
+		if rows.is_empty() { return Ok(0); }
+		let query = "UPDATE ".to_owned() + table + " SET deleted_at = ? WHERE " + filter;
+		let mut q = sqlx::query(&query).bind(deleted_at);
+		for _ in 0..filter.matches('?').count() { q = q.bind(id); }
+		q.execute(&mut *conn).await?;
+		let action = if deleted_at.is_some() { "delete" } else { "restore" };
+		for old in rows.iter() {
+			let mut new = old.clone();
+			new.set_deleted_at(deleted_at);
+			Self::record(&mut *conn, at, action, Some(old), Some(&new)).await?;
+		}
+		Ok(rows.len() as u64)
+	}
+
+	// permanently deletes the rows matching `filter` (every ? bound to `cutoff`) and records each one
+	async fn purge_rows<T>(conn: &mut DB::Connection, table: &str, filter: &str, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error>
+	where
+		T: Tracked + for<'r> FromRow<'r, DB::Row> + Unpin,
+	{
+		let rows: Vec<T> = Self::fetch_rows(&mut *conn, table, filter, cutoff).await?;
+		if rows.is_empty() { return Ok(0); }
+		let query = "DELETE FROM ".to_owned() + table + " WHERE " + filter;
+		let mut q = sqlx::query(&query);
+		for _ in 0..filter.matches('?').count() { q = q.bind(cutoff); }
+		q.execute(&mut *conn).await?;
+		let now = Utc::now();
+		for old in rows.iter() {
+			Self::record(&mut *conn, now, "purge", Some(old), None).await?;
+		}
+		Ok(rows.len() as u64)
+	}
+
+	// history of `target` rows after `at` matching `filter` (every ? bound to `id`), newest first
+	async fn fetch_history_after(
+		conn: &mut DB::Connection, filter: &str, id: u32, at: DateTime<Utc>
+	) -> Result<Vec<EavHistory>, sqlx::Error> {
+		let query = "SELECT * FROM eav_history WHERE changed_at > ? AND (".to_owned() + filter + ") ORDER BY id DESC";
+		let mut q = sqlx::query_as::<_, EavHistory>(&query).bind(at);
+		for _ in 0..filter.matches('?').count() { q = q.bind(id); }
+		q.fetch_all(&mut *conn).await
+	}
+
+	// id of the latest history entry, 0 while the history is empty
+	async fn history_mark(conn: &mut DB::Connection) -> Result<u32, sqlx::Error> {
+		let row = sqlx::query_as::<_, Int>("SELECT id FROM eav_history ORDER BY id DESC LIMIT 1")
+			.fetch_optional(&mut *conn).await?;
+		Ok(row.map(|r| r.0).unwrap_or(0))
+	}
+
+	// current row `id` and the row it should become, a missing state means the row didn't exist and goes to the trash
+	async fn next_state<T>(
+		conn: &mut DB::Connection, table: &str, id: u32, state: Option<&str>, at: DateTime<Utc>
+	) -> Result<(T, T), EavError>
+	where
+		T: Tracked + Clone + for<'r> FromRow<'r, DB::Row> + Unpin,
+	{
+		let current = match Self::fetch_rows::<T, u32>(&mut *conn, table, "id = ?", id).await?.pop() {
+			Some(row) => row,
+			None => return Err(EavError::new(ErrorCode::NotFound, "Item has been purged from the trash").with_field("id"))
+		};
+		let next = match state {
+			Some(data) => match serde_json::from_str::<T>(data) {
+				Ok(row) => row,
+				Err(e) => return Err(EavError::new(ErrorCode::Database, &e.to_string()))
+			},
+			None => {
+				let mut row = current.clone();
+				row.set_deleted_at(Some(at));
+				row
+			}
+		};
+		Ok((current, next))
+	}
+
+	// overwrites the row a history entry points to with its old or new contents and records that as a new change
+	async fn apply_change(
+		conn: &mut DB::Connection, at: DateTime<Utc>, action: &str, change: &EavHistory, undo: bool
+	) -> Result<(), EavError> {
+		let state = if undo { change.old_data.as_deref() } else { change.new_data.as_deref() };
+		let id = change.target_id;
+		match change.target.as_str() {
+			"entity_type" => {
+				let (old, new) = Self::next_state::<EavEntityType>(&mut *conn, "eav_entity_types", id, state, at).await?;
+				sqlx::query("UPDATE eav_entity_types SET entity_type = ?, deleted_at = ? WHERE id = ?")
+					.bind(&new.entity_type).bind(new.deleted_at).bind(id)
+					.execute(&mut *conn).await?;
+				Self::record(&mut *conn, at, action, Some(&old), Some(&new)).await?;
+			}
+			"entity" => {
+				let (old, new) = Self::next_state::<EavEntity>(&mut *conn, "eav_entities", id, state, at).await?;
+				sqlx::query("UPDATE eav_entities SET entity = ?, entity_type_id = ?, deleted_at = ? WHERE id = ?")
+					.bind(&new.entity).bind(new.entity_type_id).bind(new.deleted_at).bind(id)
+					.execute(&mut *conn).await?;
+				Self::record(&mut *conn, at, action, Some(&old), Some(&new)).await?;
+			}
+			"attr" => {
+				let (old, new) = Self::next_state::<EavAttribute>(&mut *conn, "eav_attrs", id, state, at).await?;
+				let query = "UPDATE eav_attrs SET attr = ?, value_type = ?, entity_type_id = ?, allow_multiple = ?, deleted_at = ?, ".to_owned() +
+					"ref_entity_type_id = ?, on_delete = ? WHERE id = ?";
+				sqlx::query(&query)
+					.bind(&new.attr).bind(&new.value_type).bind(new.entity_type_id).bind(new.allow_multiple).bind(new.deleted_at)
+					.bind(new.ref_entity_type_id).bind(&new.on_delete).bind(id)
+					.execute(&mut *conn).await?;
+				Self::record(&mut *conn, at, action, Some(&old), Some(&new)).await?;
+			}
+			"value" => {
+				let (old, new) = Self::next_state::<EavValue>(&mut *conn, "eav_values", id, state, at).await?;
+				let query = "UPDATE eav_values SET entity_id = ?, attr_id = ?, value_str = ?, value_int = ?, value_float = ?, ".to_owned() +
+					"value_time = ?, value_bool = ?, deleted_at = ? WHERE id = ?";
+				sqlx::query(&query)
+					.bind(new.entity_id).bind(new.attr_id).bind(&new.value_str).bind(new.value_int).bind(new.value_float)
+					.bind(new.value_time).bind(new.value_bool).bind(new.deleted_at).bind(id)
+					.execute(&mut *conn).await?;
+				Self::record(&mut *conn, at, action, Some(&old), Some(&new)).await?;
+			}
+			_ => return Err(EavError::new(ErrorCode::InvalidArgument, "Unknown history target").with_field("target"))
+		}
+		Ok(())
+	}
+
+	// category a ref attr is limited to, it has to be out of the trash
+	async fn ref_target(conn: &mut DB::Connection, type_id: u32) -> Result<EavEntityType, EavError> {
+		sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ? AND deleted_at IS NULL")
+			.bind(type_id)
+			.fetch_optional(&mut *conn)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("ref_entity_type_id"))
+	}
+
+	// a ref value has to point at a live entity, one of the attr's category if it has one
+	async fn check_ref(conn: &mut DB::Connection, attr: &EavAttribute, value: &EavValue) -> Result<(), EavError> {
+		if value.value_int.is_none() {
+			return Err(EavError::new(ErrorCode::InvalidValueType, "Value does not match attribute type ref").with_field("value_int"));
+		}
+		let target = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL")
+			.bind(value.value_int)
+			.fetch_optional(&mut *conn)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Referenced entity not found").with_field("value_int"))?;
+		if let Some(type_id) = attr.ref_entity_type_id.filter(|t| *t != target.entity_type_id) {
+			let entity_type = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ?")
+				.bind(type_id).fetch_one(&mut *conn).await?;
+			let msg = attr.attr.clone() + " has to point at an entity of " + &entity_type.entity_type;
+			return Err(EavError::new(ErrorCode::InvalidArgument, &msg).with_field("value_int"));
+		}
+		Ok(())
+	}
+
+	// applies the on_delete rules of the ref values pointing at entities about to go to the trash. holders of
+	// cascading refs go as well and their own backlinks are followed in turn, refs held by doomed entities are left alone
+	async fn release_refs(conn: &mut DB::Connection, at: DateTime<Utc>, ids: &[u32]) -> Result<DeleteSummary, EavError> {
+		let mut doomed: HashSet<u32> = ids.iter().copied().collect();
+		let mut pending = ids.to_vec();
+		let mut attrs: HashMap<u32, EavAttribute> = HashMap::new();
+		let mut nullified: Vec<EavValue> = Vec::new();
+		let mut cascaded: Vec<u32> = Vec::new();
+		while let Some(id) = pending.pop() {
+			let refs: Vec<EavValue> = Self::fetch_rows(&mut *conn, "eav_values", &(REF_VALUES.to_owned() + " ORDER BY id"), id).await?;
+			for value in refs {
+				if doomed.contains(&value.entity_id) { continue; }
+				if !attrs.contains_key(&value.attr_id) {
+					let attr = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+						.bind(value.attr_id).fetch_one(&mut *conn).await?;
+					attrs.insert(attr.id, attr);
+				}
+				let attr = &attrs[&value.attr_id];
+				match OnDelete::of(attr) {
+					OnDelete::Block => {
+						let name = |id: u32| sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?").bind(id);
+						let target = name(id).fetch_one(&mut *conn).await?;
+						let holder = name(value.entity_id).fetch_one(&mut *conn).await?;
+						let msg = target.entity + " is the " + &attr.attr + " of " + &holder.entity;
+						return Err(EavError::new(ErrorCode::Referenced, &msg).with_field("id"));
+					}
+					OnDelete::Nullify => nullified.push(value),
+					OnDelete::Cascade => {
+						doomed.insert(value.entity_id);
+						pending.push(value.entity_id);
+						cascaded.push(value.entity_id);
+					}
+				}
+			}
+		}
+		let mut summary = DeleteSummary::default();
+		for value in nullified.iter().filter(|v| !doomed.contains(&v.entity_id)) {
+			sqlx::query("UPDATE eav_values SET value_int = NULL WHERE id = ?")
+				.bind(value.id).execute(&mut *conn).await?;
+			let new = EavValue { value_int: None, ..value.clone() };
+			Self::record(&mut *conn, at, "update", Some(value), Some(&new)).await?;
+			summary.values += 1;
+		}
+		for id in cascaded {
+			summary.values += Self::set_deleted_at::<EavValue>(&mut *conn, "eav_values",
+				"entity_id = ? AND deleted_at IS NULL", id, at, Some(at)).await?;
+			summary.entities += Self::set_deleted_at::<EavEntity>(&mut *conn, "eav_entities",
+				"id = ? AND deleted_at IS NULL", id, at, Some(at)).await?;
+		}
+		Ok(summary)
+	}
+
+	// names of the live entities among `ids`, for showing ref values
+	async fn entity_names(conn: &mut DB::Connection, ids: Vec<u32>) -> Result<HashMap<u32, String>, sqlx::Error> {
+		if ids.is_empty() { return Ok(HashMap::new()); }
+		let ids: Vec<String> = ids.iter().map(u32::to_string).collect();
+		let query = "SELECT * FROM eav_entities WHERE deleted_at IS NULL AND id IN (".to_owned() + &ids.join(",") + ")";
+		let rows = sqlx::query_as::<_, EavEntity>(&query).fetch_all(&mut *conn).await?;
+		Ok(rows.into_iter().map(|e| (e.id, e.entity)).collect())
+	}
+}
+
+// names are stored trimmed and can't be empty
+fn clean_name<'a>(name: &'a str, field: &str) -> Result<&'a str, EavError> {
+	let name = name.trim();
+	if name.is_empty() {
+		return Err(EavError::new(ErrorCode::InvalidArgument, "Name can't be empty").with_field(field));
+	}
+	Ok(name)
+}
+
+// values hold the same thing, whatever the attribute's type
+fn same_content(a: &EavValue, b: &EavValue) -> bool {
+	a.value_str == b.value_str && a.value_int == b.value_int && a.value_float == b.value_float
+		&& a.value_time == b.value_time && a.value_bool == b.value_bool
+}
+
+// names tried for a copy of `name`: a trailing number counts up, like Vol. 3 to Vol. 4, anything else gets (2), (3)...
+fn copy_names(name: &str) -> impl Iterator<Item = String> + '_ {
+	let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+	let number = name[stem.len()..].parse::<u64>().ok();
+	(number.map_or(2, |n| n + 1)..).map(move |n| match number {
+		Some(_) => stem.to_owned() + &n.to_string(),
+		None => name.to_owned() + " (" + &n.to_string() + ")"
+	})
+}
+
+// rolls rows back over history entries (newest first) so they show their state from before those changes
+fn revert_rows<T: Tracked>(rows: &mut BTreeMap<u32, T>, history: &[EavHistory]) {
+	for change in history.iter().filter(|h| h.target == T::TARGET) {
+		let old = change.old_data.as_ref().and_then(|d| serde_json::from_str::<T>(d).ok());
+		match old {
+			Some(row) => { rows.insert(change.target_id, row); }
+			None => { rows.remove(&change.target_id); }
+		}
+	}
+}
+
+// a row moved to another entity is logged once per entity, right after each other,
+// this keeps one of them so the move is written back and reported once
+fn dedup_moves(changes: &mut Vec<EavHistory>) {
+	changes.dedup_by(|a, b| a.target == b.target && a.target_id == b.target_id && a.old_data == b.old_data && a.new_data == b.new_data);
+}
+
+#[async_trait]
+impl<DB> EavStore for SqlStore<DB>
+where
+	DB: Dialect,
+	for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+	for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+	for<'q> u32: Encode<'q, DB> + Type<DB>,
+	for<'q> String: Encode<'q, DB> + Type<DB>,
+	for<'q> &'q str: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<u32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<String>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<i32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<f32>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<DateTime<Utc>>: Encode<'q, DB> + Type<DB>,
+	for<'q> DateTime<Utc>: Encode<'q, DB> + Type<DB>,
+	for<'q> Option<bool>: Encode<'q, DB> + Type<DB>,
+	for<'q> i64: Encode<'q, DB> + Type<DB>,
+	for<'q> f64: Encode<'q, DB> + Type<DB>,
+	for<'q> bool: Encode<'q, DB> + Type<DB>,
+	for<'r> Int: FromRow<'r, DB::Row>,
+	for<'r> Count: FromRow<'r, DB::Row>,
+	for<'r> EavEntityType: FromRow<'r, DB::Row>,
+	for<'r> EavEntity: FromRow<'r, DB::Row>,
+	for<'r> EavAttribute: FromRow<'r, DB::Row>,
+	for<'r> EavValue: FromRow<'r, DB::Row>,
+	for<'r> EavView: FromRow<'r, DB::Row>,
+	for<'r> EavHistory: FromRow<'r, DB::Row>,
+	for<'r> TrashItem: FromRow<'r, DB::Row>,
+{
+	// -- ENTITY TYPES --
+	async fn fetch_entity_types(&self) -> Result<Vec<EavEntityType>, EavError> {
+		let rows = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE deleted_at IS NULL")
+			.fetch_all(&self.pool)
+			.await?;
+		println!("fetch_entity_types: {} results", rows.len());
+		Ok(rows)
+	}
+
+	async fn create_entity_type(&self, name: &str) -> Result<EavEntityType, EavError> {
+		let mut tx = self.pool.begin().await?;
+		let res = sqlx::query("INSERT INTO eav_entity_types (entity_type) VALUES (?)")
+			.bind(name).execute(&mut *tx).await?;
+		let id = DB::last_insert_id(&res);
+		println!("create_entity_type: {}", id);
+		let res = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ?")
+			.bind(id)
+			.fetch_one(&mut *tx)
+			.await?;
+		Self::record(&mut tx, Utc::now(), "create", None, Some(&res)).await?;
+		tx.commit().await?;
+		Ok(res)
+	}
+
+	async fn rename_entity_type(&self, id: u32, name: &str) -> Result<EavEntityType, EavError> {
+		let name = clean_name(name, "entity_type")?;
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("id"))?;
+		// the unique index on entity_type turns away duplicates
+		sqlx::query("UPDATE eav_entity_types SET entity_type = ? WHERE id = ?")
+			.bind(name).bind(id).execute(&mut *tx).await?;
+		let res = EavEntityType { entity_type: name.to_owned(), ..old.clone() };
+		Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("rename_entity_type: {}", id);
+		Ok(res)
+	}
+
+	// moves the entity type with its entities, attrs and values to the trash
+	async fn delete_entity_type(&self, id: u32) -> Result<DeleteSummary, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let entity_ids: Vec<u32> = Self::fetch_rows::<EavEntity, u32>(&mut tx, "eav_entities", "entity_type_id = ? AND deleted_at IS NULL", id)
+			.await?.into_iter().map(|e| e.id).collect();
+		let released = Self::release_refs(&mut tx, now, &entity_ids).await?;
+		// trash values of every attr of the entity type, which covers all values of its entities
+		let values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+			"attr_id IN (SELECT id FROM eav_attrs WHERE entity_type_id = ?) AND deleted_at IS NULL", id, now, Some(now)).await?;
+		// trash entities + attributes for entity type
+		let entities = Self::set_deleted_at::<EavEntity>(&mut tx, "eav_entities",
+			"entity_type_id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		let attrs = Self::set_deleted_at::<EavAttribute>(&mut tx, "eav_attrs",
+			"entity_type_id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		// trash entity type
+		let entity_types = Self::set_deleted_at::<EavEntityType>(&mut tx, "eav_entity_types",
+			"id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		if entity_types == 0 {
+			return Err(EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("id"));
+		}
+		tx.commit().await?;
+		let summary = DeleteSummary { entity_types, entities: entities + released.entities, attrs, values: values + released.values };
+		println!("delete_entity_type: {:?}", summary);
+		Ok(summary)
+	}
+
+	// -- ENTITIES --
+	async fn fetch_entities(
+		&self, entity_type_id: u32, sort: &[SortKey], page: &PageRequest
+	) -> Result<Page<EavEntity>, EavError> {
+		let res = self.fetch_entity_page("e.entity_type_id = ?", vec![QueryParam::Int(entity_type_id as i64)], sort, page).await?;
+		println!("fetch_entities: {} of {} results", res.items.len(), res.total);
+		Ok(res)
+	}
+
+	async fn fetch_entity_by_id(&self, id: u32) -> Result<EavEntity, EavError> {
+		let row = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_one(&self.pool)
+			.await?;
+		println!("fetch_entity_by_id: {}", row.id);
+		Ok(row)
+	}
+
+	async fn create_entity(&self, entity_type: &str, entity: &str) -> Result<EavEntity, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let existing = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE entity_type = ?")
+			.bind(entity_type).fetch_optional(&mut *tx).await?;
+		// the name of a trashed entity type stays taken until it is restored or purged
+		if existing.as_ref().is_some_and(|t| t.deleted_at.is_some()) {
+			return Err(EavError::new(ErrorCode::InTrash, "Entity type is in the trash").with_field("entity_type"));
+		}
+		let type_exists = existing.is_some();
+		let id = DB::insert_entity(&mut tx, entity_type, entity).await?;
+		println!("create_entity: {}", id);
+		let res = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?")
+			.bind(id).fetch_one(&mut *tx).await?;
+		// entity type is created on the fly when missing
+		if !type_exists {
+			let et = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ?")
+				.bind(res.entity_type_id).fetch_one(&mut *tx).await?;
+			Self::record(&mut tx, now, "create", None, Some(&et)).await?;
+		}
+		Self::record(&mut tx, now, "create", None, Some(&res)).await?;
+		tx.commit().await?;
+		Ok(res)
+	}
+
+	async fn rename_entity(&self, id: u32, name: &str) -> Result<EavEntity, EavError> {
+		let name = clean_name(name, "entity")?;
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("id"))?;
+		// same rule as create_eav_entity, trashed entities keep their name taken
+		let query = "SELECT COUNT(*) FROM eav_entities WHERE entity = ? AND entity_type_id = ? AND id <> ?";
+		let taken = sqlx::query_as::<_, Count>(query)
+			.bind(name).bind(old.entity_type_id).bind(id)
+			.fetch_one(&mut *tx).await?.0 > 0;
+		if taken {
+			return Err(EavError::new(ErrorCode::DuplicateEntity, "Entity already exists").with_field("entity"));
+		}
+		sqlx::query("UPDATE eav_entities SET entity = ? WHERE id = ?")
+			.bind(name).bind(id).execute(&mut *tx).await?;
+		let res = EavEntity { entity: name.to_owned(), ..old.clone() };
+		Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("rename_entity: {}", id);
+		Ok(res)
+	}
+
+	async fn clone_entity(&self, id: u32, options: &CloneOptions) -> Result<EavEntity, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("id"))?;
+		let entity_type = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ?")
+			.bind(old.entity_type_id).fetch_one(&mut *tx).await?;
+		let attrs: Vec<EavAttribute> = Self::fetch_rows(&mut tx, "eav_attrs",
+			"entity_type_id = ? AND deleted_at IS NULL", old.entity_type_id).await?;
+		let known = |id: &u32| attrs.iter().find(|a| a.id == *id);
+		if options.attrs.iter().flatten().chain(options.increment.iter()).any(|id| known(id).is_none()) {
+			return Err(EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("attrs"));
+		}
+		if options.increment.iter().filter_map(known).any(|a| a.value_type != "int" && a.value_type != "float") {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Only numbers can be incremented").with_field("increment"));
+		}
+		let name = match &options.name {
+			Some(name) => clean_name(name, "entity")?.to_owned(),
+			None => {
+				// trashed entities keep their name taken
+				let entities: Vec<EavEntity> = Self::fetch_rows(&mut tx, "eav_entities", "entity_type_id = ?", old.entity_type_id).await?;
+				let taken: HashSet<String> = entities.into_iter().map(|e| e.entity).collect();
+				copy_names(&old.entity).find(|n| !taken.contains(n)).unwrap_or_default()
+			}
+		};
+		let entity_id = DB::insert_entity(&mut tx, &entity_type.entity_type, &name).await?;
+		let entity = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?")
+			.bind(entity_id).fetch_one(&mut *tx).await?;
+		Self::record(&mut tx, now, "create", None, Some(&entity)).await?;
+		let values: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values", "entity_id = ? AND deleted_at IS NULL ORDER BY id", id).await?;
+		let mut copies = 0;
+		for value in values {
+			if options.attrs.as_ref().is_some_and(|attrs| !attrs.contains(&value.attr_id)) { continue; }
+			let mut copy = EavValue { entity_id, ..value };
+			if options.increment.contains(&copy.attr_id) {
+				let overflow = || EavError::new(ErrorCode::InvalidArgument, "Number is too large to increment").with_field("increment");
+				copy.value_int = copy.value_int.map(|i| i.checked_add(1).ok_or_else(overflow)).transpose()?;
+				copy.value_float = copy.value_float.map(|f| f + 1.0);
+			}
+			Self::insert_value(&mut tx, now, &copy).await?;
+			copies += 1;
+		}
+		tx.commit().await?;
+		println!("clone_entity: {} to {} with {} values", id, entity_id, copies);
+		Ok(entity)
+	}
+
+	async fn move_entity(
+		&self, id: u32, entity_type_id: u32, mapping: &[AttrMapping], create_missing: bool, dry_run: bool
+	) -> Result<MoveReport, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("id"))?;
+		if old.entity_type_id == entity_type_id {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Entity is already in this category").with_field("entity_type_id"));
+		}
+		let entity_type = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ? AND deleted_at IS NULL")
+			.bind(entity_type_id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("entity_type_id"))?;
+		let taken = sqlx::query_as::<_, Count>("SELECT COUNT(*) FROM eav_entities WHERE entity = ? AND entity_type_id = ?")
+			.bind(&old.entity).bind(entity_type_id)
+			.fetch_one(&mut *tx).await?.0 > 0;
+		if taken {
+			let msg = "Entity ".to_owned() + &old.entity + " already exists in " + &entity_type.entity_type;
+			return Err(EavError::new(ErrorCode::DuplicateEntity, &msg).with_field("entity"));
+		}
+		// refs restricted to the old category can't follow the entity
+		let query = "SELECT COUNT(*) FROM eav_values v JOIN eav_attrs a ON a.id = v.attr_id ".to_owned() +
+			"WHERE v.value_int = ? AND v.deleted_at IS NULL AND a.deleted_at IS NULL AND a.value_type = 'ref' " +
+			"AND a.ref_entity_type_id IS NOT NULL AND a.ref_entity_type_id <> ?";
+		let pinned = sqlx::query_as::<_, Count>(&query)
+			.bind(id).bind(entity_type_id)
+			.fetch_one(&mut *tx).await?.0 > 0;
+		if pinned {
+			let msg = "Entity ".to_owned() + &old.entity + " is referenced by attributes that only point at its current category";
+			return Err(EavError::new(ErrorCode::Referenced, &msg).with_field("entity_type_id"));
+		}
+		let live = "entity_type_id = ? AND deleted_at IS NULL ORDER BY id";
+		let sources: Vec<EavAttribute> = Self::fetch_rows(&mut tx, "eav_attrs", live, old.entity_type_id).await?;
+		let mut targets: Vec<EavAttribute> = Self::fetch_rows(&mut tx, "eav_attrs", live, entity_type_id).await?;
+		for m in mapping {
+			let known = sources.iter().any(|a| a.id == m.from) && m.to.is_none_or(|to| targets.iter().any(|a| a.id == to));
+			if !known {
+				return Err(EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("mapping"));
+			}
+		}
+		let values: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values",
+			"entity_id = ? AND deleted_at IS NULL ORDER BY attr_id, created_at, id", id).await?;
+		let mut report = MoveReport {
+			dry_run, committed: false, entity: EavEntity { entity_type_id, ..old.clone() },
+			mapping: Vec::new(), values: 0, attrs: 0, dropped: Vec::new()
+		};
+		for source in sources.iter() {
+			let explicit = mapping.iter().find(|m| m.from == source.id);
+			let mut target = match explicit {
+				Some(m) => m.to.and_then(|to| targets.iter().find(|a| a.id == to)).cloned(),
+				None => targets.iter().find(|a| a.attr == source.attr).cloned()
+			};
+			// attrs are only created for values that need them
+			let created = explicit.is_none() && target.is_none() && create_missing && values.iter().any(|v| v.attr_id == source.id);
+			if created {
+				let allow_multiple = source.allow_multiple.unwrap_or(false);
+				let attr_id = DB::insert_attr(&mut tx, entity_type_id, &source.attr, &source.value_type, allow_multiple).await?;
+				sqlx::query("UPDATE eav_attrs SET ref_entity_type_id = ?, on_delete = ? WHERE id = ?")
+					.bind(source.ref_entity_type_id).bind(&source.on_delete).bind(attr_id).execute(&mut *tx).await?;
+				let attr = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+					.bind(attr_id).fetch_one(&mut *tx).await?;
+				Self::record(&mut tx, now, "create", None, Some(&attr)).await?;
+				report.attrs += 1;
+				targets.push(attr.clone());
+				target = Some(attr);
+			}
+			report.mapping.push(AttrMove {
+				from: source.id,
+				attr: source.attr.clone(),
+				to: target.as_ref().map(|a| a.id),
+				target: target.map(|a| a.attr),
+				created,
+			});
+		}
+		sqlx::query("UPDATE eav_entities SET entity_type_id = ? WHERE id = ?")
+			.bind(entity_type_id).bind(id).execute(&mut *tx).await?;
+		Self::record(&mut tx, now, "update", Some(&old), Some(&report.entity)).await?;
+		// target attrs holding a value, single value attrs keep the oldest
+		let mut filled: HashSet<u32> = HashSet::new();
+		for value in values {
+			let Some(source) = sources.iter().find(|a| a.id == value.attr_id) else { continue };
+			let target = report.mapping.iter().find(|m| m.from == source.id)
+				.and_then(|m| m.to)
+				.and_then(|to| targets.iter().find(|a| a.id == to));
+			let mut moved = match target {
+				None => Err("Not mapped to an attribute of ".to_owned() + &entity_type.entity_type),
+				Some(target) if !target.allow_multiple.unwrap_or(false) && filled.contains(&target.id) => {
+					Err(target.attr.clone() + " takes one value")
+				}
+				Some(target) => eav_import::convert_value(&value, &source.value_type, &target.value_type)
+					.map(|new| EavValue { attr_id: target.id, ..new })
+					.map_err(str::to_owned)
+			};
+			if let (Ok(new), Some(target)) = (&moved, target.filter(|t| t.value_type == "ref")) {
+				if let Err(e) = Self::check_ref(&mut tx, target, new).await {
+					moved = Err(e.message);
+				}
+			}
+			match moved {
+				Ok(new) => {
+					let query = "UPDATE eav_values SET ".to_owned() +
+						"attr_id = ?, value_str = ?, value_int = ?, value_float = ?, value_time = ?, value_bool = ? " +
+						"WHERE id = ?";
+					sqlx::query(&query).bind(new.attr_id).bind(&new.value_str).bind(new.value_int).bind(new.value_float)
+						.bind(new.value_time).bind(new.value_bool).bind(new.id)
+						.execute(&mut *tx).await?;
+					Self::record(&mut tx, now, "update", Some(&value), Some(&new)).await?;
+					filled.insert(new.attr_id);
+					report.values += 1;
+				}
+				Err(reason) => {
+					Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", value.id, now, Some(now)).await?;
+					report.dropped.push(DroppedValue {
+						value_id: value.id,
+						attr: source.attr.clone(),
+						value: eav_import::value_text(&value, &source.value_type, &source.value_type).unwrap_or_default(),
+						reason,
+					});
+				}
+			}
+		}
+		report.committed = !dry_run;
+		if report.committed {
+			tx.commit().await?;
+		} else {
+			tx.rollback().await?;
+		}
+		println!("move_entity: {} to {}, {} values, {} attrs, {} dropped, committed: {}",
+			id, entity_type_id, report.values, report.attrs, report.dropped.len(), report.committed);
+		Ok(report)
+	}
+
+	async fn delete_entity(&self, id: u32) -> Result<DeleteSummary, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		// trash values for entity
+		let values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+			"entity_id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		// trash entity
+		let entities = Self::set_deleted_at::<EavEntity>(&mut tx, "eav_entities",
+			"id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		if entities == 0 {
+			return Err(EavError::new(ErrorCode::NotFound, "Entity not found").with_field("id"));
+		}
+		let released = Self::release_refs(&mut tx, now, &[id]).await?;
+		tx.commit().await?;
+		let summary = DeleteSummary { entities: entities + released.entities, values: values + released.values, ..Default::default() };
+		println!("delete_entity: {:?}", summary);
+		Ok(summary)
+	}
+
+	async fn merge_entities(
+		&self, source_id: u32, target_id: u32, strategies: &[AttrStrategy], dry_run: bool
+	) -> Result<MergeReport, EavError> {
+		if source_id == target_id {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Can't merge an entity into itself").with_field("source_id"));
+		}
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let query = "SELECT * FROM eav_entities WHERE id = ? AND deleted_at IS NULL";
+		let source = sqlx::query_as::<_, EavEntity>(query)
+			.bind(source_id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("source_id"))?;
+		let target = sqlx::query_as::<_, EavEntity>(query)
+			.bind(target_id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("target_id"))?;
+		if source.entity_type_id != target.entity_type_id {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Entities have to be in the same category").with_field("target_id"));
+		}
+		let attrs: Vec<EavAttribute> = Self::fetch_rows(&mut tx, "eav_attrs",
+			"entity_type_id = ? AND deleted_at IS NULL", target.entity_type_id).await?;
+		let order = "entity_id = ? AND deleted_at IS NULL ORDER BY attr_id, created_at, id";
+		let sources: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values", order, source_id).await?;
+		// the target's values as the merge goes
+		let mut kept: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values", order, target_id).await?;
+		let mut report = MergeReport {
+			dry_run, committed: false, entity: target, moved: 0, duplicates: 0, relinked: 0, self_refs: 0, conflicts: Vec::new()
+		};
+		// conflicting refs show the names of the entities they point at
+		let is_ref = |v: &EavValue| attrs.iter().any(|a| a.id == v.attr_id && a.value_type == "ref");
+		let ref_ids = sources.iter().chain(kept.iter()).filter(|v| is_ref(v)).filter_map(|v| v.value_int.map(|i| i as u32)).collect();
+		let names = Self::entity_names(&mut tx, ref_ids).await?;
+		for value in sources {
+			let Some(attr) = attrs.iter().find(|a| a.id == value.attr_id) else { continue };
+			// a ref to either of the two would point the target at itself, it stays with the source
+			if is_ref(&value) && value.value_int.is_some_and(|i| i as u32 == source_id || i as u32 == target_id) {
+				report.self_refs += 1;
+				continue;
+			}
+			if kept.iter().any(|k| k.attr_id == value.attr_id && same_content(k, &value)) {
+				report.duplicates += 1;
+				continue;
+			}
+			let single = !attr.allow_multiple.unwrap_or(false);
+			if let Some(pos) = kept.iter().position(|k| k.attr_id == value.attr_id).filter(|_| single) {
+				let strategy = strategies.iter().find(|s| s.attr_id == attr.id).map_or(MergeStrategy::KeepTarget, |s| s.strategy);
+				let current = &kept[pos];
+				let take = match strategy {
+					MergeStrategy::KeepTarget => false,
+					MergeStrategy::KeepSource => true,
+					MergeStrategy::KeepNewest => value.created_at > current.created_at
+				};
+				let text = |v: &EavValue| match v.value_int.and_then(|i| names.get(&(i as u32))) {
+					Some(name) if attr.value_type == "ref" => name.clone(),
+					_ => eav_import::value_text(v, &attr.value_type, &attr.value_type).unwrap_or_default()
+				};
+				report.conflicts.push(MergeConflict {
+					attr_id: attr.id,
+					attr: attr.attr.clone(),
+					target: text(current),
+					source: text(&value),
+					kept_source: take,
+				});
+				if !take { continue; }
+				Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", current.id, now, Some(now)).await?;
+				kept.remove(pos);
+			}
+			sqlx::query("UPDATE eav_values SET entity_id = ? WHERE id = ?")
+				.bind(target_id).bind(value.id).execute(&mut *tx).await?;
+			let moved = EavValue { entity_id: target_id, ..value.clone() };
+			Self::record(&mut tx, now, "update", Some(&value), Some(&moved)).await?;
+			kept.push(moved);
+			report.moved += 1;
+		}
+		// whatever stayed with the source goes to the trash with it
+		Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "entity_id = ? AND deleted_at IS NULL", source_id, now, Some(now)).await?;
+		// refs to the source point at the target from now on, unless their holder already points at the target that way
+		let refs: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values", &(REF_VALUES.to_owned() + " ORDER BY id"), source_id).await?;
+		for value in refs {
+			if value.entity_id == target_id {
+				Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", value.id, now, Some(now)).await?;
+				report.self_refs += 1;
+				continue;
+			}
+			let query = "SELECT COUNT(*) FROM eav_values WHERE entity_id = ? AND attr_id = ? AND value_int = ? AND deleted_at IS NULL";
+			let linked = sqlx::query_as::<_, Count>(query)
+				.bind(value.entity_id).bind(value.attr_id).bind(target_id)
+				.fetch_one(&mut *tx).await?.0 > 0;
+			if linked {
+				Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", value.id, now, Some(now)).await?;
+				continue;
+			}
+			sqlx::query("UPDATE eav_values SET value_int = ? WHERE id = ?")
+				.bind(target_id).bind(value.id).execute(&mut *tx).await?;
+			let relinked = EavValue { value_int: Some(target_id as i32), ..value.clone() };
+			Self::record(&mut tx, now, "update", Some(&value), Some(&relinked)).await?;
+			report.relinked += 1;
+		}
+		Self::set_deleted_at::<EavEntity>(&mut tx, "eav_entities", "id = ?", source_id, now, Some(now)).await?;
+		report.committed = !dry_run;
+		if report.committed {
+			tx.commit().await?;
+		} else {
+			tx.rollback().await?;
+		}
+		println!("merge_entities: {} into {}, {} moved, {} duplicates, {} relinked, {} self refs, {} conflicts, committed: {}",
+			source_id, target_id, report.moved, report.duplicates, report.relinked, report.self_refs, report.conflicts.len(),
+			report.committed);
+		Ok(report)
+	}
+
+	async fn search(&self, query: &Expr, sort: &[SortKey], page: &PageRequest) -> Result<Page<EavEntity>, EavError> {
+		let mut params = Vec::new();
+		let filter = compile_query::<DB>(query, &mut params)?;
+		let res = self.fetch_entity_page(&filter, params, sort, page).await?;
+		println!("search: {} of {} results", res.items.len(), res.total);
+		Ok(res)
+	}
+
+	async fn fetch_table(
+		&self, entity_type_id: u32, filter: Option<&Expr>, sort: &[SortKey], page: &PageRequest
+	) -> Result<EavTable, EavError> {
+		let entity_type = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ? AND deleted_at IS NULL")
+			.bind(entity_type_id)
+			.fetch_optional(&self.pool)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("entity_type_id"))?;
+		let columns = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE entity_type_id = ? AND deleted_at IS NULL ORDER BY id")
+			.bind(entity_type_id)
+			.fetch_all(&self.pool)
+			.await?;
+		let mut params = vec![QueryParam::Int(entity_type_id as i64)];
+		let mut cond = "e.entity_type_id = ?".to_owned();
+		if let Some(filter) = filter {
+			cond = cond + " AND " + &compile_query::<DB>(filter, &mut params)?;
+		}
+		let entities = self.fetch_entity_page(&cond, params, sort, page).await?;
+		// values of every entity on the page at once
+		let values = if entities.items.is_empty() || columns.is_empty() {
+			Vec::new()
+		} else {
+			let ids: Vec<String> = entities.items.iter().map(|e| e.id.to_string()).collect();
+			let query = "SELECT v.* FROM eav_values v JOIN eav_attrs a ON a.id = v.attr_id ".to_owned() +
+				"WHERE v.deleted_at IS NULL AND a.deleted_at IS NULL AND a.entity_type_id = ? " +
+				"AND v.entity_id IN (" + &ids.join(",") + ") ORDER BY v.id";
+			sqlx::query_as::<_, EavValue>(&query).bind(entity_type_id).fetch_all(&self.pool).await?
+		};
+		let col_at: HashMap<u32, usize> = columns.iter().enumerate().map(|(i, c)| (c.id, i)).collect();
+		let row_at: HashMap<u32, usize> = entities.items.iter().enumerate().map(|(i, e)| (e.id, i)).collect();
+		let ref_ids = values.iter()
+			.filter(|v| col_at.get(&v.attr_id).is_some_and(|c| columns[*c].value_type == "ref"))
+			.filter_map(|v| v.value_int.map(|i| i as u32))
+			.collect();
+		let names = Self::entity_names(&mut *self.pool.acquire().await?, ref_ids).await?;
+		let empty: Vec<TableCell> = columns.iter()
+			.map(|c| if c.allow_multiple.unwrap_or(false) { TableCell::Many(Vec::new()) } else { TableCell::Empty })
+			.collect();
+		let mut cells = vec![empty; entities.items.len()];
+		for value in values {
+			let (Some(&row), Some(&col)) = (row_at.get(&value.entity_id), col_at.get(&value.attr_id)) else { continue };
+			let Some(mut cell) = EavCell::from_value(&columns[col].value_type, &value) else { continue };
+			if let EavCell::Ref { id, entity } = &mut cell { *entity = names.get(id).cloned(); }
+			match &mut cells[row][col] {
+				TableCell::Many(list) => list.push(cell),
+				slot @ TableCell::Empty => *slot = TableCell::One(cell),
+				TableCell::One(_) => ()
+			}
+		}
+		let Page { items, total, page, per_page, has_more, next_cursor } = entities;
+		let items = items.into_iter().zip(cells).map(|(entity, cells)| TableRow { entity, cells }).collect();
+		let rows = Page { items, total, page, per_page, has_more, next_cursor };
+		println!("fetch_table: {} of {} rows, {} columns", rows.items.len(), rows.total, columns.len());
+		Ok(EavTable { entity_type, columns, rows })
+	}
+
+	// -- ATTRIBUTES --
+	async fn fetch_attrs(&self, entity_type_id: u32, multi_only: bool) -> Result<Vec<EavAttribute>, EavError> {
+		let mut rows = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE entity_type_id = ? AND deleted_at IS NULL")
+			.bind(entity_type_id)
+			.fetch_all(&self.pool)
+			.await?;
+		if multi_only {
+			rows.retain(|a| a.allow_multiple.unwrap_or(false));
+		}
+		println!("fetch_attrs: {}", rows.len());
+		Ok(rows)
+	}
+
+	async fn fetch_attr_by_id(&self, id: u32) -> Result<EavAttribute, EavError> {
+		let row = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_one(&self.pool)
+			.await?;
+		println!("fetch_attr_by_id: {}", row.id);
+		Ok(row)
+	}
+
+	async fn create_attr(&self,
+		entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool,
+		ref_entity_type_id: Option<u32>, on_delete: Option<OnDelete>
+	) -> Result<EavAttribute, EavError> {
+		if attr_type != "ref" && (ref_entity_type_id.is_some() || on_delete.is_some()) {
+			let field = if ref_entity_type_id.is_some() { "ref_entity_type_id" } else { "on_delete" };
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Only entity references take a category and delete rule").with_field(field));
+		}
+		let mut tx = self.pool.begin().await?;
+		if let Some(type_id) = ref_entity_type_id {
+			Self::ref_target(&mut tx, type_id).await?;
+		}
+		let id = DB::insert_attr(&mut tx, entity_type_id, attr_name, attr_type, allow_multiple).await?;
+		if attr_type == "ref" {
+			sqlx::query("UPDATE eav_attrs SET ref_entity_type_id = ?, on_delete = ? WHERE id = ?")
+				.bind(ref_entity_type_id).bind(on_delete.unwrap_or_default().as_str()).bind(id).execute(&mut *tx).await?;
+		}
+		println!("create_attr: {}", id);
+		let res = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+			.bind(id).fetch_one(&mut *tx).await?;
+		Self::record(&mut tx, Utc::now(), "create", None, Some(&res)).await?;
+		tx.commit().await?;
+		Ok(res)
+	}
+
+	async fn rename_attr(&self, id: u32, name: &str) -> Result<EavAttribute, EavError> {
+		let name = clean_name(name, "attr")?;
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("id"))?;
+		// same rule as create_eav_attr
+		let query = "SELECT COUNT(*) FROM eav_attrs WHERE attr = ? AND entity_type_id = ? AND id <> ?";
+		let taken = sqlx::query_as::<_, Count>(query)
+			.bind(name).bind(old.entity_type_id).bind(id)
+			.fetch_one(&mut *tx).await?.0 > 0;
+		if taken {
+			return Err(EavError::new(ErrorCode::DuplicateAttribute, "Attribute already exists").with_field("attr"));
+		}
+		sqlx::query("UPDATE eav_attrs SET attr = ? WHERE id = ?")
+			.bind(name).bind(id).execute(&mut *tx).await?;
+		let res = EavAttribute { attr: name.to_owned(), ..old.clone() };
+		Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("rename_attr: {}", id);
+		Ok(res)
+	}
+
+	async fn set_attr_allow_multiple(&self, id: u32, allow_multiple: bool, keep: Option<KeepValue>) -> Result<EavAttribute, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("id"))?;
+		if !allow_multiple {
+			// values past the first of each entity, oldest first
+			let values: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values",
+				"attr_id = ? ORDER BY entity_id, created_at, id", id).await?;
+			let mut by_entity: BTreeMap<u32, Vec<EavValue>> = BTreeMap::new();
+			for value in values { by_entity.entry(value.entity_id).or_default().push(value); }
+			by_entity.retain(|_, values| values.len() > 1);
+			match keep {
+				_ if by_entity.is_empty() => {}
+				None => {
+					let count = match by_entity.len() {
+						1 => "1 entity has".to_owned(),
+						n => n.to_string() + " entities have"
+					};
+					let msg = count + " more than one value for " + &old.attr;
+					return Err(EavError::new(ErrorCode::SingleValueViolation, &msg).with_field("allow_multiple"));
+				}
+				Some(keep) => for mut values in by_entity.into_values() {
+					match keep {
+						KeepValue::Oldest => { values.remove(0); }
+						KeepValue::Newest => { values.pop(); }
+					}
+					for value in values {
+						Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", value.id, now, Some(now)).await?;
+					}
+				}
+			}
+		}
+		sqlx::query("UPDATE eav_attrs SET allow_multiple = ? WHERE id = ?")
+			.bind(allow_multiple).bind(id).execute(&mut *tx).await?;
+		let res = EavAttribute { allow_multiple: Some(allow_multiple), ..old.clone() };
+		Self::record(&mut tx, now, "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("set_attr_allow_multiple: {} {}", id, allow_multiple);
+		Ok(res)
+	}
+
+	async fn set_attr_ref(&self, id: u32, ref_entity_type_id: Option<u32>, on_delete: OnDelete) -> Result<EavAttribute, EavError> {
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("id"))?;
+		if old.value_type != "ref" {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Attribute is not an entity reference").with_field("value_type"));
+		}
+		if let Some(type_id) = ref_entity_type_id {
+			let entity_type = Self::ref_target(&mut tx, type_id).await?;
+			let query = "SELECT COUNT(*) FROM eav_values v JOIN eav_entities e ON e.id = v.value_int ".to_owned() +
+				"WHERE v.attr_id = ? AND v.deleted_at IS NULL AND e.entity_type_id <> ?";
+			let outside = sqlx::query_as::<_, Count>(&query)
+				.bind(id).bind(type_id)
+				.fetch_one(&mut *tx).await?.0;
+			if outside > 0 {
+				let count = match outside {
+					1 => "1 value of ".to_owned(),
+					n => n.to_string() + " values of "
+				};
+				let msg = count + &old.attr + " point outside of " + &entity_type.entity_type;
+				return Err(EavError::new(ErrorCode::InvalidArgument, &msg).with_field("ref_entity_type_id"));
+			}
+		}
+		sqlx::query("UPDATE eav_attrs SET ref_entity_type_id = ?, on_delete = ? WHERE id = ?")
+			.bind(ref_entity_type_id).bind(on_delete.as_str()).bind(id).execute(&mut *tx).await?;
+		let res = EavAttribute { ref_entity_type_id, on_delete: Some(on_delete.as_str().to_owned()), ..old.clone() };
+		Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("set_attr_ref: {} {:?} {:?}", id, ref_entity_type_id, on_delete);
+		Ok(res)
+	}
+
+	async fn convert_attr_type(
+		&self, id: u32, value_type: &str, on_failure: ConvertPolicy, dry_run: bool
+	) -> Result<ConvertReport, EavError> {
+		if !VALUE_TYPES.contains(&value_type) {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Invalid value type").with_field("value_type"));
+		}
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("id"))?;
+		if old.value_type == value_type {
+			let msg = "Attribute is already ".to_owned() + value_type;
+			return Err(EavError::new(ErrorCode::InvalidArgument, &msg).with_field("value_type"));
+		}
+		if old.value_type == "ref" {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Entity references can't be converted").with_field("value_type"));
+		}
+		let entities: Vec<EavEntity> = Self::fetch_rows(&mut tx, "eav_entities", "entity_type_id = ?", old.entity_type_id).await?;
+		let names: HashMap<u32, String> = entities.into_iter().map(|e| (e.id, e.entity)).collect();
+		let values: Vec<EavValue> = Self::fetch_rows(&mut tx, "eav_values",
+			"attr_id = ? ORDER BY entity_id, created_at, id", id).await?;
+		let mut report = ConvertReport { dry_run, ..Default::default() };
+		for value in values {
+			let new = match eav_import::convert_value(&value, &old.value_type, value_type) {
+				Ok(new) => {
+					report.converted += 1;
+					new
+				}
+				Err(message) => {
+					report.failures.push(ConvertFailure {
+						value_id: value.id,
+						entity_id: value.entity_id,
+						entity: names.get(&value.entity_id).cloned().unwrap_or_default(),
+						value: eav_import::value_text(&value, &old.value_type, value_type).unwrap_or_default(),
+						message: message.to_owned(),
+						in_trash: value.deleted_at.is_some(),
+					});
+					match on_failure {
+						// values already in the trash stay there as they were
+						ConvertPolicy::Trash if value.deleted_at.is_some() => continue,
+						ConvertPolicy::Trash => {
+							Self::set_deleted_at::<EavValue>(&mut tx, "eav_values", "id = ?", value.id, now, Some(now)).await?;
+							report.trashed += 1;
+							continue;
+						}
+						ConvertPolicy::Null => {
+							report.emptied += 1;
+							EavValue { value_str: None, value_int: None, value_float: None, value_time: None, value_bool: None, ..value.clone() }
+						}
+						ConvertPolicy::Abort => continue
+					}
+				}
+			};
+			let query = "UPDATE eav_values SET ".to_owned() +
+				"value_str = ?, value_int = ?, value_float = ?, value_time = ?, value_bool = ? " +
+				"WHERE id = ?";
+			sqlx::query(&query).bind(&new.value_str).bind(new.value_int).bind(new.value_float)
+				.bind(new.value_time).bind(new.value_bool).bind(new.id)
+				.execute(&mut *tx).await?;
+			Self::record(&mut tx, now, "update", Some(&value), Some(&new)).await?;
+		}
+		sqlx::query("UPDATE eav_attrs SET value_type = ? WHERE id = ?")
+			.bind(value_type).bind(id).execute(&mut *tx).await?;
+		let attr = EavAttribute { value_type: value_type.to_owned(), ..old.clone() };
+		Self::record(&mut tx, now, "update", Some(&old), Some(&attr)).await?;
+		let aborted = on_failure == ConvertPolicy::Abort && !report.failures.is_empty();
+		report.committed = !dry_run && !aborted;
+		if report.committed {
+			tx.commit().await?;
+		} else {
+			tx.rollback().await?;
+		}
+		println!("convert_attr_type: {} to {}, {} converted, {} failed, committed: {}",
+			id, value_type, report.converted, report.failures.len(), report.committed);
+		Ok(report)
+	}
+
+	async fn delete_attr(&self, id: u32) -> Result<DeleteSummary, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		// trash values for attr
+		let values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+			"attr_id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		// trash attr
+		let attrs = Self::set_deleted_at::<EavAttribute>(&mut tx, "eav_attrs",
+			"id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		if attrs == 0 {
+			return Err(EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("id"));
+		}
+		tx.commit().await?;
+		let summary = DeleteSummary { attrs, values, ..Default::default() };
+		println!("delete_attr: {:?}", summary);
+		Ok(summary)
+	}
+
+	// -- VALUES --
+	async fn fetch_value_by_id(&self, id: u32) -> Result<EavValue, EavError> {
+		let row = sqlx::query_as::<_, EavValue>("SELECT * FROM eav_values WHERE id = ? AND deleted_at IS NULL")
+			.bind(id)
+			.fetch_one(&self.pool)
+			.await?;
+		println!("fetch_value_by_id: {}", row.id);
+		Ok(row)
+	}
+
+	async fn create_value(&self, input: EavValue) -> Result<EavValue, EavError> {
+		let attr = self.fetch_attr_by_id(input.attr_id).await?;
+		// sanitize input
+		let mut str_val: Option<String> = None;
+		let mut int_val: Option<i32> = None;
+		let mut float_val: Option<f32> = None;
+		let mut time_val: Option<DateTime<Utc>> = None;
+		let mut bool_val: Option<bool> = None;
+		let mut val_exists = true;
+		match attr.value_type.as_str() {
+			"str" => if input.value_str.is_some() {
+				str_val = input.value_str;
+			} else { val_exists = false }
+			"int" => if input.value_int.is_some() {
+				int_val = input.value_int;
+				str_val = input.value_str; // optionally append a unit
+			} else { val_exists = false }
+			"float" => if input.value_float.is_some() {
+				float_val = input.value_float;
+				str_val = input.value_str; // optionally append a unit
+			} else { val_exists = false }
+			"time" => if input.value_time.is_some() {
+				time_val = input.value_time;
+			} else { val_exists = false }
+			"bool" => if input.value_bool.is_some() {
+				bool_val = input.value_bool;
+			} else { val_exists = false }
+			"ref" => if input.value_int.is_some() {
+				int_val = input.value_int;
+			} else { val_exists = false }
+			_ => { val_exists = false }
+		}
+		if !val_exists {
+			let msg = "Value does not match attribute type ".to_owned() + &attr.value_type;
+			// refs keep the id of their entity in value_int
+			let field = if attr.value_type == "ref" { "value_int".to_owned() } else { "value_".to_owned() + &attr.value_type };
+			return Err(EavError::new(ErrorCode::InvalidValueType, &msg).with_field(&field));
+		}
+		// check and insert in one transaction so a concurrent create can't slip in between
+		let mut tx = self.pool.begin().await?;
+		// the value has to belong to a live entity of the attr's category
+		let entity = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?")
+			.bind(input.entity_id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity not found").with_field("entity_id"))?;
+		if entity.deleted_at.is_some() {
+			return Err(EavError::new(ErrorCode::InTrash, "Entity is in the trash").with_field("entity_id"));
+		}
+		if entity.entity_type_id != attr.entity_type_id {
+			return Err(EavError::new(ErrorCode::InvalidArgument, "Attribute belongs to another category").with_field("attr_id"));
+		}
+		// check for existing value on single value attrs
+		if !attr.allow_multiple.unwrap_or(false) {
+			let query = "SELECT COUNT(*) FROM eav_values WHERE entity_id = ? AND attr_id = ? AND deleted_at IS NULL";
+			let existing = sqlx::query_as::<_, Count>(query)
+				.bind(input.entity_id).bind(input.attr_id)
+				.fetch_one(&mut *tx).await?;
+			if existing.0 > 0 {
+				return Err(EavError::new(ErrorCode::SingleValueViolation, "Attribute does not allow multiple entries")
+					.with_field("attr_id"));
+			}
+		}
+		// perform insertion
+		let value = EavValue {
+			value_str: str_val, value_int: int_val, value_float: float_val, value_time: time_val, value_bool: bool_val, ..input
+		};
+		if attr.value_type == "ref" {
+			Self::check_ref(&mut tx, &attr, &value).await?;
+		}
+		let res = Self::insert_value(&mut tx, Utc::now(), &value).await?;
+		println!("create_value: {}", res.id);
+		tx.commit().await?;
+		Ok(res)
+	}
+
+	async fn update_value(&self, input: EavValue) -> Result<EavValue, EavError> {
+		let mut tx = self.pool.begin().await?;
+		let old = sqlx::query_as::<_, EavValue>("SELECT * FROM eav_values WHERE id = ? AND deleted_at IS NULL")
+			.bind(input.id).fetch_one(&mut *tx).await?;
+		let attr = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+			.bind(old.attr_id).fetch_one(&mut *tx).await?;
+		if attr.value_type == "ref" {
+			Self::check_ref(&mut tx, &attr, &input).await?;
+		}
+		let query = "UPDATE eav_values SET ".to_owned() +
+			"value_str = ?, value_int = ?, value_float = ?, value_time = ?, value_bool = ? " +
+			"WHERE id = ?";
+		let debug = sqlx::query(&query).bind(input.value_str).bind(input.value_int).bind(input.value_float)
+			.bind(input.value_time).bind(input.value_bool).bind(input.id)
+			.execute(&mut *tx).await?;
+		let res = sqlx::query_as::<_, EavValue>("SELECT * FROM eav_values WHERE id = ?")
+			.bind(input.id).fetch_one(&mut *tx).await?;
+		Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&res)).await?;
+		tx.commit().await?;
+		println!("update_value: {}", DB::rows_affected(&debug));
+		Ok(res)
+	}
+
+	async fn delete_value(&self, id: u32) -> Result<DeleteSummary, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+			"id = ? AND deleted_at IS NULL", id, now, Some(now)).await?;
+		if values == 0 {
+			return Err(EavError::new(ErrorCode::NotFound, "Value not found").with_field("id"));
+		}
+		tx.commit().await?;
+		let summary = DeleteSummary { values, ..Default::default() };
+		println!("delete_value: {:?}", summary);
+		Ok(summary)
+	}
+
+	// -- VIEWS --
+	async fn fetch_views_by_entity_id(&self, entity_id: u32, page: u32) -> Result<Vec<EavView>, EavError> {
+		let query = "SELECT * FROM all_existing_eav_data WHERE entity_id = ?".to_owned() + &page_to_limit(page, None);
+		let rows = sqlx::query_as::<_, EavView>(&query)
+			.bind(entity_id)
+			.fetch_all(&self.pool)
+			.await?;
+		println!("fetch_views_by_entity_id: {} results", rows.len());
+		Ok(rows)
+	}
+
+	async fn fetch_backlinks(&self, entity_id: u32, page: u32) -> Result<Vec<EavView>, EavError> {
+		let query = "SELECT * FROM all_existing_eav_data WHERE value_type = 'ref' AND value_int = ?".to_owned() + &page_to_limit(page, None);
+		let rows = sqlx::query_as::<_, EavView>(&query)
+			.bind(entity_id)
+			.fetch_all(&self.pool)
+			.await?;
+		println!("fetch_backlinks: {} results", rows.len());
+		Ok(rows)
+	}
+
+	// -- HISTORY --
+	// changes to the entity and its values, newest first
+	async fn fetch_entity_history(&self, entity_id: u32, page: &PageRequest) -> Result<Page<EavHistory>, EavError> {
+		let params = vec![QueryParam::Int(entity_id as i64)];
+		let order = PageOrder::Keyset { table: "eav_history", alias: "h", terms: vec![OrderTerm::new("{row}.id", true)] };
+		let res = self.fetch_page("SELECT h.*", "FROM eav_history h WHERE h.entity_id = ?", params, order, |h: &EavHistory| h.id, page).await?;
+		println!("fetch_entity_history: {} of {} results", res.items.len(), res.total);
+		Ok(res)
+	}
+
+	// rebuilds the values of an entity as they were at `at` by rolling the current rows back over later changes
+	async fn fetch_views_at(&self, entity_id: u32, at: DateTime<Utc>) -> Result<Vec<EavView>, EavError> {
+		let mut conn = self.pool.acquire().await?;
+		// current rows including trashed ones, purged rows come back from the history
+		let mut entities: BTreeMap<u32, EavEntity> = Self::fetch_rows::<EavEntity, u32>(&mut conn, "eav_entities", "id = ?", entity_id)
+			.await?.into_iter().map(|r| (r.id, r)).collect();
+		let mut values: BTreeMap<u32, EavValue> = Self::fetch_rows::<EavValue, u32>(&mut conn, "eav_values", "entity_id = ?", entity_id)
+			.await?.into_iter().map(|r| (r.id, r)).collect();
+		let history = Self::fetch_history_after(&mut conn, "entity_id = ?", entity_id, at).await?;
+		revert_rows(&mut entities, &history);
+		revert_rows(&mut values, &history);
+		let entity = match entities.remove(&entity_id) {
+			Some(e) if e.deleted_at.is_none() && e.created_at <= at => e,
+			_ => return Err(EavError::new(ErrorCode::NotFound, "Entity did not exist at that time").with_field("at"))
+		};
+		// schema of the entity type at that time
+		let type_id = entity.entity_type_id;
+		let mut types: BTreeMap<u32, EavEntityType> = Self::fetch_rows::<EavEntityType, u32>(&mut conn, "eav_entity_types", "id = ?", type_id)
+			.await?.into_iter().map(|r| (r.id, r)).collect();
+		let mut attrs: BTreeMap<u32, EavAttribute> = Self::fetch_rows::<EavAttribute, u32>(&mut conn, "eav_attrs", "entity_type_id = ?", type_id)
+			.await?.into_iter().map(|r| (r.id, r)).collect();
+		let history = Self::fetch_history_after(&mut conn, "entity_type_id = ? AND target IN ('entity_type', 'attr')", type_id, at).await?;
+		revert_rows(&mut types, &history);
+		revert_rows(&mut attrs, &history);
+		let entity_type = match types.remove(&type_id) {
+			Some(et) => et,
+			None => return Err(EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("entity_type_id"))
+		};
+		let mut rows: Vec<EavView> = values.values()
+			// values merged in later still belonged to another entity
+			.filter(|v| v.entity_id == entity_id && v.deleted_at.is_none() && v.created_at <= at)
+			.filter_map(|v| match attrs.get(&v.attr_id) {
+				Some(a) if a.deleted_at.is_none() => Some(EavView::from_value(&entity_type, &entity, a, v)),
+				_ => None
+			})
+			.collect();
+		rows.sort_by_key(|v| (v.attr_id, v.value_id));
+		// referenced entities show their current name
+		let is_ref = |v: &EavView| v.value_type.as_deref() == Some("ref");
+		let ref_ids = rows.iter().filter(|v| is_ref(v)).filter_map(|v| v.value_int.map(|i| i as u32)).collect();
+		let names = Self::entity_names(&mut conn, ref_ids).await?;
+		for row in rows.iter_mut().filter(|v| is_ref(v)) {
+			row.ref_entity = row.value_int.and_then(|i| names.get(&(i as u32)).cloned());
+		}
+		println!("fetch_views_at: {} results", rows.len());
+		Ok(rows)
+	}
+
+	async fn last_history_id(&self) -> Result<u32, EavError> {
+		let mut conn = self.pool.acquire().await?;
+		Ok(Self::history_mark(&mut conn).await?)
+	}
+
+	// undo walks the changes back newest first so parents come back before their children,
+	// redo replays them in their original order
+	async fn revert_history(&self, after_id: u32, to_id: u32, undo: bool) -> Result<Vec<EavHistory>, EavError> {
+		let order = if undo { "DESC" } else { "ASC" };
+		let action = if undo { "undo" } else { "redo" };
+		let mut tx = self.pool.begin().await?;
+		let query = "SELECT * FROM eav_history WHERE id > ? AND id <= ? ORDER BY id ".to_owned() + order;
+		let mut changes = sqlx::query_as::<_, EavHistory>(&query)
+			.bind(after_id).bind(to_id)
+			.fetch_all(&mut *tx).await?;
+		dedup_moves(&mut changes);
+		let mark = Self::history_mark(&mut tx).await?;
+		let now = Utc::now();
+		for change in changes.iter() {
+			Self::apply_change(&mut tx, now, action, change, undo).await?;
+		}
+		let mut applied = sqlx::query_as::<_, EavHistory>("SELECT * FROM eav_history WHERE id > ? ORDER BY id")
+			.bind(mark)
+			.fetch_all(&mut *tx).await?;
+		dedup_moves(&mut applied);
+		tx.commit().await?;
+		println!("revert_history: {} changes", applied.len());
+		Ok(applied)
+	}
+
+	// -- TRASH --
+	async fn fetch_trash(&self, page: &PageRequest) -> Result<Page<TrashItem>, EavError> {
+		// children trashed in the same cascade share the deleted_at of their parent and are skipped
+		let query = "SELECT 'entity_type' AS kind, id, entity_type AS name, NULL AS parent, deleted_at ".to_owned() +
+			"FROM eav_entity_types WHERE deleted_at IS NOT NULL " +
+			"UNION ALL SELECT 'entity', ee.id, ee.entity, eet.entity_type, ee.deleted_at FROM eav_entities ee " +
+			"JOIN eav_entity_types eet ON eet.id = ee.entity_type_id " +
+			"WHERE ee.deleted_at IS NOT NULL AND (eet.deleted_at IS NULL OR eet.deleted_at <> ee.deleted_at) " +
+			"UNION ALL SELECT 'attr', ea.id, ea.attr, eet.entity_type, ea.deleted_at FROM eav_attrs ea " +
+			"JOIN eav_entity_types eet ON eet.id = ea.entity_type_id " +
+			"WHERE ea.deleted_at IS NOT NULL AND (eet.deleted_at IS NULL OR eet.deleted_at <> ea.deleted_at) " +
+			"UNION ALL SELECT 'value', ev.id, ea.attr, ee.entity, ev.deleted_at FROM eav_values ev " +
+			"JOIN eav_entities ee ON ee.id = ev.entity_id " +
+			"JOIN eav_attrs ea ON ea.id = ev.attr_id " +
+			"WHERE ev.deleted_at IS NOT NULL AND (ee.deleted_at IS NULL OR ee.deleted_at <> ev.deleted_at) " +
+			"AND (ea.deleted_at IS NULL OR ea.deleted_at <> ev.deleted_at)";
+		let from = "FROM (".to_owned() + &query + ") trash";
+		let order = PageOrder::By("deleted_at DESC, kind, id");
+		let res = self.fetch_page("SELECT *", &from, Vec::new(), order, |t: &TrashItem| t.id, page).await?;
+		println!("fetch_trash: {} of {} results", res.items.len(), res.total);
+		Ok(res)
+	}
+
+	// restores the item and the children that were trashed in the same cascade
+	async fn restore_from_trash(&self, kind: TrashKind, id: u32) -> Result<DeleteSummary, EavError> {
+		// parents have to be restored first
+		let parent_query = match kind {
+			TrashKind::EntityType => None,
+			TrashKind::Entity => Some("SELECT COUNT(*) FROM eav_entities ee JOIN eav_entity_types eet ON eet.id = ee.entity_type_id ".to_owned() +
+				"WHERE ee.id = ? AND eet.deleted_at IS NOT NULL"),
+			TrashKind::Attr => Some("SELECT COUNT(*) FROM eav_attrs ea JOIN eav_entity_types eet ON eet.id = ea.entity_type_id ".to_owned() +
+				"WHERE ea.id = ? AND eet.deleted_at IS NOT NULL"),
+			TrashKind::Value => Some("SELECT COUNT(*) FROM eav_values ev ".to_owned() +
+				"JOIN eav_entities ee ON ee.id = ev.entity_id JOIN eav_attrs ea ON ea.id = ev.attr_id " +
+				"WHERE ev.id = ? AND (ee.deleted_at IS NOT NULL OR ea.deleted_at IS NOT NULL)"),
+		};
+		let mut tx = self.pool.begin().await?;
+		if let Some(query) = parent_query {
+			let trashed = sqlx::query_as::<_, Count>(&query).bind(id).fetch_one(&mut *tx).await?;
+			if trashed.0 > 0 {
+				return Err(EavError::new(ErrorCode::InTrash, "Parent is in the trash").with_field("id"));
+			}
+		}
+		let now = Utc::now();
+		let mut summary = DeleteSummary::default();
+		match kind {
+			TrashKind::EntityType => {
+				summary.values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+					"deleted_at = (SELECT deleted_at FROM eav_entity_types WHERE id = ?) \
+					AND attr_id IN (SELECT id FROM eav_attrs WHERE entity_type_id = ?)", id, now, None).await?;
+				summary.entities = Self::set_deleted_at::<EavEntity>(&mut tx, "eav_entities",
+					"entity_type_id = ? AND deleted_at = (SELECT deleted_at FROM eav_entity_types WHERE id = ?)", id, now, None).await?;
+				summary.attrs = Self::set_deleted_at::<EavAttribute>(&mut tx, "eav_attrs",
+					"entity_type_id = ? AND deleted_at = (SELECT deleted_at FROM eav_entity_types WHERE id = ?)", id, now, None).await?;
+				summary.entity_types = Self::set_deleted_at::<EavEntityType>(&mut tx, "eav_entity_types",
+					"id = ? AND deleted_at IS NOT NULL", id, now, None).await?;
+			}
+			TrashKind::Entity => {
+				summary.values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+					"entity_id = ? AND deleted_at = (SELECT deleted_at FROM eav_entities WHERE id = ?)", id, now, None).await?;
+				summary.entities = Self::set_deleted_at::<EavEntity>(&mut tx, "eav_entities",
+					"id = ? AND deleted_at IS NOT NULL", id, now, None).await?;
+			}
+			TrashKind::Attr => {
+				summary.values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+					"attr_id = ? AND deleted_at = (SELECT deleted_at FROM eav_attrs WHERE id = ?)", id, now, None).await?;
+				summary.attrs = Self::set_deleted_at::<EavAttribute>(&mut tx, "eav_attrs",
+					"id = ? AND deleted_at IS NOT NULL", id, now, None).await?;
+			}
+			TrashKind::Value => {
+				// a new value may have taken the place of a trashed single value
+				let query = "SELECT COUNT(*) FROM eav_values ev JOIN eav_values old ON old.entity_id = ev.entity_id ".to_owned() +
+					"AND old.attr_id = ev.attr_id JOIN eav_attrs ea ON ea.id = old.attr_id " +
+					"WHERE old.id = ? AND ev.deleted_at IS NULL AND ea.allow_multiple = 0";
+				let existing = sqlx::query_as::<_, Count>(&query).bind(id).fetch_one(&mut *tx).await?;
+				if existing.0 > 0 {
+					return Err(EavError::new(ErrorCode::SingleValueViolation, "Attribute does not allow multiple entries")
+						.with_field("attr_id"));
+				}
+				summary.values = Self::set_deleted_at::<EavValue>(&mut tx, "eav_values",
+					"id = ? AND deleted_at IS NOT NULL", id, now, None).await?;
+			}
+		}
+		if summary == DeleteSummary::default() {
+			return Err(EavError::new(ErrorCode::NotFound, "Item not found in trash").with_field("id"));
+		}
+		tx.commit().await?;
+		println!("restore_from_trash: {:?}", summary);
+		Ok(summary)
+	}
+
+	// permanently deletes rows that have been in the trash longer than max_age_days
+	async fn purge_trash(&self, max_age_days: u32) -> Result<DeleteSummary, EavError> {
+		let cutoff = Utc::now() - Duration::days(max_age_days as i64);
+		let mut tx = self.pool.begin().await?;
+		// children are purged with their parent, even if they were trashed later
+		let values = Self::purge_rows::<EavValue>(&mut tx, "eav_values", "deleted_at < ? \
+			OR entity_id IN (SELECT id FROM eav_entities WHERE deleted_at < ? \
+			OR entity_type_id IN (SELECT id FROM eav_entity_types WHERE deleted_at < ?)) \
+			OR attr_id IN (SELECT id FROM eav_attrs WHERE deleted_at < ? \
+			OR entity_type_id IN (SELECT id FROM eav_entity_types WHERE deleted_at < ?))", cutoff).await?;
+		let entities = Self::purge_rows::<EavEntity>(&mut tx, "eav_entities", "deleted_at < ? \
+			OR entity_type_id IN (SELECT id FROM eav_entity_types WHERE deleted_at < ?)", cutoff).await?;
+		// ref attrs restricted to a purged category point at any entity from now on
+		let restricted: Vec<EavAttribute> = Self::fetch_rows(&mut tx, "eav_attrs",
+			"ref_entity_type_id IN (SELECT id FROM eav_entity_types WHERE deleted_at < ?)", cutoff).await?;
+		for old in restricted {
+			sqlx::query("UPDATE eav_attrs SET ref_entity_type_id = NULL WHERE id = ?")
+				.bind(old.id).execute(&mut *tx).await?;
+			Self::record(&mut tx, Utc::now(), "update", Some(&old), Some(&EavAttribute { ref_entity_type_id: None, ..old.clone() })).await?;
+		}
+		let attrs = Self::purge_rows::<EavAttribute>(&mut tx, "eav_attrs", "deleted_at < ? \
+			OR entity_type_id IN (SELECT id FROM eav_entity_types WHERE deleted_at < ?)", cutoff).await?;
+		let entity_types = Self::purge_rows::<EavEntityType>(&mut tx, "eav_entity_types", "deleted_at < ?", cutoff).await?;
+		tx.commit().await?;
+		let summary = DeleteSummary { entity_types, entities, attrs, values };
+		println!("purge_trash: {:?}", summary);
+		Ok(summary)
+	}
+
+	// -- IMPORT --
+	async fn import_rows(&self, entity_type_id: u32, plan: ImportPlan, dry_run: bool) -> Result<ImportReport, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		let entity_type = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ? AND deleted_at IS NULL")
+			.bind(entity_type_id)
+			.fetch_optional(&mut *tx)
+			.await?
+			.ok_or_else(|| EavError::new(ErrorCode::NotFound, "Entity type not found").with_field("entity_type_id"))?;
+		let mut report = ImportReport { dry_run, rows: plan.total_rows, errors: plan.errors, ..Default::default() };
+		// attrs missing from the entity type are created first
+		let mut attr_ids = Vec::new();
+		for column in plan.columns.iter() {
+			let id = match column.attr_id {
+				Some(id) => id,
+				None => {
+					let id = DB::insert_attr(&mut tx, entity_type_id, &column.attr, &column.value_type, column.allow_multiple).await?;
+					let attr = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+						.bind(id).fetch_one(&mut *tx).await?;
+					Self::record(&mut tx, now, "create", None, Some(&attr)).await?;
+					report.attrs += 1;
+					id
+				}
+			};
+			attr_ids.push(id);
+		}
+		for row in plan.rows {
+			// a failed statement leaves the transaction usable, so duplicates are reported per row
+			let id = match DB::insert_entity(&mut tx, &entity_type.entity_type, &row.entity).await.map_err(EavError::from) {
+				Ok(id) => id,
+				Err(e) if e.code == ErrorCode::DuplicateEntity => {
+					report.errors.push(ImportError { line: Some(row.line), column: Some(plan.entity_column.clone()), message: e.message });
+					continue;
+				}
+				Err(e) => return Err(e)
+			};
+			let entity = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?")
+				.bind(id).fetch_one(&mut *tx).await?;
+			Self::record(&mut tx, now, "create", None, Some(&entity)).await?;
+			report.entities += 1;
+			for (column, mut value) in row.values {
+				value.entity_id = id;
+				value.attr_id = attr_ids[column];
+				Self::insert_value(&mut tx, now, &value).await?;
+				report.values += 1;
+			}
+		}
+		report.errors.sort_by_key(|e| e.line);
+		report.committed = !dry_run && report.errors.is_empty();
+		if report.committed {
+			tx.commit().await?;
+		} else {
+			tx.rollback().await?;
+		}
+		println!("import_rows: {} entities, {} attrs, {} values, {} errors, committed: {}",
+			report.entities, report.attrs, report.values, report.errors.len(), report.committed);
+		Ok(report)
+	}
+
+	// -- ARCHIVE --
+	async fn export_archive(&self, out: &mut (dyn Write + Send)) -> Result<ArchiveSummary, EavError> {
+		// one transaction for a consistent snapshot
+		let mut tx = self.pool.begin().await?;
+		let mut summary = ArchiveSummary::default();
+		// database id -> archive id
+		let mut type_ids: HashMap<u32, u32> = HashMap::new();
+		let mut attr_ids: HashMap<u32, u32> = HashMap::new();
+		let mut entity_ids: HashMap<u32, u32> = HashMap::new();
+		let mut ref_attrs: HashSet<u32> = HashSet::new();
+		eav_archive::write_header(out)?;
+		Self::for_each_row(&mut tx, "eav_entity_types", |row: EavEntityType| {
+			summary.entity_types += 1;
+			type_ids.insert(row.id, summary.entity_types as u32);
+			eav_archive::write_record(out, &ArchiveRecord::EntityType {
+				id: summary.entity_types as u32, entity_type: row.entity_type, created_at: row.created_at, deleted_at: row.deleted_at
+			})
+		}).await?;
+		Self::for_each_row(&mut tx, "eav_attrs", |row: EavAttribute| {
+			let Some(entity_type_id) = type_ids.get(&row.entity_type_id) else { return Ok(()) };
+			summary.attrs += 1;
+			attr_ids.insert(row.id, summary.attrs as u32);
+			if row.value_type == "ref" { ref_attrs.insert(row.id); }
+			eav_archive::write_record(out, &ArchiveRecord::Attr {
+				id: summary.attrs as u32, entity_type_id: *entity_type_id, attr: row.attr, value_type: row.value_type,
+				allow_multiple: row.allow_multiple.unwrap_or(false), created_at: row.created_at, deleted_at: row.deleted_at,
+				ref_entity_type_id: row.ref_entity_type_id.and_then(|id| type_ids.get(&id).copied()), on_delete: row.on_delete
+			})
+		}).await?;
+		Self::for_each_row(&mut tx, "eav_entities", |row: EavEntity| {
+			let Some(entity_type_id) = type_ids.get(&row.entity_type_id) else { return Ok(()) };
+			summary.entities += 1;
+			entity_ids.insert(row.id, summary.entities as u32);
+			eav_archive::write_record(out, &ArchiveRecord::Entity {
+				id: summary.entities as u32, entity_type_id: *entity_type_id, entity: row.entity,
+				created_at: row.created_at, deleted_at: row.deleted_at
+			})
+		}).await?;
+		Self::for_each_row(&mut tx, "eav_values", |row: EavValue| {
+			let (Some(entity_id), Some(attr_id)) = (entity_ids.get(&row.entity_id), attr_ids.get(&row.attr_id)) else { return Ok(()) };
+			summary.values += 1;
+			let value_int = match ref_attrs.contains(&row.attr_id) {
+				true => row.value_int.and_then(|id| entity_ids.get(&(id as u32))).map(|id| *id as i32),
+				false => row.value_int
+			};
+			eav_archive::write_record(out, &ArchiveRecord::Value {
+				entity_id: *entity_id, attr_id: *attr_id, created_at: row.created_at,
+				value_str: row.value_str, value_int, value_float: row.value_float,
+				value_time: row.value_time, value_bool: row.value_bool, deleted_at: row.deleted_at
+			})
+		}).await?;
+		out.flush().map_err(eav_archive::io_error)?;
+		tx.rollback().await?;
+		println!("export_archive: {:?}", summary);
+		Ok(summary)
+	}
+
+	async fn import_archive(
+		&self, records: &mut (dyn Iterator<Item = Result<ArchiveLine, EavError>> + Send), dry_run: bool, replace: bool
+	) -> Result<ArchiveReport, EavError> {
+		let now = Utc::now();
+		let mut tx = self.pool.begin().await?;
+		if replace {
+			// history entries would point at ids the restored rows reuse
+			for table in ["eav_history", "eav_values", "eav_entities", "eav_attrs", "eav_entity_types"] {
+				sqlx::query(&("DELETE FROM ".to_owned() + table)).execute(&mut *tx).await?;
+			}
+		}
+		let mut report = ArchiveReport { dry_run, ..Default::default() };
+		// archive id -> database id, None when the row was skipped
+		let mut type_ids: HashMap<u32, Option<u32>> = HashMap::new();
+		let mut attr_ids: HashMap<u32, Option<u32>> = HashMap::new();
+		let mut entity_ids: HashMap<u32, Option<u32>> = HashMap::new();
+		// database ids of ref attrs, their values get entity ids mapped as well
+		let mut ref_attrs: HashSet<u32> = HashSet::new();
+		// trashed rows are added live and trashed at the end, so their children can still be inserted
+		let mut trashed: Vec<(&str, u32, DateTime<Utc>)> = Vec::new();
+		let unknown = |line: u64, what: &str, id: u32| EavError::new(ErrorCode::InvalidArgument,
+			&("Line ".to_owned() + &line.to_string() + ": Unknown " + what + " " + &id.to_string())).with_field("path");
+		for record in records {
+			let (line, record) = record?;
+			let conflict = |column: &str, message: String| ImportError { line: Some(line), column: Some(column.to_owned()), message };
+			match record {
+				ArchiveRecord::EntityType { id, entity_type, created_at, deleted_at } => {
+					// a category with the same name is reused unless it's in the trash
+					let existing = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE entity_type = ?")
+						.bind(&entity_type).fetch_optional(&mut *tx).await?;
+					let db_id = match existing {
+						Some(et) if et.deleted_at.is_none() => Some(et.id),
+						Some(_) => {
+							report.conflicts.push(conflict("entity_type", "Category ".to_owned() + &entity_type + " is in the trash"));
+							None
+						}
+						None => {
+							let res = sqlx::query("INSERT INTO eav_entity_types (entity_type, created_at) VALUES (?, ?)")
+								.bind(&entity_type).bind(created_at).execute(&mut *tx).await?;
+							let et = sqlx::query_as::<_, EavEntityType>("SELECT * FROM eav_entity_types WHERE id = ?")
+								.bind(DB::last_insert_id(&res)).fetch_one(&mut *tx).await?;
+							Self::record(&mut tx, now, "create", None, Some(&et)).await?;
+							if let Some(at) = deleted_at { trashed.push(("eav_entity_types", et.id, at)); }
+							report.entity_types += 1;
+							Some(et.id)
+						}
+					};
+					type_ids.insert(id, db_id);
+				}
+				ArchiveRecord::Attr {
+					id, entity_type_id, attr, value_type, allow_multiple, created_at, deleted_at, ref_entity_type_id, on_delete
+				} => {
+					if !VALUE_TYPES.contains(&value_type.as_str()) && value_type != "ref" {
+						return Err(EavError::new(ErrorCode::InvalidArgument,
+							&("Line ".to_owned() + &line.to_string() + ": Invalid value type " + &value_type)).with_field("path"));
+					}
+					let Some(type_id) = *type_ids.get(&entity_type_id).ok_or_else(|| unknown(line, "category", entity_type_id))? else {
+						attr_ids.insert(id, None);
+						report.skipped += 1;
+						continue;
+					};
+					// a restriction to a skipped category is dropped
+					let ref_type_id = ref_entity_type_id.and_then(|id| type_ids.get(&id).copied().flatten());
+					let rule = |on_delete: &Option<String>| on_delete.clone().unwrap_or_else(|| OnDelete::default().as_str().to_owned());
+					// an attribute with the same name is reused when it has the same definition, refs with the same category and rule
+					let existing = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE entity_type_id = ? AND attr = ?")
+						.bind(type_id).bind(&attr).fetch_optional(&mut *tx).await?;
+					let same = |a: &EavAttribute| a.value_type == value_type && a.allow_multiple.unwrap_or(false) == allow_multiple;
+					let same_ref = |a: &EavAttribute| value_type != "ref" || (a.ref_entity_type_id == ref_type_id && rule(&a.on_delete) == rule(&on_delete));
+					let db_id = match existing {
+						Some(a) if a.deleted_at.is_none() && same(&a) && same_ref(&a) => Some(a.id),
+						Some(a) if a.deleted_at.is_some() => {
+							report.conflicts.push(conflict("attr", "Attribute ".to_owned() + &attr + " is in the trash"));
+							None
+						}
+						Some(a) if same(&a) => {
+							report.conflicts.push(conflict("attr",
+								"Attribute ".to_owned() + &attr + " already exists with another category or delete rule"));
+							None
+						}
+						Some(a) => {
+							let multiple = if a.allow_multiple.unwrap_or(false) { " with multiple values" } else { "" };
+							report.conflicts.push(conflict("attr",
+								"Attribute ".to_owned() + &attr + " already exists as " + &a.value_type + multiple));
+							None
+						}
+						None => {
+							let query = "INSERT INTO eav_attrs ".to_owned() +
+								"(entity_type_id, attr, value_type, allow_multiple, created_at, ref_entity_type_id, on_delete) " +
+								"VALUES (?, ?, ?, ?, ?, ?, ?)";
+							let res = sqlx::query(&query)
+								.bind(type_id).bind(&attr).bind(&value_type).bind(allow_multiple).bind(created_at)
+								.bind(ref_type_id).bind(&on_delete)
+								.execute(&mut *tx).await?;
+							let a = sqlx::query_as::<_, EavAttribute>("SELECT * FROM eav_attrs WHERE id = ?")
+								.bind(DB::last_insert_id(&res)).fetch_one(&mut *tx).await?;
+							Self::record(&mut tx, now, "create", None, Some(&a)).await?;
+							if let Some(at) = deleted_at { trashed.push(("eav_attrs", a.id, at)); }
+							report.attrs += 1;
+							Some(a.id)
+						}
+					};
+					if value_type == "ref" { ref_attrs.extend(db_id); }
+					attr_ids.insert(id, db_id);
+				}
+				ArchiveRecord::Entity { id, entity_type_id, entity, created_at, deleted_at } => {
+					let Some(type_id) = *type_ids.get(&entity_type_id).ok_or_else(|| unknown(line, "category", entity_type_id))? else {
+						entity_ids.insert(id, None);
+						report.skipped += 1;
+						continue;
+					};
+					let existing = sqlx::query_as::<_, Count>("SELECT COUNT(*) FROM eav_entities WHERE entity_type_id = ? AND entity = ?")
+						.bind(type_id).bind(&entity).fetch_one(&mut *tx).await?.0 > 0;
+					if existing {
+						report.conflicts.push(conflict("entity", "Entity ".to_owned() + &entity + " already exists"));
+						entity_ids.insert(id, None);
+						continue;
+					}
+					let res = sqlx::query("INSERT INTO eav_entities (entity_type_id, entity, created_at) VALUES (?, ?, ?)")
+						.bind(type_id).bind(&entity).bind(created_at).execute(&mut *tx).await?;
+					let e = sqlx::query_as::<_, EavEntity>("SELECT * FROM eav_entities WHERE id = ?")
+						.bind(DB::last_insert_id(&res)).fetch_one(&mut *tx).await?;
+					Self::record(&mut tx, now, "create", None, Some(&e)).await?;
+					if let Some(at) = deleted_at { trashed.push(("eav_entities", e.id, at)); }
+					report.entities += 1;
+					entity_ids.insert(id, Some(e.id));
+				}
+				ArchiveRecord::Value {
+					entity_id, attr_id, created_at, value_str, value_int, value_float, value_time, value_bool, deleted_at
+				} => {
+					let entity_id = *entity_ids.get(&entity_id).ok_or_else(|| unknown(line, "entity", entity_id))?;
+					let attr_id = *attr_ids.get(&attr_id).ok_or_else(|| unknown(line, "attribute", attr_id))?;
+					let (Some(entity_id), Some(attr_id)) = (entity_id, attr_id) else {
+						report.skipped += 1;
+						continue;
+					};
+					// refs to entities that weren't added are left out
+					let value_int = match (ref_attrs.contains(&attr_id), value_int) {
+						(true, Some(id)) => match entity_ids.get(&(id as u32)) {
+							Some(Some(id)) => Some(*id as i32),
+							_ => {
+								report.skipped += 1;
+								continue;
+							}
+						},
+						(_, value_int) => value_int
+					};
+					let query = "INSERT INTO eav_values ".to_owned() +
+						"(entity_id, attr_id, value_str, value_int, value_float, value_time, value_bool, created_at) " +
+						"VALUES (?, ?, ?, ?, ?, ?, ?, ?)";
+					let res = sqlx::query(&query)
+						.bind(entity_id).bind(attr_id).bind(value_str).bind(value_int)
+						.bind(value_float).bind(value_time).bind(value_bool).bind(created_at)
+						.execute(&mut *tx).await?;
+					let v = sqlx::query_as::<_, EavValue>("SELECT * FROM eav_values WHERE id = ?")
+						.bind(DB::last_insert_id(&res)).fetch_one(&mut *tx).await?;
+					Self::record(&mut tx, now, "create", None, Some(&v)).await?;
+					if let Some(at) = deleted_at { trashed.push(("eav_values", v.id, at)); }
+					report.values += 1;
+				}
+			}
+		}
+		// children first, like a delete
+		for (table, id, at) in trashed.into_iter().rev() {
+			match table {
+				"eav_entity_types" => Self::set_deleted_at::<EavEntityType>(&mut tx, table, "id = ?", id, now, Some(at)).await?,
+				"eav_attrs" => Self::set_deleted_at::<EavAttribute>(&mut tx, table, "id = ?", id, now, Some(at)).await?,
+				"eav_entities" => Self::set_deleted_at::<EavEntity>(&mut tx, table, "id = ?", id, now, Some(at)).await?,
+				_ => Self::set_deleted_at::<EavValue>(&mut tx, table, "id = ?", id, now, Some(at)).await?,
+			};
+		}
+		report.committed = !dry_run;
+		if report.committed {
+			tx.commit().await?;
+		} else {
+			tx.rollback().await?;
+		}
+		println!("import_archive: {} entity types, {} entities, {} attrs, {} values, {} skipped, {} conflicts, committed: {}",
+			report.entity_types, report.entities, report.attrs, report.values, report.skipped, report.conflicts.len(), report.committed);
+		Ok(report)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::db_sqlite;
+	use crate::eav_structs::ColumnMapping;
+
+	fn value(entity_id: u32, attr_id: u32, text: &str) -> EavValue {
+		EavValue {
+			id: 0, created_at: Utc::now(), entity_id, attr_id, value_str: Some(text.to_owned()), value_int: None,
+			value_float: None, value_time: None, value_bool: None, deleted_at: None
+		}
+	}
+
+	#[async_std::test]
+	async fn entities_are_not_created_in_a_trashed_type() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let dune = store.create_entity("book", "Dune").await.unwrap();
+		store.delete_entity_type(dune.entity_type_id).await.unwrap();
+		let e = store.create_entity("book", "Emma").await.unwrap_err();
+		assert_eq!((e.code, e.field.as_deref()), (ErrorCode::InTrash, Some("entity_type")));
+		let trash = store.fetch_trash(&PageRequest::default()).await.unwrap();
+		assert!(trash.items.iter().all(|t| t.name.as_deref() != Some("Emma")));
+
+		store.restore_from_trash(TrashKind::EntityType, dune.entity_type_id).await.unwrap();
+		let emma = store.create_entity("book", "Emma").await.unwrap();
+		assert_eq!(emma.entity_type_id, dune.entity_type_id);
+	}
+
+	// a moment strictly between the changes before and after it
+	async fn moment() -> DateTime<Utc> {
+		async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+		let at = Utc::now();
+		async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+		at
+	}
+
+	#[async_std::test]
+	async fn history_replays_an_entity_to_any_moment() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let before = moment().await;
+		let dune = store.create_entity("book", "Dune").await.unwrap();
+		let attr = store.create_attr(dune.entity_type_id, "title", "str", false, None, None).await.unwrap();
+		let draft = store.create_value(value(dune.id, attr.id, "Dune (draft)")).await.unwrap();
+		let drafted = moment().await;
+		store.update_value(EavValue { value_str: Some("Dune".to_owned()), ..draft.clone() }).await.unwrap();
+		store.rename_attr(attr.id, "name").await.unwrap();
+		let renamed = moment().await;
+		store.delete_value(draft.id).await.unwrap();
+		let deleted = moment().await;
+		// purged rows are rebuilt from the history
+		store.purge_trash(0).await.unwrap();
+
+		let e = store.fetch_views_at(dune.id, before).await.unwrap_err();
+		assert_eq!(e.code, ErrorCode::NotFound);
+		let cases = [(drafted, vec![("title", "Dune (draft)")]), (renamed, vec![("name", "Dune")]), (deleted, vec![])];
+		for (at, expected) in cases {
+			let views = store.fetch_views_at(dune.id, at).await.unwrap();
+			let got: Vec<(&str, &str)> = views.iter()
+				.map(|v| (v.attr.as_deref().unwrap_or_default(), v.value_str.as_deref().unwrap_or_default()))
+				.collect();
+			assert_eq!(got, expected, "at {}", at);
+		}
+
+		let history = store.fetch_entity_history(dune.id, &PageRequest::default()).await.unwrap();
+		let changes: Vec<(&str, &str)> = history.items.iter().map(|h| (h.target.as_str(), h.action.as_str())).collect();
+		assert_eq!(changes, [("value", "purge"), ("value", "delete"), ("value", "update"), ("value", "create"), ("entity", "create")]);
+		let update = &history.items[2];
+		assert!(update.old_data.as_deref().unwrap().contains("Dune (draft)"));
+		assert!(update.new_data.as_deref().unwrap().contains("\"Dune\""));
+	}
+
+	async fn import(store: &impl EavStore, entity_type_id: u32, text: &str, dry_run: bool) -> ImportReport {
+		let options = eav_import::CsvOptions::new(None, None).unwrap();
+		let pages = ColumnMapping { column: 1, attr: "pages".to_owned(), value_type: "int".to_owned(), allow_multiple: false, attr_id: None };
+		let plan = eav_import::plan(text, &options, 0, vec![pages], &[]).unwrap();
+		store.import_rows(entity_type_id, plan, dry_run).await.unwrap()
+	}
+
+	async fn names(store: &impl EavStore, entity_type_id: u32) -> Vec<String> {
+		let page = store.fetch_entities(entity_type_id, &[], &PageRequest::default()).await.unwrap();
+		page.items.into_iter().map(|e| e.entity).collect()
+	}
+
+	#[async_std::test]
+	async fn imports_are_all_or_nothing() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let dune = store.create_entity("book", "Dune").await.unwrap();
+		let book = dune.entity_type_id;
+
+		let report = import(&store, book, "title,pages\nEmma,474\nIvanhoe,544\n", true).await;
+		assert_eq!((report.rows, report.entities, report.attrs, report.values), (2, 2, 1, 2));
+		assert!(report.dry_run && !report.committed);
+		assert_eq!(names(&store, book).await, ["Dune"]);
+		assert!(store.fetch_attrs(book, false).await.unwrap().is_empty());
+
+		// one duplicate keeps every other row out too
+		let report = import(&store, book, "title,pages\nEmma,474\nDune,412\nIvanhoe,544\n", false).await;
+		assert!(!report.committed);
+		let lines: Vec<Option<u64>> = report.errors.iter().map(|e| e.line).collect();
+		assert_eq!(lines, [Some(3)]);
+		assert_eq!(names(&store, book).await, ["Dune"]);
+		assert!(store.fetch_attrs(book, false).await.unwrap().is_empty());
+
+		let report = import(&store, book, "title,pages\nEmma,474\nIvanhoe,544\n", false).await;
+		assert!(report.committed && report.errors.is_empty());
+		let mut imported = names(&store, book).await;
+		imported.sort();
+		assert_eq!(imported, ["Dune", "Emma", "Ivanhoe"]);
+		let attrs = store.fetch_attrs(book, false).await.unwrap();
+		assert_eq!(attrs.iter().map(|a| (a.attr.as_str(), a.value_type.as_str())).collect::<Vec<_>>(), [("pages", "int")]);
+	}
+
+	async fn archive(store: &impl EavStore) -> Vec<u8> {
+		let mut out = Vec::new();
+		store.export_archive(&mut out).await.unwrap();
+		out
+	}
+
+	async fn restore(store: &impl EavStore, archived: &[u8]) -> ArchiveReport {
+		let mut records = eav_archive::ArchiveReader::new(archived).unwrap();
+		store.import_archive(&mut records, false, false).await.unwrap()
+	}
+
+	#[async_std::test]
+	async fn archives_restore_to_the_same_bytes() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let dune = store.create_entity("book", "Dune").await.unwrap();
+		let emma = store.create_entity("book", "Emma").await.unwrap();
+		let ivanhoe = store.create_entity("book", "Ivanhoe").await.unwrap();
+		let book = dune.entity_type_id;
+		let title = store.create_attr(book, "title", "str", true, None, None).await.unwrap();
+		let isbn = store.create_attr(book, "isbn", "str", false, None, None).await.unwrap();
+		let sequel = store.create_attr(book, "sequel", "ref", false, Some(book), None).await.unwrap();
+		store.create_value(value(dune.id, title.id, "Dune")).await.unwrap();
+		let draft = store.create_value(value(dune.id, title.id, "Dune (draft)")).await.unwrap();
+		store.create_value(value(dune.id, isbn.id, "0441013597")).await.unwrap();
+		store.create_value(value(ivanhoe.id, title.id, "Ivanhoe")).await.unwrap();
+		store.create_value(EavValue { value_str: None, value_int: Some(emma.id as i32), ..value(dune.id, sequel.id, "") }).await.unwrap();
+		let film = store.create_entity("film", "Dune").await.unwrap();
+		store.delete_value(draft.id).await.unwrap();
+		store.delete_attr(isbn.id).await.unwrap();
+		store.delete_entity(ivanhoe.id).await.unwrap();
+		store.delete_entity_type(film.entity_type_id).await.unwrap();
+
+		let archived = archive(&store).await;
+		assert_eq!(String::from_utf8_lossy(&archived).matches("\"deleted_at\"").count(), 7);
+		let fresh = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let report = restore(&fresh, &archived).await;
+		assert!(report.committed && report.conflicts.is_empty());
+		assert_eq!((report.entity_types, report.entities, report.attrs, report.values, report.skipped), (2, 4, 3, 5, 0));
+		assert_eq!(String::from_utf8(archive(&fresh).await).unwrap(), String::from_utf8(archived.clone()).unwrap());
+
+		// same names in a store with other rows are reported and left alone
+		let other = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let kept = other.create_entity("book", "Dune").await.unwrap();
+		other.create_attr(kept.entity_type_id, "title", "int", false, None, None).await.unwrap();
+		let report = restore(&other, &archived).await;
+		assert!(report.committed);
+		let conflicts: Vec<(&str, &str)> = report.conflicts.iter()
+			.map(|c| (c.column.as_deref().unwrap_or_default(), c.message.as_str()))
+			.collect();
+		assert_eq!(conflicts, [("attr", "Attribute title already exists as int"), ("entity", "Entity Dune already exists")]);
+		let mut names: Vec<String> = other.fetch_entities(kept.entity_type_id, &[], &PageRequest::default()).await.unwrap()
+			.items.into_iter().map(|e| e.entity).collect();
+		names.sort();
+		assert_eq!(names, ["Dune", "Emma"]);
+	}
+
+	#[async_std::test]
+	async fn conversions_reach_into_the_trash() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let book = store.create_entity_type("book").await.unwrap();
+		let attr = store.create_attr(book.id, "pages", "str", false, None, None).await.unwrap().id;
+		let mut values = Vec::new();
+		for (name, pages, trashed) in [("Dune", "412", false), ("Emma", "474", true), ("Ivanhoe", "many", false), ("Frank", "lots", true)] {
+			let entity = store.create_entity("book", name).await.unwrap();
+			let v = store.create_value(value(entity.id, attr, pages)).await.unwrap();
+			if trashed { store.delete_value(v.id).await.unwrap(); }
+			values.push(v);
+		}
+		let failed = |report: &ConvertReport| -> Vec<(String, bool)> {
+			report.failures.iter().map(|f| (f.entity.clone(), f.in_trash)).collect()
+		};
+
+		let report = store.convert_attr_type(attr, "int", ConvertPolicy::Abort, false).await.unwrap();
+		assert!(!report.committed);
+		assert_eq!(failed(&report), [("Ivanhoe".to_owned(), false), ("Frank".to_owned(), true)]);
+
+		let report = store.convert_attr_type(attr, "int", ConvertPolicy::Trash, false).await.unwrap();
+		assert!(report.committed);
+		assert_eq!((report.converted, report.trashed, report.failures.len()), (2, 1, 2));
+		assert_eq!(store.fetch_value_by_id(values[0].id).await.unwrap().value_int, Some(412));
+		assert_eq!(store.fetch_value_by_id(values[2].id).await.unwrap_err().code, ErrorCode::NotFound);
+		// a restored value fits the new type
+		store.restore_from_trash(TrashKind::Value, values[1].id).await.unwrap();
+		let emma = store.fetch_value_by_id(values[1].id).await.unwrap();
+		assert_eq!((emma.value_int, emma.value_str), (Some(474), None));
+	}
+
+	#[async_std::test]
+	async fn values_need_a_live_entity_of_the_attr_category() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let dune = store.create_entity("book", "Dune").await.unwrap();
+		let emma = store.create_entity("book", "Emma").await.unwrap();
+		let alien = store.create_entity("film", "Alien").await.unwrap();
+		let title = store.create_attr(dune.entity_type_id, "title", "str", false, None, None).await.unwrap();
+		let sequel = store.create_attr(dune.entity_type_id, "sequel", "ref", false, None, None).await.unwrap();
+		store.delete_entity(emma.id).await.unwrap();
+		let link = EavValue { value_str: None, value_int: Some(dune.id as i32), ..value(alien.id, sequel.id, "") };
+		let cases = [
+			(value(999, title.id, "Dune"), ErrorCode::NotFound, "entity_id"),
+			(value(emma.id, title.id, "Emma"), ErrorCode::InTrash, "entity_id"),
+			(value(alien.id, title.id, "Alien"), ErrorCode::InvalidArgument, "attr_id"),
+			(link, ErrorCode::InvalidArgument, "attr_id"),
+		];
+		for (input, code, field) in cases {
+			let e = store.create_value(input).await.unwrap_err();
+			assert_eq!((e.code, e.field.as_deref()), (code, Some(field)), "{}", e.message);
+		}
+		assert!(store.fetch_views_by_entity_id(alien.id, 0).await.unwrap().is_empty());
+		store.create_value(value(dune.id, title.id, "Dune")).await.unwrap();
+	}
+
+	#[async_std::test]
+	async fn merged_values_stay_in_the_past_of_the_source() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let source = store.create_entity("book", "The Hobbit").await.unwrap();
+		let target = store.create_entity("book", "Hobbit, The").await.unwrap();
+		let attr = store.create_attr(source.entity_type_id, "isbn", "str", false, None, None).await.unwrap();
+		store.create_value(value(source.id, attr.id, "978-0261102217")).await.unwrap();
+		async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+		let before = Utc::now();
+		async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+		let report = store.merge_entities(source.id, target.id, &[], false).await.unwrap();
+		assert_eq!(report.moved, 1);
+
+		let past = store.fetch_views_at(source.id, before).await.unwrap();
+		assert_eq!(past.len(), 1);
+		assert_eq!(past[0].value_str.as_deref(), Some("978-0261102217"));
+		assert!(store.fetch_views_at(target.id, before).await.unwrap().is_empty());
+		let moves = |h: &Page<EavHistory>| h.items.iter().filter(|c| c.target == "value" && c.action == "update").count();
+		assert_eq!(moves(&store.fetch_entity_history(source.id, &PageRequest::default()).await.unwrap()), 1);
+		assert_eq!(moves(&store.fetch_entity_history(target.id, &PageRequest::default()).await.unwrap()), 1);
+	}
+
+	#[async_std::test]
+	async fn merges_keep_one_of_each_value() {
+		let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+		let target = store.create_entity("book", "Dune").await.unwrap();
+		let source = store.create_entity("book", "Dune (novel)").await.unwrap();
+		let emma = store.create_entity("book", "Emma").await.unwrap();
+		let book = target.entity_type_id;
+		let mut attrs = HashMap::new();
+		for (name, allow_multiple) in [("title", false), ("subtitle", false), ("year", false), ("pages", false), ("tags", true)] {
+			attrs.insert(name, store.create_attr(book, name, "str", allow_multiple, None, None).await.unwrap().id);
+		}
+		let sequel = store.create_attr(book, "sequel", "ref", false, Some(book), None).await.unwrap().id;
+		let link = |from: u32, to: u32| EavValue { value_str: None, value_int: Some(to as i32), ..value(from, sequel, "") };
+		let older = [
+			(target.id, "title", "Dune"), (source.id, "title", "Dune, 1965"),
+			(target.id, "subtitle", "a novel"), (source.id, "subtitle", "book one"),
+			(source.id, "year", "1966"), (target.id, "pages", "412"),
+			(target.id, "tags", "sf"), (target.id, "tags", "classic"), (source.id, "tags", "classic"), (source.id, "tags", "desert"),
+		];
+		for (entity_id, attr, text) in older {
+			store.create_value(value(entity_id, attrs[attr], text)).await.unwrap();
+		}
+		// created_at is stored to the second, keep_newest needs the next one
+		async_std::task::sleep(std::time::Duration::from_millis(1100)).await;
+		for (entity_id, attr, text) in [(target.id, "year", "1965"), (source.id, "pages", "896")] {
+			store.create_value(value(entity_id, attrs[attr], text)).await.unwrap();
+		}
+		// refs between the two, and one from elsewhere
+		store.create_value(link(target.id, source.id)).await.unwrap();
+		store.create_value(link(source.id, target.id)).await.unwrap();
+		store.create_value(link(emma.id, source.id)).await.unwrap();
+		let strategies = [
+			AttrStrategy { attr_id: attrs["subtitle"], strategy: MergeStrategy::KeepSource },
+			AttrStrategy { attr_id: attrs["year"], strategy: MergeStrategy::KeepNewest },
+			AttrStrategy { attr_id: attrs["pages"], strategy: MergeStrategy::KeepNewest },
+		];
+
+		let report = store.merge_entities(source.id, target.id, &strategies, false).await.unwrap();
+		assert_eq!((report.moved, report.duplicates, report.relinked, report.self_refs), (3, 1, 1, 2));
+		let kept: Vec<(&str, bool)> = report.conflicts.iter().map(|c| (c.attr.as_str(), c.kept_source)).collect();
+		assert_eq!(kept, [("title", false), ("subtitle", true), ("year", false), ("pages", true)]);
+		let mut merged: Vec<(String, String)> = store.fetch_views_by_entity_id(target.id, 0).await.unwrap().into_iter()
+			.map(|v| (v.attr.unwrap_or_default(), v.value_str.unwrap_or_default()))
+			.collect();
+		merged.sort();
+		let expected = [("pages", "896"), ("subtitle", "book one"), ("tags", "classic"), ("tags", "desert"), ("tags", "sf"),
+			("title", "Dune"), ("year", "1965")];
+		assert_eq!(merged, expected.map(|(a, v)| (a.to_owned(), v.to_owned())));
+		let links: Vec<Option<i32>> = store.fetch_views_by_entity_id(emma.id, 0).await.unwrap().into_iter().map(|v| v.value_int).collect();
+		assert_eq!(links, [Some(target.id as i32)]);
+	}
+}
@@ -0,0 +1,144 @@
+// JSON Lines archive of the whole database, for backups, migrations and sharing.
+// The first line names the format, every other line is one row. Rows are written parents first and in id order,
+// and refer to each other by archive ids numbered from 1, so restoring an archive into an empty database
+// and archiving it again gives the same file byte for byte.
+
+use std::io::{BufRead, Write};
+
+use chrono::{DateTime, Utc};
+
+use crate::eav_error::{EavError, ErrorCode};
+
+pub const FORMAT: &str = "taureav-archive";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveHeader {
+  pub format: String,
+  pub version: u32,
+}
+
+// one row, `id` and the *_id references are archive ids rather than database ids
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArchiveRecord {
+  EntityType {
+    id: u32,
+    entity_type: String,
+    created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+  },
+  Attr {
+    id: u32,
+    entity_type_id: u32,
+    attr: String,
+    value_type: String,
+    allow_multiple: bool,
+    created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ref_entity_type_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    on_delete: Option<String>,
+  },
+  Entity {
+    id: u32,
+    entity_type_id: u32,
+    entity: String,
+    created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+  },
+  // value_int of a ref value is the archive id of the entity it points to
+  Value {
+    entity_id: u32,
+    attr_id: u32,
+    created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_str: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_int: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_float: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value_bool: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+  },
+}
+
+// a record with the line it was read from, for the import report
+pub type ArchiveLine = (u64, ArchiveRecord);
+
+pub fn io_error(e: impl std::fmt::Display) -> EavError {
+  EavError::new(ErrorCode::Io, &e.to_string()).with_field("path")
+}
+
+pub fn write_header(out: &mut dyn Write) -> Result<(), EavError> {
+  write_line(out, &ArchiveHeader { format: FORMAT.to_owned(), version: VERSION })
+}
+
+pub fn write_record(out: &mut dyn Write, record: &ArchiveRecord) -> Result<(), EavError> {
+  write_line(out, record)
+}
+
+fn write_line<T: serde::Serialize>(out: &mut dyn Write, line: &T) -> Result<(), EavError> {
+  serde_json::to_writer(&mut *out, line).map_err(io_error)?;
+  out.write_all(b"\n").map_err(io_error)
+}
+
+fn archive_error(line: u64, message: &str) -> EavError {
+  EavError::new(ErrorCode::InvalidArgument, &("Line ".to_owned() + &line.to_string() + ": " + message)).with_field("path")
+}
+
+// records of an archive one line at a time, the header is checked before the first record
+pub struct ArchiveReader<R: BufRead> {
+  lines: std::io::Lines<R>,
+  line: u64,
+}
+
+impl<R: BufRead> ArchiveReader<R> {
+  pub fn new(input: R) -> Result<Self, EavError> {
+    let mut reader = ArchiveReader { lines: input.lines(), line: 0 };
+    let header = match reader.next_line()? {
+      Some(text) => serde_json::from_str::<ArchiveHeader>(&text).ok(),
+      None => None
+    };
+    match header {
+      Some(h) if h.format == FORMAT && h.version <= VERSION => Ok(reader),
+      Some(h) if h.format == FORMAT => Err(archive_error(1, "Archive was written by a newer version of the app")),
+      _ => Err(archive_error(1, "Not an archive file")),
+    }
+  }
+
+  // next line that isn't blank
+  fn next_line(&mut self) -> Result<Option<String>, EavError> {
+    for text in self.lines.by_ref() {
+      self.line += 1;
+      let text = text.map_err(io_error)?;
+      let text = text.trim_start_matches('\u{feff}').trim();
+      if !text.is_empty() { return Ok(Some(text.to_owned())); }
+    }
+    Ok(None)
+  }
+}
+
+impl<R: BufRead> Iterator for ArchiveReader<R> {
+  type Item = Result<ArchiveLine, EavError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.next_line() {
+      Ok(Some(text)) => Some(
+        serde_json::from_str::<ArchiveRecord>(&text)
+          .map(|record| (self.line, record))
+          .map_err(|e| archive_error(self.line, &e.to_string()))
+      ),
+      Ok(None) => None,
+      Err(e) => Some(Err(e)),
+    }
+  }
+}
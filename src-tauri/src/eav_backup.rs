@@ -0,0 +1,171 @@
+// Gzipped archives of the whole database in the backup directory, named after the time they were taken and why,
+// like taureav-20240131-154500-123-manual.jsonl.gz. Every new backup prunes the old ones by the BackupConfig rules.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::config::BackupConfig;
+use crate::db_store::EavStore;
+use crate::eav_archive::{self, ArchiveReader};
+use crate::eav_error::{EavError, ErrorCode};
+
+const PREFIX: &str = "taureav-";
+const EXTENSION: &str = ".jsonl.gz";
+// milliseconds keep two snapshots in the same second apart
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BackupInfo {
+  pub name: String,
+  pub created_at: DateTime<Utc>,
+  // manual, start or the command it was taken before
+  pub reason: String,
+  pub size: u64,
+}
+
+impl BackupInfo {
+  // None for files in the directory that aren't backups
+  fn parse(name: &str, size: u64) -> Option<Self> {
+    let rest = name.strip_prefix(PREFIX)?.strip_suffix(EXTENSION)?;
+    // the timestamp is 19 characters, the reason follows after a dash
+    let (time, reason) = (rest.get(..19)?, rest.get(20..)?);
+    if reason.is_empty() || !reason.chars().all(|c| c.is_ascii_lowercase() || c == '_') { return None; }
+    let created_at = NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?.and_utc();
+    Some(BackupInfo { name: name.to_owned(), created_at, reason: reason.to_owned(), size })
+  }
+}
+
+fn io_error(e: impl std::fmt::Display) -> EavError {
+  EavError::new(ErrorCode::Io, &("Backup failed: ".to_owned() + &e.to_string())).with_field("dir")
+}
+
+// backups in `dir`, newest first. a missing directory has none
+pub fn list(dir: &Path) -> Result<Vec<BackupInfo>, EavError> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(e) => return Err(io_error(e))
+  };
+  let mut backups: Vec<BackupInfo> = entries
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| {
+      let size = entry.metadata().ok()?.len();
+      BackupInfo::parse(entry.file_name().to_str()?, size)
+    })
+    .collect();
+  backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.name.cmp(&a.name)));
+  Ok(backups)
+}
+
+// backups the retention rules drop: the newest `keep_last` are kept, and so is the newest one of each day
+// for `keep_daily_days`. the newest backup is always kept
+pub fn expired<'a>(backups: &'a [BackupInfo], config: &BackupConfig, now: DateTime<Utc>) -> Vec<&'a BackupInfo> {
+  let mut days = HashSet::new();
+  let daily_cutoff = now - Duration::days(config.keep_daily_days as i64);
+  backups.iter().enumerate()
+    .filter(|(i, backup)| {
+      let newest_of_day = days.insert(backup.created_at.date_naive());
+      let keep = *i < config.keep_last.max(1) || (newest_of_day && backup.created_at > daily_cutoff);
+      !keep
+    })
+    .map(|(_, backup)| backup)
+    .collect()
+}
+
+// writes a new backup and prunes the expired ones
+pub async fn create(store: &dyn EavStore, config: &BackupConfig, reason: &str) -> Result<BackupInfo, EavError> {
+  fs::create_dir_all(&config.dir).map_err(io_error)?;
+  let name = PREFIX.to_owned() + &Utc::now().format(TIME_FORMAT).to_string() + "-" + reason + EXTENSION;
+  let mut backup = BackupInfo::parse(&name, 0)
+    .ok_or_else(|| EavError::new(ErrorCode::InvalidArgument, "Invalid backup reason").with_field("reason"))?;
+  let path = config.dir.join(&name);
+  // written under another name first, so a failed backup never looks like a good one
+  let part = config.dir.join(name.clone() + ".part");
+  let file = File::create(&part).map_err(io_error)?;
+  let mut out = GzEncoder::new(BufWriter::new(file), Compression::default());
+  let res = match store.export_archive(&mut out).await {
+    Ok(_) => out.finish().and_then(|mut w| w.flush()).map_err(io_error),
+    Err(e) => Err(e)
+  };
+  if let Err(e) = res.and_then(|_| fs::rename(&part, &path).map_err(io_error)) {
+    fs::remove_file(&part).ok();
+    return Err(e);
+  }
+  backup.size = fs::metadata(&path).map_err(io_error)?.len();
+  // pruning is housekeeping, the backup itself is done
+  match list(&config.dir) {
+    Ok(backups) => for old in expired(&backups, config, Utc::now()) {
+      if let Err(e) = fs::remove_file(config.dir.join(&old.name)) {
+        println!("Failed to remove backup {}: {:?}", old.name, e);
+      }
+    },
+    Err(e) => println!("Failed to prune backups: {:?}", e)
+  }
+  println!("backup: {} ({} bytes)", backup.name, backup.size);
+  Ok(backup)
+}
+
+// records of the backup `name`, which has to be one of the backups in `dir`
+pub fn open(dir: &Path, name: &str) -> Result<ArchiveReader<BufReader<GzDecoder<File>>>, EavError> {
+  let path: PathBuf = match list(dir)?.into_iter().find(|b| b.name == name) {
+    Some(backup) => dir.join(backup.name),
+    None => return Err(EavError::new(ErrorCode::NotFound, "Backup not found").with_field("name"))
+  };
+  let file = File::open(path).map_err(eav_archive::io_error)?;
+  ArchiveReader::new(BufReader::new(GzDecoder::new(file)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{Datelike, TimeZone, Timelike};
+
+  fn backup(day: u32, hour: u32) -> BackupInfo {
+    let at = Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+    BackupInfo::parse(&(PREFIX.to_owned() + &at.format(TIME_FORMAT).to_string() + "-manual" + EXTENSION), 0).unwrap()
+  }
+
+  fn config(keep_last: usize, keep_daily_days: u32) -> BackupConfig {
+    BackupConfig { dir: PathBuf::new(), keep_last, keep_daily_days, on_start: false, before_delete: false }
+  }
+
+  #[test]
+  fn names_are_parsed() {
+    let parsed = BackupInfo::parse("taureav-20240131-154500-123-delete_entity_type.jsonl.gz", 42).unwrap();
+    assert_eq!(parsed.created_at, Utc.with_ymd_and_hms(2024, 1, 31, 15, 45, 0).unwrap() + Duration::milliseconds(123));
+    assert_eq!((parsed.reason.as_str(), parsed.size), ("delete_entity_type", 42));
+    for name in [
+      "taureav-20240131-154500-123-manual.jsonl",
+      "backup-20240131-154500-123-manual.jsonl.gz",
+      "taureav-20240131-154500-123-.jsonl.gz",
+      "taureav-20240131-154500-123-Manual.jsonl.gz",
+      "taureav-20240131-154500-123-../manual.jsonl.gz",
+      "taureav-20241331-154500-123-manual.jsonl.gz",
+      "taureav-20240131-1545-manual.jsonl.gz",
+      "taureav-20240131-154500-123-manual.jsonl.gz.part",
+    ] {
+      assert_eq!(BackupInfo::parse(name, 0), None, "{}", name);
+    }
+  }
+
+  #[test]
+  fn old_backups_expire() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+    // newest first, like list gives them
+    let backups = [
+      backup(31, 11), backup(31, 10), backup(31, 9), backup(30, 20), backup(30, 8), backup(29, 8), backup(28, 13), backup(27, 23)
+    ];
+    let hours = |config: BackupConfig| -> Vec<(u32, u32)> {
+      expired(&backups, &config, now).iter().map(|b| (b.created_at.day(), b.created_at.hour())).collect()
+    };
+    assert_eq!(hours(config(2, 3)), [(31, 9), (30, 8), (27, 23)]);
+    assert_eq!(hours(config(5, 0)), [(29, 8), (28, 13), (27, 23)]);
+    assert_eq!(hours(config(0, 0)).len(), backups.len() - 1);
+    assert!(hours(config(10, 0)).is_empty());
+  }
+}
@@ -0,0 +1,257 @@
+// Duplicate detection: every pair of entities of a type is compared by name, after case, accents, punctuation
+// and a leading or trailing article are taken out. alt_title values count as names too, and key attributes
+// agreeing raise the score while any key attribute disagreeing rules the pair out.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::db_store::EavStore;
+use crate::eav_error::{EavError, ErrorCode};
+use crate::eav_structs::{EavCell, EavEntity, PageRequest, TableCell};
+
+const ALT_TITLE: &str = "alt_title";
+// "The Hobbit" and "Hobbit, The" are the same name
+const ARTICLES: [&str; 16] = ["the", "a", "an", "le", "la", "les", "l", "der", "die", "das", "el", "los", "las", "il", "lo", "gli"];
+
+// names match when they are at most `max_distance` edits apart and at least `min_similarity` alike,
+// similarity being 1 minus the edits per character of the longer name
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DuplicateOptions {
+  pub max_distance: usize,
+  pub min_similarity: f32,
+  pub key_attrs: Vec<String>,
+}
+
+impl Default for DuplicateOptions {
+  fn default() -> Self {
+    DuplicateOptions { max_distance: 3, min_similarity: 0.8, key_attrs: Vec::new() }
+  }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DuplicatePair {
+  pub a: u32,
+  pub b: u32,
+  pub score: f32,
+  pub reasons: Vec<String>,
+}
+
+// entities linked by matching pairs, oldest first. `score` is the one of the best pair
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DuplicateGroup {
+  pub score: f32,
+  pub entities: Vec<EavEntity>,
+  pub pairs: Vec<DuplicatePair>,
+}
+
+struct Candidate {
+  entity: EavEntity,
+  // as written and normalized
+  names: Vec<(String, String)>,
+  // normalized values of each key attribute
+  keys: Vec<Vec<String>>,
+}
+
+pub fn normalize(name: &str) -> String {
+  let folded: String = name.nfd()
+    .filter(|c| !is_combining_mark(*c))
+    .flat_map(char::to_lowercase)
+    .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+    .collect();
+  let mut words: Vec<&str> = folded.split_whitespace().collect();
+  if words.len() > 1 && ARTICLES.contains(&words[0]) { words.remove(0); }
+  if words.len() > 1 && ARTICLES.contains(&words[words.len() - 1]) { words.pop(); }
+  words.join(" ")
+}
+
+pub fn distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut row: Vec<usize> = (0..=b.len()).collect();
+  for (i, ca) in a.chars().enumerate() {
+    let mut diagonal = row[0];
+    row[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let next = (diagonal + usize::from(ca != *cb)).min(row[j] + 1).min(row[j + 1] + 1);
+      diagonal = row[j + 1];
+      row[j + 1] = next;
+    }
+  }
+  row[b.len()]
+}
+
+fn cell_text(cell: &EavCell) -> String {
+  match cell {
+    EavCell::Str(s) => s.clone(),
+    EavCell::Int(i) => i.to_string(),
+    EavCell::Float(f) => f.to_string(),
+    EavCell::Bool(b) => b.to_string(),
+    EavCell::Time(t) => t.to_rfc3339(),
+    EavCell::Measure { value, unit } => cell_text(value) + " " + unit,
+    // the same entity, whatever it's called
+    EavCell::Ref { id, .. } => id.to_string(),
+  }
+}
+
+fn cell_texts(cell: Option<&TableCell>) -> Vec<String> {
+  match cell {
+    Some(TableCell::Many(cells)) => cells.iter().map(cell_text).collect(),
+    Some(TableCell::One(cell)) => vec![cell_text(cell)],
+    _ => Vec::new()
+  }
+}
+
+// the best matching names of the two, None when nothing matches or a key attribute disagrees
+fn compare(a: &Candidate, b: &Candidate, options: &DuplicateOptions) -> Option<DuplicatePair> {
+  let mut best: Option<(f32, String)> = None;
+  for (raw_a, norm_a) in a.names.iter() {
+    for (raw_b, norm_b) in b.names.iter() {
+      let (len_a, len_b) = (norm_a.chars().count(), norm_b.chars().count());
+      if len_a == 0 || len_b == 0 || len_a.abs_diff(len_b) > options.max_distance { continue; }
+      let edits = distance(norm_a, norm_b);
+      let similarity = 1.0 - edits as f32 / len_a.max(len_b) as f32;
+      if edits > options.max_distance || similarity < options.min_similarity { continue; }
+      if best.as_ref().is_some_and(|(s, _)| *s >= similarity) { continue; }
+      let reason = match edits {
+        0 => format!("\"{}\" and \"{}\" match", raw_a, raw_b),
+        1 => format!("\"{}\" and \"{}\" are 1 edit apart", raw_a, raw_b),
+        n => format!("\"{}\" and \"{}\" are {} edits apart", raw_a, raw_b, n),
+      };
+      best = Some((similarity, reason));
+    }
+  }
+  let (similarity, reason) = best?;
+  let mut reasons = vec![reason];
+  let mut agreeing = 0;
+  for (i, attr) in options.key_attrs.iter().enumerate() {
+    let (keys_a, keys_b) = (&a.keys[i], &b.keys[i]);
+    if keys_a.is_empty() || keys_b.is_empty() { continue; }
+    if !keys_a.iter().any(|k| keys_b.contains(k)) { return None; }
+    agreeing += 1;
+    reasons.push(attr.clone() + " matches");
+  }
+  let score = (similarity + agreeing as f32) / (1 + agreeing) as f32;
+  Some(DuplicatePair { a: a.entity.id, b: b.entity.id, score, reasons })
+}
+
+fn root(parents: &mut [usize], i: usize) -> usize {
+  let mut i = i;
+  while parents[i] != i {
+    parents[i] = parents[parents[i]];
+    i = parents[i];
+  }
+  i
+}
+
+// likely duplicates among the entities of `entity_type_id`, best groups first
+pub async fn find(store: &dyn EavStore, entity_type_id: u32, options: &DuplicateOptions) -> Result<Vec<DuplicateGroup>, EavError> {
+  if !(0.0..=1.0).contains(&options.min_similarity) {
+    return Err(EavError::new(ErrorCode::InvalidArgument, "Similarity has to be between 0 and 1").with_field("min_similarity"));
+  }
+  let mut candidates = Vec::new();
+  let mut cursor = None;
+  loop {
+    let page = PageRequest::new(None, Some(PageRequest::MAX_PER_PAGE), cursor);
+    let table = store.fetch_table(entity_type_id, None, &[], &page).await?;
+    let column = |attr: &str| table.columns.iter().position(|c| c.attr == attr);
+    let mut key_columns = Vec::new();
+    for attr in options.key_attrs.iter() {
+      let col = column(attr).ok_or_else(|| {
+        EavError::new(ErrorCode::NotFound, &("Attribute ".to_owned() + attr + " not found")).with_field("key_attrs")
+      })?;
+      key_columns.push(col);
+    }
+    let alt_title = column(ALT_TITLE);
+    for row in table.rows.items {
+      let mut names = vec![row.entity.entity.clone()];
+      names.extend(alt_title.map(|col| cell_texts(row.cells.get(col))).unwrap_or_default());
+      let keys = key_columns.iter()
+        .map(|col| cell_texts(row.cells.get(*col)).iter().map(|k| normalize(k)).filter(|k| !k.is_empty()).collect())
+        .collect();
+      candidates.push(Candidate {
+        names: names.into_iter().map(|n| { let norm = normalize(&n); (n, norm) }).collect(),
+        keys,
+        entity: row.entity,
+      });
+    }
+    cursor = table.rows.next_cursor;
+    if cursor.is_none() { break; }
+  }
+
+  // matching pairs link their entities into groups
+  let mut pairs = Vec::new();
+  let mut parents: Vec<usize> = (0..candidates.len()).collect();
+  for i in 0..candidates.len() {
+    for j in i + 1..candidates.len() {
+      if let Some(pair) = compare(&candidates[i], &candidates[j], options) {
+        let (ri, rj) = (root(&mut parents, i), root(&mut parents, j));
+        parents[rj] = ri;
+        pairs.push((i, pair));
+      }
+    }
+  }
+  let mut groups: Vec<DuplicateGroup> = Vec::new();
+  let mut group_of = vec![None; candidates.len()];
+  for (i, pair) in pairs {
+    let r = root(&mut parents, i);
+    let g = *group_of[r].get_or_insert_with(|| {
+      groups.push(DuplicateGroup { score: 0.0, entities: Vec::new(), pairs: Vec::new() });
+      groups.len() - 1
+    });
+    groups[g].score = groups[g].score.max(pair.score);
+    groups[g].pairs.push(pair);
+  }
+  for (i, candidate) in candidates.into_iter().enumerate() {
+    let r = root(&mut parents, i);
+    if let Some(g) = group_of[r] { groups[g].entities.push(candidate.entity); }
+  }
+  for group in groups.iter_mut() {
+    group.entities.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+    group.pairs.sort_by(|a, b| b.score.total_cmp(&a.score));
+  }
+  groups.sort_by(|a, b| b.score.total_cmp(&a.score));
+  println!("find_duplicates: {} groups among {} entities", groups.len(), group_of.len());
+  Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn names_lose_case_accents_and_articles() {
+    let cases = [
+      ("The Hobbit", "hobbit"),
+      ("Hobbit, The", "hobbit"),
+      ("  HOBBIT!  ", "hobbit"),
+      ("Pokémon", "pokemon"),
+      ("L'Étranger", "etranger"),
+      ("Die Verwandlung", "verwandlung"),
+      ("The Lord of the Rings", "lord of the rings"),
+      ("Spider-Man: Homecoming", "spider man homecoming"),
+      // a name that is only an article stays
+      ("The", "the"),
+      ("A Team, The", "team"),
+    ];
+    for (name, expected) in cases {
+      assert_eq!(normalize(name), expected, "{}", name);
+    }
+  }
+
+  #[test]
+  fn distance_counts_edits() {
+    let cases = [
+      ("hobbit", "hobbit", 0),
+      ("", "dune", 4),
+      ("dune", "", 4),
+      ("dune", "dunes", 1),
+      ("dune", "june", 1),
+      ("dune", "dnue", 2),
+      ("kitten", "sitting", 3),
+      ("éa", "ea", 1),
+    ];
+    for (a, b, expected) in cases {
+      assert_eq!(distance(a, b), expected, "{} {}", a, b);
+      assert_eq!(distance(b, a), expected, "{} {}", b, a);
+    }
+  }
+}
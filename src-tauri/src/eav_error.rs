@@ -0,0 +1,113 @@
+use std::fmt;
+
+use sqlx::error::ErrorKind;
+
+// stable codes the frontend can match on
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ErrorCode {
+  NotConnected,
+  ServerUnavailable,
+  MigrationFailed,
+  DuplicateEntityType,
+  DuplicateEntity,
+  DuplicateAttribute,
+  InvalidValueType,
+  SingleValueViolation,
+  NotFound,
+  InTrash,
+  Referenced,
+  InvalidRegex,
+  InvalidArgument,
+  InvalidQuery,
+  Io,
+  Database,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EavError {
+  pub code: ErrorCode,
+  pub message: String,
+  pub field: Option<String>,
+  // character offset into the search query for InvalidQuery errors
+  pub position: Option<usize>,
+}
+
+impl EavError {
+  pub fn new(code: ErrorCode, message: &str) -> Self {
+    EavError { code, message: message.to_owned(), field: None, position: None }
+  }
+
+  pub fn with_field(mut self, field: &str) -> Self {
+    self.field = Some(field.to_owned());
+    self
+  }
+
+  pub fn with_position(mut self, position: usize) -> Self {
+    self.position = Some(position);
+    self
+  }
+
+  pub fn not_connected() -> Self {
+    EavError::new(ErrorCode::NotConnected, "Not connected to database")
+  }
+
+  // maps the SIGNAL messages from the mysql procedures (and matching sqlite triggers)
+  fn from_db_message(msg: &str, kind: ErrorKind) -> Self {
+    let (code, field) = match msg {
+      "ERR: Entity already exists" => (ErrorCode::DuplicateEntity, Some("entity")),
+      "ERR: Attribute already exists" => (ErrorCode::DuplicateAttribute, Some("attr")),
+      "ERR: Invalid value type" => (ErrorCode::InvalidValueType, Some("value_type")),
+      "ERR: Attribute does not allow multiple entries" => (ErrorCode::SingleValueViolation, Some("attr_id")),
+      "ERR: Entity not found" => (ErrorCode::NotFound, Some("entity_id")),
+      "ERR: Attribute not found" => (ErrorCode::NotFound, Some("attr_id")),
+      "ERR: No entity type selected" => (ErrorCode::NotFound, Some("entity_type_id")),
+      "ERR: Parent is in the trash" => (ErrorCode::InTrash, None),
+      "ERR: Referenced entity not found" => (ErrorCode::NotFound, Some("value_int")),
+      "ERR: String value not provided" => (ErrorCode::InvalidValueType, Some("value_str")),
+      "ERR: int value not provided" => (ErrorCode::InvalidValueType, Some("value_int")),
+      "ERR: float value not provided" => (ErrorCode::InvalidValueType, Some("value_float")),
+      "ERR: time value not provided" => (ErrorCode::InvalidValueType, Some("value_time")),
+      "ERR: bool value not provided" => (ErrorCode::InvalidValueType, Some("value_bool")),
+      // entity_type is the only unique column in the schema
+      _ if kind == ErrorKind::UniqueViolation && msg.contains("entity_type") => {
+        (ErrorCode::DuplicateEntityType, Some("entity_type"))
+      }
+      _ if kind == ErrorKind::ForeignKeyViolation => (ErrorCode::NotFound, None),
+      // mysql regex errors all mention the regular expression
+      _ if msg.contains("regular expression") => (ErrorCode::InvalidRegex, Some("regex")),
+      _ => (ErrorCode::Database, None),
+    };
+    let message = match code {
+      ErrorCode::DuplicateEntityType => "Entity type already exists",
+      _ => msg.strip_prefix("ERR: ").unwrap_or(msg),
+    };
+    EavError { code, message: message.to_owned(), field: field.map(|f| f.to_owned()), position: None }
+  }
+}
+
+impl fmt::Display for EavError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.field {
+      Some(field) => write!(f, "{:?} ({}): {}", self.code, field, self.message)?,
+      None => write!(f, "{:?}: {}", self.code, self.message)?,
+    }
+    match self.position {
+      Some(position) => write!(f, " at {}", position),
+      None => Ok(()),
+    }
+  }
+}
+
+impl std::error::Error for EavError {}
+
+impl From<sqlx::Error> for EavError {
+  fn from(e: sqlx::Error) -> Self {
+    match e {
+      sqlx::Error::RowNotFound => EavError::new(ErrorCode::NotFound, "Row not found"),
+      sqlx::Error::PoolClosed | sqlx::Error::PoolTimedOut => EavError::not_connected(),
+      sqlx::Error::Migrate(e) => EavError::new(ErrorCode::MigrationFailed, &e.to_string()),
+      sqlx::Error::Database(e) => EavError::from_db_message(e.message(), e.kind()),
+      _ => EavError::new(ErrorCode::Database, &e.to_string()),
+    }
+  }
+}
@@ -0,0 +1,358 @@
+// CSV and XLSX export: one row per entity and one column per attribute, like the table view.
+// Rows are fetched a page at a time and written straight to the file, so exports of any size
+// only ever hold one page in memory.
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use chrono::Datelike;
+use chrono::format::{Item, StrftimeItems};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+use crate::db_store::EavStore;
+use crate::eav_error::{EavError, ErrorCode};
+use crate::eav_query::Expr;
+use crate::eav_structs::{EavCell, PageRequest, SortKey, TableCell};
+
+// xlsx sheets end at row 1048576, the header takes one
+const XLSX_MAX_ROWS: u64 = 1_048_575;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat { Csv, Xlsx }
+
+impl ExportFormat {
+  // an explicit format wins over the file extension, anything but .xlsx is written as csv
+  pub fn new(format: Option<&str>, path: &str) -> Result<Self, EavError> {
+    match format.map(str::to_lowercase).as_deref() {
+      Some("csv") => Ok(ExportFormat::Csv),
+      Some("xlsx") => Ok(ExportFormat::Xlsx),
+      Some(_) => Err(EavError::new(ErrorCode::InvalidArgument, "Format has to be csv or xlsx").with_field("format")),
+      None if path.to_lowercase().ends_with(".xlsx") => Ok(ExportFormat::Xlsx),
+      None => Ok(ExportFormat::Csv),
+    }
+  }
+}
+
+// file written by an export
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportSummary {
+  pub path: String,
+  pub format: ExportFormat,
+  pub rows: u64,
+  pub columns: u64,
+}
+
+// how cells are written, `time_format` is a strftime pattern (RFC 3339 in csv and real dates in xlsx when missing)
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+  pub separator: String,
+  pub units: bool,
+  pub time_format: Option<String>,
+}
+
+impl ExportOptions {
+  pub const DEFAULT_SEPARATOR: &'static str = "; ";
+
+  pub fn new(separator: Option<String>, units: Option<bool>, time_format: Option<String>) -> Result<Self, EavError> {
+    let time_format = time_format.filter(|f| !f.trim().is_empty());
+    if let Some(format) = &time_format {
+      if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(EavError::new(ErrorCode::InvalidArgument, "Invalid date format").with_field("time_format"));
+      }
+    }
+    Ok(ExportOptions {
+      separator: separator.filter(|s| !s.is_empty()).unwrap_or(Self::DEFAULT_SEPARATOR.to_owned()),
+      units: units.unwrap_or(true),
+      time_format,
+    })
+  }
+
+  fn format_cell(&self, cell: &EavCell) -> String {
+    match cell {
+      EavCell::Bool(b) => b.to_string(),
+      EavCell::Int(i) => i.to_string(),
+      EavCell::Float(f) => f.to_string(),
+      EavCell::Time(t) => match &self.time_format {
+        Some(format) => t.format(format).to_string(),
+        None => t.to_rfc3339(),
+      },
+      EavCell::Measure { value, unit } if self.units => self.format_cell(value) + " " + unit,
+      EavCell::Measure { value, .. } => self.format_cell(value),
+      EavCell::Ref { entity, .. } => entity.clone().unwrap_or_default(),
+      EavCell::Str(s) => s.clone(),
+    }
+  }
+
+  fn format_list(&self, cells: &[EavCell]) -> String {
+    cells.iter().map(|c| self.format_cell(c)).collect::<Vec<String>>().join(&self.separator)
+  }
+}
+
+fn io_error(e: impl std::fmt::Display) -> EavError {
+  EavError::new(ErrorCode::Io, &("Could not write export: ".to_owned() + &e.to_string())).with_field("path")
+}
+
+impl From<XlsxError> for EavError {
+  fn from(e: XlsxError) -> Self { io_error(e) }
+}
+
+// destination of the exported rows, written in order
+trait RowWriter {
+  fn header(&mut self, names: &[String]) -> Result<(), EavError>;
+  fn row(&mut self, cells: &[&TableCell]) -> Result<(), EavError>;
+  fn finish(self: Box<Self>) -> Result<(), EavError>;
+}
+
+struct CsvWriter {
+  writer: csv::Writer<File>,
+  options: ExportOptions,
+}
+
+impl RowWriter for CsvWriter {
+  fn header(&mut self, names: &[String]) -> Result<(), EavError> {
+    self.writer.write_record(names).map_err(io_error)
+  }
+
+  fn row(&mut self, cells: &[&TableCell]) -> Result<(), EavError> {
+    let record = cells.iter().map(|cell| match cell {
+      TableCell::Many(list) => self.options.format_list(list),
+      TableCell::One(cell) => self.options.format_cell(cell),
+      TableCell::Empty => String::new(),
+    });
+    self.writer.write_record(record).map_err(io_error)
+  }
+
+  fn finish(mut self: Box<Self>) -> Result<(), EavError> {
+    self.writer.flush().map_err(io_error)
+  }
+}
+
+// the sheet is written in constant memory mode, rows go to a temp file until the workbook is saved
+struct XlsxWriter {
+  workbook: Workbook,
+  path: String,
+  row: u32,
+  options: ExportOptions,
+  bold: Format,
+  date: Format,
+  // number formats that show a unit after the number, one per unit
+  units: HashMap<String, Format>,
+}
+
+impl XlsxWriter {
+  fn new(path: &str, options: ExportOptions) -> Self {
+    let mut workbook = Workbook::new();
+    workbook.add_worksheet_with_constant_memory();
+    XlsxWriter {
+      workbook,
+      path: path.to_owned(),
+      row: 0,
+      options,
+      bold: Format::new().set_bold(),
+      date: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+      units: HashMap::new(),
+    }
+  }
+
+  // numbers stay numbers, f32 goes through its shortest text form so 4.1 doesn't turn into 4.0999999
+  fn number(cell: &EavCell) -> Option<f64> {
+    match cell {
+      EavCell::Int(i) => Some(*i as f64),
+      EavCell::Float(f) => f.to_string().parse().ok(),
+      _ => None
+    }
+  }
+}
+
+impl RowWriter for XlsxWriter {
+  fn header(&mut self, names: &[String]) -> Result<(), EavError> {
+    let sheet = self.workbook.worksheet_from_index(0)?;
+    for (col, name) in names.iter().enumerate() {
+      sheet.write_string_with_format(0, col as u16, name, &self.bold)?;
+    }
+    self.row = 1;
+    Ok(())
+  }
+
+  fn row(&mut self, cells: &[&TableCell]) -> Result<(), EavError> {
+    let row = self.row;
+    let sheet = self.workbook.worksheet_from_index(0)?;
+    for (col, cell) in cells.iter().enumerate() {
+      let col = col as u16;
+      let cell = match cell {
+        TableCell::Many(list) if list.is_empty() => continue,
+        TableCell::Many(list) => {
+          sheet.write_string(row, col, self.options.format_list(list))?;
+          continue;
+        }
+        TableCell::One(cell) => cell,
+        TableCell::Empty => continue,
+      };
+      match cell {
+        EavCell::Bool(b) => { sheet.write_boolean(row, col, *b)?; }
+        // excel has no dates before 1900, those stay text
+        EavCell::Time(t) if self.options.time_format.is_none() && t.year() >= 1900 => {
+          sheet.write_datetime_with_format(row, col, t.naive_utc(), &self.date)?;
+        }
+        EavCell::Measure { value, unit } if self.options.units => {
+          let Some(number) = Self::number(value) else { continue };
+          let format = self.units.entry(unit.clone())
+            .or_insert_with(|| Format::new().set_num_format("General\" ".to_owned() + &unit.replace('"', "") + "\""));
+          sheet.write_number_with_format(row, col, number, &*format)?;
+        }
+        EavCell::Measure { value, .. } => match Self::number(value) {
+          Some(number) => { sheet.write_number(row, col, number)?; }
+          None => continue
+        },
+        cell => match Self::number(cell) {
+          Some(number) => { sheet.write_number(row, col, number)?; }
+          None => { sheet.write_string(row, col, self.options.format_cell(cell))?; }
+        }
+      }
+    }
+    self.row += 1;
+    Ok(())
+  }
+
+  fn finish(mut self: Box<Self>) -> Result<(), EavError> {
+    self.workbook.save(&self.path)?;
+    Ok(())
+  }
+}
+
+// writes the entities of `entity_type_ids` matching `filter` to `path`. with more than one entity type
+// the first column names the category and attributes with the same name share a column
+pub async fn export(
+  store: &dyn EavStore, entity_type_ids: &[u32], filter: Option<&Expr>, sort: &[SortKey],
+  path: &str, format: ExportFormat, options: ExportOptions
+) -> Result<ExportSummary, EavError> {
+  // a first tiny page per type tells which types have rows and what their columns are
+  let mut tables = Vec::new();
+  let mut total = 0;
+  for id in entity_type_ids {
+    let probe = store.fetch_table(*id, filter, sort, &PageRequest::new(None, Some(1), None)).await?;
+    if probe.rows.total > 0 || entity_type_ids.len() == 1 {
+      total += probe.rows.total;
+      tables.push(probe);
+    }
+  }
+  if format == ExportFormat::Xlsx && total > XLSX_MAX_ROWS {
+    return Err(EavError::new(ErrorCode::InvalidArgument, "Too many rows for an xlsx sheet, export to csv instead").with_field("format"));
+  }
+  let with_category = entity_type_ids.len() > 1;
+  let mut names: Vec<String> = Vec::new();
+  if with_category { names.push("Category".to_owned()); }
+  names.push(match tables.as_slice() {
+    [table] if !with_category => table.entity_type.entity_type.clone(),
+    _ => "Entity".to_owned()
+  });
+  let fixed = names.len();
+  // column of every attribute of every type
+  let mut col_of: HashMap<u32, usize> = HashMap::new();
+  for table in tables.iter() {
+    for attr in table.columns.iter() {
+      let col = match names[fixed..].iter().position(|n| *n == attr.attr) {
+        Some(i) => i + fixed,
+        None => {
+          names.push(attr.attr.clone());
+          names.len() - 1
+        }
+      };
+      col_of.insert(attr.id, col);
+    }
+  }
+
+  let mut writer: Box<dyn RowWriter> = match format {
+    ExportFormat::Csv => {
+      let file = File::create(path).map_err(io_error)?;
+      Box::new(CsvWriter { writer: csv::Writer::from_writer(file), options })
+    }
+    ExportFormat::Xlsx => Box::new(XlsxWriter::new(path, options)),
+  };
+  writer.header(&names)?;
+  let mut rows = 0;
+  for probe in tables {
+    let mut cursor = None;
+    loop {
+      let page = PageRequest::new(None, Some(PageRequest::MAX_PER_PAGE), cursor);
+      let table = store.fetch_table(probe.entity_type.id, filter, sort, &page).await?;
+      let category = TableCell::One(EavCell::Str(table.entity_type.entity_type.clone()));
+      for row in table.rows.items.iter() {
+        let name = TableCell::One(EavCell::Str(row.entity.entity.clone()));
+        let mut cells = vec![&TableCell::Empty; names.len()];
+        if with_category { cells[0] = &category; }
+        cells[fixed - 1] = &name;
+        for (attr, cell) in table.columns.iter().zip(row.cells.iter()) {
+          if let Some(col) = col_of.get(&attr.id) { cells[*col] = cell; }
+        }
+        writer.row(&cells)?;
+        rows += 1;
+      }
+      cursor = table.rows.next_cursor;
+      if cursor.is_none() { break; }
+    }
+  }
+  writer.finish()?;
+  println!("export: {} rows, {} columns to {}", rows, names.len(), path);
+  Ok(ExportSummary { path: path.to_owned(), format, rows, columns: names.len() as u64 })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db_sqlite;
+  use crate::eav_import::{self, CsvOptions};
+  use crate::eav_structs::EavValue;
+
+  // Dune with a unit, two tags and a date, then enough untitled rows for a second page
+  async fn books() -> (impl EavStore, u32) {
+    let store = db_sqlite::connect("sqlite::memory:").await.unwrap();
+    let dune = store.create_entity("book", "Dune").await.unwrap();
+    let book = dune.entity_type_id;
+    for (attr, value_type, allow_multiple, cells) in [
+      ("weight", "float", false, vec!["1.5 kg"]),
+      ("tags", "str", true, vec!["sf", "classic"]),
+      ("released", "time", false, vec!["1965-08-01"]),
+    ] {
+      let attr = store.create_attr(book, attr, value_type, allow_multiple, None, None).await.unwrap();
+      for cell in cells {
+        let value = eav_import::parse_cell(value_type, cell).unwrap();
+        store.create_value(EavValue { entity_id: dune.id, attr_id: attr.id, ..value }).await.unwrap();
+      }
+    }
+    let text = (1..=PageRequest::MAX_PER_PAGE).fold("title\n".to_owned(), |text, i| text + &format!("Book {:04}\n", i));
+    let plan = eav_import::plan(&text, &CsvOptions::new(None, None).unwrap(), 0, vec![], &[]).unwrap();
+    assert!(store.import_rows(book, plan, false).await.unwrap().committed);
+    (store, book)
+  }
+
+  async fn export_lines(store: &dyn EavStore, book: u32, name: &str, options: ExportOptions) -> (ExportSummary, Vec<String>) {
+    let path = std::env::temp_dir().join(format!("taureav-{}-{}.csv", name, std::process::id()));
+    let path = path.to_str().unwrap();
+    let summary = export(store, &[book], None, &[], path, ExportFormat::Csv, options).await.unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    (summary, text.lines().map(str::to_owned).collect())
+  }
+
+  #[async_std::test]
+  async fn csv_rows_follow_the_options() {
+    let (store, book) = books().await;
+    let cases = [
+      (ExportOptions::new(None, None, None), "Dune,1.5 kg,sf; classic,1965-08-01T00:00:00+00:00"),
+      (ExportOptions::new(Some(" | ".to_owned()), Some(false), Some("%d.%m.%Y".to_owned())), "Dune,1.5,sf | classic,01.08.1965"),
+    ];
+    for (i, (options, dune)) in cases.into_iter().enumerate() {
+      let (summary, lines) = export_lines(&store, book, &i.to_string(), options.unwrap()).await;
+      assert_eq!(lines[0], "book,weight,tags,released");
+      assert!(lines.contains(&dune.to_owned()), "{}", dune);
+      // every row once, across both pages
+      assert_eq!((summary.rows, summary.columns), (PageRequest::MAX_PER_PAGE as u64 + 1, 4));
+      let mut names: Vec<&str> = lines[1..].iter().map(|l| l.split(',').next().unwrap()).collect();
+      names.sort();
+      names.dedup();
+      assert_eq!(names.len(), PageRequest::MAX_PER_PAGE as usize + 1);
+    }
+  }
+}
@@ -0,0 +1,459 @@
+// CSV import: reads the file, proposes a mapping of its columns to attributes and turns rows into values.
+// The first line holds the column names, one column names the entity and every other column fills an attribute.
+// Writing the rows is left to the store, see EavStore::import_rows.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveTime, Utc};
+
+use crate::eav_error::{EavError, ErrorCode};
+use crate::eav_query::{parse_bool, parse_time};
+use crate::eav_structs::{ColumnMapping, CsvPreview, EavAttribute, EavValue, ImportError};
+
+// rows shown in the preview
+pub const PREVIEW_ROWS: usize = 20;
+
+// types values can be imported and converted to, entity references ("ref") only come from the app
+pub const VALUE_TYPES: [&str; 5] = ["str", "int", "float", "time", "bool"];
+
+// how the file is read, cells of allow_multiple attributes are split on `separator`
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+  pub delimiter: Option<u8>,
+  pub separator: String,
+}
+
+impl CsvOptions {
+  pub const DEFAULT_SEPARATOR: &'static str = ";";
+
+  // a missing delimiter is guessed from the header line
+  pub fn new(delimiter: Option<String>, separator: Option<String>) -> Result<Self, EavError> {
+    let delimiter = match delimiter.as_deref() {
+      None | Some("") => None,
+      Some("\\t") => Some(b'\t'),
+      Some(d) if d.len() == 1 && d.is_ascii() => Some(d.as_bytes()[0]),
+      Some(_) => return Err(EavError::new(ErrorCode::InvalidArgument, "Delimiter has to be a single character").with_field("delimiter")),
+    };
+    let separator = separator.filter(|s| !s.trim().is_empty()).unwrap_or(Self::DEFAULT_SEPARATOR.to_owned());
+    Ok(CsvOptions { delimiter, separator })
+  }
+}
+
+// a data row with the line it starts on, for the error report
+pub struct CsvRecord {
+  pub line: u64,
+  pub cells: Vec<String>,
+}
+
+// rows of one entity ready to be written, values point at the plan column they belong to
+pub struct ImportRow {
+  pub line: u64,
+  pub entity: String,
+  pub values: Vec<(usize, EavValue)>,
+}
+
+// columns resolved against the existing attributes, attr_id is None for attributes the import creates
+pub struct ImportPlan {
+  pub entity_column: String,
+  pub columns: Vec<ColumnMapping>,
+  pub rows: Vec<ImportRow>,
+  pub total_rows: u64,
+  pub errors: Vec<ImportError>,
+}
+
+// the most frequent candidate in the header line, comma when there is none
+fn sniff_delimiter(text: &str) -> u8 {
+  let header = text.lines().next().unwrap_or("");
+  [b',', b';', b'\t', b'|'].into_iter()
+    .map(|d| (header.bytes().filter(|b| *b == d).count(), d))
+    .filter(|(count, _)| *count > 0)
+    .max_by_key(|(count, _)| *count)
+    .map(|(_, d)| d)
+    .unwrap_or(b',')
+}
+
+fn read_csv(text: &str, delimiter: u8) -> Result<(Vec<String>, Vec<CsvRecord>), EavError> {
+  let csv_error = |e: csv::Error| EavError::new(ErrorCode::InvalidArgument, &("Could not read csv: ".to_owned() + &e.to_string()))
+    .with_field("text");
+  let text = text.trim_start_matches('\u{feff}');
+  let mut reader = csv::ReaderBuilder::new()
+    .delimiter(delimiter)
+    .flexible(true)
+    .from_reader(text.as_bytes());
+  let headers: Vec<String> = reader.headers().map_err(csv_error)?.iter().map(|h| h.trim().to_owned()).collect();
+  if headers.iter().all(|h| h.is_empty()) {
+    return Err(EavError::new(ErrorCode::InvalidArgument, "The first line has to name the columns").with_field("text"));
+  }
+  let mut records = Vec::new();
+  for record in reader.records() {
+    let record = record.map_err(csv_error)?;
+    // the reader doesn't count blank lines and its position is in front of them, so count from the byte offset
+    let offset = record.position().map(|p| p.byte() as usize).unwrap_or(0).min(text.len());
+    let start = offset + text[offset..].find(|c: char| c != '\r' && c != '\n').unwrap_or(0);
+    let line = text.as_bytes()[..start].iter().filter(|b| **b == b'\n').count() as u64 + 1;
+    let cells: Vec<String> = record.iter().map(|c| c.trim().to_owned()).collect();
+    // blank lines are not rows
+    if cells.iter().all(|c| c.is_empty()) { continue; }
+    records.push(CsvRecord { line, cells });
+  }
+  Ok((headers, records))
+}
+
+fn column_name(headers: &[String], column: usize) -> String {
+  match headers.get(column) {
+    Some(h) if !h.is_empty() => h.clone(),
+    _ => "column ".to_owned() + &(column + 1).to_string()
+  }
+}
+
+// the parts of a cell that become values
+fn split_cell<'a>(cell: &'a str, allow_multiple: bool, separator: &str) -> Vec<&'a str> {
+  if !allow_multiple { return vec![cell]; }
+  cell.split(separator).map(str::trim).filter(|p| !p.is_empty()).collect()
+}
+
+// "12.5 kg" is a number with a unit, the unit ends up in value_str like values entered by hand
+fn parse_number(text: &str) -> Option<(f64, Option<String>)> {
+  let end = text.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+')).unwrap_or(text.len());
+  let number: f64 = text[..end].parse().ok()?;
+  if !number.is_finite() { return None; }
+  let unit = text[end..].trim();
+  if unit.is_empty() { return Some((number, None)); }
+  if unit.len() > 12 || unit.contains(|c: char| c.is_whitespace() || c.is_ascii_digit())
+    || !unit.starts_with(|c: char| c.is_alphabetic() || c == '%' || c == '°') {
+    return None;
+  }
+  Some((number, Some(unit.to_owned())))
+}
+
+fn empty_value() -> EavValue {
+  EavValue {
+    id: 0, created_at: Utc::now(), entity_id: 0, attr_id: 0,
+    value_str: None, value_int: None, value_float: None, value_time: None, value_bool: None, deleted_at: None
+  }
+}
+
+// one cell part as a value of the attribute type
+pub fn parse_cell(value_type: &str, text: &str) -> Result<EavValue, &'static str> {
+  let mut value = empty_value();
+  match value_type {
+    "str" => value.value_str = Some(text.to_owned()),
+    "int" => {
+      let (number, unit) = parse_number(text).ok_or("Expected a whole number")?;
+      if number.fract() != 0.0 || number < i32::MIN as f64 || number > i32::MAX as f64 {
+        return Err("Expected a whole number");
+      }
+      value.value_int = Some(number as i32);
+      value.value_str = unit;
+    }
+    "float" => {
+      let (number, unit) = parse_number(text).ok_or("Expected a number")?;
+      value.value_float = Some(number as f32);
+      value.value_str = unit;
+    }
+    // relative dates like -30d only make sense in searches
+    "time" => {
+      let span = parse_time(text).filter(|_| !text.starts_with('-')).ok_or("Expected a date like 2024-01-31")?;
+      value.value_time = Some(span.start);
+    }
+    "bool" => {
+      let flag = match text {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => parse_bool(text)
+      };
+      value.value_bool = Some(flag.ok_or("Expected yes or no")?);
+    }
+    // entity references by id, checking the entity is up to the store
+    "ref" => {
+      let id = text.parse::<u32>().ok().filter(|id| *id <= i32::MAX as u32).ok_or("Expected an entity id")?;
+      value.value_int = Some(id as i32);
+    }
+    _ => return Err("Invalid value type")
+  }
+  Ok(value)
+}
+
+// text of a stored value of `value_type` on its way to `target`. numbers keep their unit,
+// booleans become 1 or 0 for numbers and yes or no otherwise, refs give the id of their entity
+pub fn value_text(value: &EavValue, value_type: &str, target: &str) -> Option<String> {
+  let with_unit = |number: String| match &value.value_str {
+    Some(unit) => number + " " + unit,
+    None => number
+  };
+  let text = match value_type {
+    "str" => value.value_str.clone(),
+    "int" => value.value_int.map(|i| with_unit(i.to_string())),
+    "float" => value.value_float.map(|f| with_unit(f.to_string())),
+    "time" => value.value_time.map(|t| match t.time() == NaiveTime::MIN {
+      true => t.format("%Y-%m-%d").to_string(),
+      false => t.format("%Y-%m-%d %H:%M:%S").to_string()
+    }),
+    "bool" => value.value_bool.map(|b| match (target, b) {
+      ("int" | "float", true) => "1".to_owned(),
+      ("int" | "float", false) => "0".to_owned(),
+      (_, true) => "yes".to_owned(),
+      (_, false) => "no".to_owned()
+    }),
+    "ref" => value.value_int.map(|id| id.to_string()),
+    _ => None
+  };
+  text.filter(|t| !t.trim().is_empty())
+}
+
+// `value` of a `value_type` attribute as a value of `target`, a value without content stays empty
+pub fn convert_value(value: &EavValue, value_type: &str, target: &str) -> Result<EavValue, &'static str> {
+  if (value_type == "ref") != (target == "ref") {
+    return Err("Entity references only convert to entity references");
+  }
+  let converted = match value_text(value, value_type, target) {
+    Some(text) => parse_cell(target, text.trim())?,
+    None => empty_value()
+  };
+  Ok(EavValue {
+    id: value.id, created_at: value.created_at, entity_id: value.entity_id, attr_id: value.attr_id,
+    deleted_at: value.deleted_at, ..converted
+  })
+}
+
+// narrowest type every value of a column fits, a column with separators in it probably holds several values
+fn infer_column(cells: &[&str], separator: &str) -> (String, bool) {
+  let allow_multiple = cells.iter().any(|c| c.contains(separator));
+  let parts: Vec<&str> = cells.iter().flat_map(|c| split_cell(c, allow_multiple, separator)).collect();
+  let value_type = ["int", "float", "bool", "time"].into_iter()
+    .find(|t| !parts.is_empty() && parts.iter().all(|p| parse_cell(t, p).is_ok()))
+    .unwrap_or("str");
+  (value_type.to_owned(), allow_multiple)
+}
+
+// first rows of the file with a proposed mapping, columns named like an attribute of the entity type map to it
+pub fn preview(text: &str, options: &CsvOptions, entity_type: &str, attrs: &[EavAttribute]) -> Result<CsvPreview, EavError> {
+  let delimiter = options.delimiter.unwrap_or_else(|| sniff_delimiter(text));
+  let (headers, records) = read_csv(text, delimiter)?;
+  let entity_column = headers.iter()
+    .position(|h| h.eq_ignore_ascii_case(entity_type))
+    .or_else(|| headers.iter().position(|h| ["name", "entity", "title"].contains(&h.to_lowercase().as_str())))
+    .unwrap_or(0);
+  let mut columns = Vec::new();
+  for column in (0..headers.len()).filter(|c| *c != entity_column) {
+    let attr = column_name(&headers, column);
+    let mapping = match attrs.iter().find(|a| a.attr.eq_ignore_ascii_case(&attr)) {
+      Some(existing) => ColumnMapping {
+        column,
+        attr: existing.attr.clone(),
+        value_type: existing.value_type.clone(),
+        allow_multiple: existing.allow_multiple.unwrap_or(false),
+        attr_id: Some(existing.id),
+      },
+      None => {
+        let cells: Vec<&str> = records.iter()
+          .filter_map(|r| r.cells.get(column).map(String::as_str))
+          .filter(|c| !c.is_empty())
+          .collect();
+        let (value_type, allow_multiple) = infer_column(&cells, &options.separator);
+        ColumnMapping { column, attr, value_type, allow_multiple, attr_id: None }
+      }
+    };
+    columns.push(mapping);
+  }
+  Ok(CsvPreview {
+    delimiter: (delimiter as char).to_string(),
+    separator: options.separator.clone(),
+    rows: records.iter().take(PREVIEW_ROWS).map(|r| r.cells.clone()).collect(),
+    total_rows: records.len() as u64,
+    headers,
+    entity_column,
+    columns,
+  })
+}
+
+// checks the mapping and parses every row, rows with a bad cell are reported and left out of the plan.
+// mapped attributes that already exist keep their value type and allow_multiple setting
+pub fn plan(
+  text: &str, options: &CsvOptions, entity_column: usize, mappings: Vec<ColumnMapping>, attrs: &[EavAttribute]
+) -> Result<ImportPlan, EavError> {
+  let delimiter = options.delimiter.unwrap_or_else(|| sniff_delimiter(text));
+  let (headers, records) = read_csv(text, delimiter)?;
+  let invalid = |msg: &str| EavError::new(ErrorCode::InvalidArgument, msg).with_field("columns");
+  if entity_column >= headers.len() {
+    return Err(EavError::new(ErrorCode::InvalidArgument, "Entity column is not in the file").with_field("entity_column"));
+  }
+  let mut seen_columns = HashSet::from([entity_column]);
+  let mut seen_attrs = HashSet::new();
+  let mut columns = Vec::new();
+  for mapping in mappings {
+    if mapping.column >= headers.len() {
+      return Err(invalid("Mapped column is not in the file"));
+    }
+    if !seen_columns.insert(mapping.column) {
+      return Err(invalid(&("Column ".to_owned() + &column_name(&headers, mapping.column) + " is mapped twice")));
+    }
+    let existing = match mapping.attr_id {
+      Some(id) => Some(attrs.iter().find(|a| a.id == id)
+        .ok_or_else(|| EavError::new(ErrorCode::NotFound, "Attribute not found").with_field("attr_id"))?),
+      None => attrs.iter().find(|a| a.attr == mapping.attr.trim()),
+    };
+    let column = match existing {
+      Some(attr) if attr.value_type == "ref" => {
+        return Err(invalid(&("Entity references like ".to_owned() + &attr.attr + " can't be imported")));
+      }
+      Some(attr) => ColumnMapping {
+        column: mapping.column,
+        attr: attr.attr.clone(),
+        value_type: attr.value_type.clone(),
+        allow_multiple: attr.allow_multiple.unwrap_or(false),
+        attr_id: Some(attr.id),
+      },
+      None => {
+        if mapping.attr.trim().is_empty() {
+          return Err(invalid(&("Column ".to_owned() + &column_name(&headers, mapping.column) + " needs an attribute name")));
+        }
+        if !VALUE_TYPES.contains(&mapping.value_type.as_str()) {
+          return Err(EavError::new(ErrorCode::InvalidValueType, "Invalid value type").with_field("value_type"));
+        }
+        ColumnMapping { attr: mapping.attr.trim().to_owned(), attr_id: None, ..mapping }
+      }
+    };
+    if !seen_attrs.insert(column.attr.clone()) {
+      return Err(invalid(&("Attribute ".to_owned() + &column.attr + " is mapped twice")));
+    }
+    columns.push(column);
+  }
+
+  let entity_header = column_name(&headers, entity_column);
+  let mut rows = Vec::new();
+  let mut errors = Vec::new();
+  for record in records.iter() {
+    let error = |column: &str, message: &str| ImportError {
+      line: Some(record.line), column: Some(column.to_owned()), message: message.to_owned()
+    };
+    let entity = record.cells.get(entity_column).cloned().unwrap_or_default();
+    let mut row_errors = Vec::new();
+    if entity.is_empty() {
+      row_errors.push(error(&entity_header, "Entity name is empty"));
+    }
+    let mut values = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+      let cell = match record.cells.get(column.column) {
+        Some(cell) if !cell.is_empty() => cell,
+        _ => continue
+      };
+      let parts = split_cell(cell, column.allow_multiple, &options.separator);
+      for part in parts {
+        match parse_cell(&column.value_type, part) {
+          Ok(value) => values.push((i, value)),
+          Err(msg) => row_errors.push(error(&column_name(&headers, column.column), msg)),
+        }
+      }
+    }
+    if row_errors.is_empty() {
+      rows.push(ImportRow { line: record.line, entity, values });
+    } else {
+      errors.extend(row_errors);
+    }
+  }
+  Ok(ImportPlan { entity_column: entity_header, columns, rows, total_rows: records.len() as u64, errors })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::{DateTime, TimeZone};
+
+  fn value(f: impl FnOnce(&mut EavValue)) -> EavValue {
+    let mut value = EavValue { id: 7, created_at: DateTime::UNIX_EPOCH, entity_id: 3, attr_id: 5, ..empty_value() };
+    f(&mut value);
+    value
+  }
+
+  #[test]
+  fn numbers_keep_their_unit() {
+    let cases = [
+      ("42", Some((42.0, None))),
+      ("-3", Some((-3.0, None))),
+      ("12.5 kg", Some((12.5, Some("kg")))),
+      ("12.5kg", Some((12.5, Some("kg")))),
+      ("50%", Some((50.0, Some("%")))),
+      ("20 °C", Some((20.0, Some("°C")))),
+      ("kg", None),
+      ("1.2.3", None),
+      ("1e5", None),
+      ("12 kg 5", None),
+      ("3 boxes of apples", None),
+      ("5 averyveryverylongunit", None),
+      ("inf", None),
+    ];
+    for (text, expected) in cases {
+      let expected = expected.map(|(n, unit): (f64, Option<&str>)| (n, unit.map(str::to_owned)));
+      assert_eq!(parse_number(text), expected, "{}", text);
+    }
+  }
+
+  #[test]
+  fn columns_get_the_narrowest_type() {
+    let cases: [(&[&str], &str, bool); 9] = [
+      (&["1", "2"], "int", false),
+      (&["1", "2.5"], "float", false),
+      (&["12 kg", "3 kg"], "int", false),
+      (&["1", "0"], "int", false),
+      (&["yes", "No"], "bool", false),
+      (&["2024-01-02", "2023"], "time", false),
+      (&["1;2", "3"], "int", true),
+      (&["a;b", "c"], "str", true),
+      (&[], "str", false),
+    ];
+    for (cells, value_type, allow_multiple) in cases {
+      assert_eq!(infer_column(cells, ";"), (value_type.to_owned(), allow_multiple), "{:?}", cells);
+    }
+  }
+
+  #[test]
+  fn rows_know_their_line() {
+    let cases: [(&str, &[u64]); 5] = [
+      ("name,year\nDune,1965\nEmma,1815\n", &[2, 3]),
+      ("name,year\nDune,1965\n\n\nEmma,1815\n", &[2, 5]),
+      ("name,year\r\nDune,1965\r\n\r\nEmma,1815", &[2, 4]),
+      ("name,year\nDune,1965\n,\nEmma,1815", &[2, 4]),
+      ("name,notes\n\"Dune\",\"two\nlines\"\nEmma,x", &[2, 4]),
+    ];
+    for (text, lines) in cases {
+      let (_, records) = read_csv(text, b',').unwrap();
+      assert_eq!(records.iter().map(|r| r.line).collect::<Vec<_>>(), lines, "{:?}", text);
+    }
+  }
+
+  #[test]
+  fn headers_are_required() {
+    let (headers, _) = read_csv("\u{feff}name ; year\nDune;1965", b';').unwrap();
+    assert_eq!(headers, ["name", "year"]);
+    assert!(read_csv(",,\nDune,1965", b',').is_err());
+  }
+
+  #[test]
+  fn values_convert_between_types() {
+    let day = Utc.with_ymd_and_hms(2024, 5, 3, 0, 0, 0).unwrap();
+    let cases = [
+      (value(|v| v.value_bool = Some(true)), "bool", "int", Ok(value(|v| v.value_int = Some(1)))),
+      (value(|v| v.value_bool = Some(false)), "bool", "float", Ok(value(|v| v.value_float = Some(0.0)))),
+      (value(|v| v.value_bool = Some(true)), "bool", "str", Ok(value(|v| v.value_str = Some("yes".to_owned())))),
+      (value(|v| v.value_str = Some("no".to_owned())), "str", "bool", Ok(value(|v| v.value_bool = Some(false)))),
+      (value(|v| { v.value_int = Some(12); v.value_str = Some("kg".to_owned()) }), "int", "float",
+        Ok(value(|v| { v.value_float = Some(12.0); v.value_str = Some("kg".to_owned()) }))),
+      (value(|v| v.value_str = Some("12 kg".to_owned())), "str", "int",
+        Ok(value(|v| { v.value_int = Some(12); v.value_str = Some("kg".to_owned()) }))),
+      (value(|v| v.value_time = Some(day)), "time", "str", Ok(value(|v| v.value_str = Some("2024-05-03".to_owned())))),
+      (value(|v| v.value_str = Some("2024-05-03".to_owned())), "str", "time", Ok(value(|v| v.value_time = Some(day)))),
+      (value(|_| ()), "int", "str", Ok(value(|_| ()))),
+      (value(|v| v.value_int = Some(9)), "ref", "ref", Ok(value(|v| v.value_int = Some(9)))),
+      (value(|v| v.value_float = Some(2.5)), "float", "int", Err("Expected a whole number")),
+      (value(|v| v.value_str = Some("abc".to_owned())), "str", "float", Err("Expected a number")),
+      (value(|v| v.value_int = Some(9)), "ref", "str", Err("Entity references only convert to entity references")),
+      (value(|v| v.value_int = Some(9)), "int", "ref", Err("Entity references only convert to entity references")),
+    ];
+    for (input, from, to, expected) in cases {
+      // EavValue has no PartialEq, its debug output covers every field
+      let res = convert_value(&input, from, to).map(|v| format!("{:?}", v));
+      assert_eq!(res, expected.map(|v| format!("{:?}", v)), "{} {:?} to {}", from, input, to);
+    }
+  }
+}
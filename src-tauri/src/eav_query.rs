@@ -0,0 +1,600 @@
+// search bar query language, parsed into an Expr tree that the store compiles to sql
+//
+//   dune OR "the hobbit"            entity name or alt_title, bare words are regex, quoted text is literal
+//   author:herbert year>=1965       attribute values, ':' matches and '=' is exact
+//   year:1900..1950 added:>-30d     ranges and dates, see parse_time
+//   has:isbn -has:cover             attribute is set / missing
+//   type:book created:>2024-01-01   entity type and creation date
+//   NOT (a OR b) AND c              grouping, AND is implied between terms
+
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::eav_error::{EavError, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator { Match, Eq, Ne, Gt, Ge, Lt, Le, Between }
+
+impl Operator {
+  pub fn is_ordered(&self) -> bool {
+    matches!(self, Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le | Operator::Between)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+  And(Box<Expr>, Box<Expr>),
+  Or(Box<Expr>, Box<Expr>),
+  Not(Box<Expr>),
+  // `literal` text is matched as is instead of as a regex, `pos` points at the text in the query
+  Text { text: String, literal: bool, pos: usize },
+  // `upper` is the end of a Between range
+  Attr { attr: String, op: Operator, value: String, upper: Option<String>, literal: bool, pos: usize },
+  Has { attr: String },
+  Type { name: String },
+  Created { op: Operator, span: DateSpan, upper: Option<DateSpan> },
+}
+
+// the time range [start, end) a date in the query stands for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateSpan {
+  pub start: DateTime<Utc>,
+  pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind { Word(String), Quoted(String), Open, Close }
+
+// `glued` tokens follow the previous token without whitespace, as in title:"some text"
+#[derive(Debug, Clone)]
+struct Token {
+  kind: TokenKind,
+  pos: usize,
+  glued: bool,
+}
+
+fn query_error(message: &str, pos: usize) -> EavError {
+  EavError::new(ErrorCode::InvalidQuery, message).with_field("query").with_position(pos)
+}
+
+// positions are counted in characters so they line up with the text in the search bar
+fn tokenize(input: &str) -> Result<Vec<Token>, EavError> {
+  let chars: Vec<char> = input.chars().collect();
+  let mut tokens = Vec::new();
+  let mut glued = false;
+  let mut i = 0;
+  while i < chars.len() {
+    if chars[i].is_whitespace() {
+      glued = false;
+      i += 1;
+      continue;
+    }
+    let pos = i;
+    let kind = match chars[i] {
+      '(' => { i += 1; TokenKind::Open }
+      ')' => { i += 1; TokenKind::Close }
+      '"' => {
+        let mut text = String::new();
+        i += 1;
+        loop {
+          match chars.get(i) {
+            None => return Err(query_error("Unterminated quote", pos)),
+            Some('"') => { i += 1; break; }
+            Some('\\') if chars.get(i + 1) == Some(&'"') => { text.push('"'); i += 2; }
+            Some(c) => { text.push(*c); i += 1; }
+          }
+        }
+        TokenKind::Quoted(text)
+      }
+      _ => {
+        while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')' | '"') { i += 1; }
+        TokenKind::Word(chars[pos..i].iter().collect())
+      }
+    };
+    tokens.push(Token { kind, pos, glued });
+    glued = true;
+  }
+  Ok(tokens)
+}
+
+// comparison at the start of `s`, a ':' may be followed by another operator as in created:>2024
+fn split_op(s: &[char]) -> Option<(Operator, usize)> {
+  let two: String = s.iter().take(2).collect();
+  let op = match two.as_str() {
+    ">=" => (Operator::Ge, 2),
+    "<=" => (Operator::Le, 2),
+    "!=" => (Operator::Ne, 2),
+    _ => match s.first() {
+      Some('>') => (Operator::Gt, 1),
+      Some('<') => (Operator::Lt, 1),
+      Some('=') => (Operator::Eq, 1),
+      Some(':') => match split_op(&s[1..]) {
+        Some((op, len)) => (op, len + 1),
+        None => (Operator::Match, 1)
+      },
+      _ => return None
+    }
+  };
+  Some(op)
+}
+
+// splits key<op>value, words without a valid key are plain text
+fn split_filter(word: &str) -> Option<(String, Operator, String, usize)> {
+  let chars: Vec<char> = word.chars().collect();
+  let key_len = chars.iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+  if key_len == 0 { return None; }
+  let (op, op_len) = split_op(&chars[key_len..])?;
+  let value_at = key_len + op_len;
+  Some((chars[..key_len].iter().collect(), op, chars[value_at..].iter().collect(), value_at))
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+  match value.to_lowercase().as_str() {
+    "true" | "yes" | "y" => Some(true),
+    "false" | "no" | "n" => Some(false),
+    _ => None
+  }
+}
+
+// right hand side of a comparison, "2024" is both a number and a year
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Operand {
+  pub number: Option<f64>,
+  pub time: Option<DateSpan>,
+}
+
+impl Operand {
+  pub fn parse(value: &str) -> Self {
+    Operand {
+      number: value.parse::<f64>().ok().filter(|n| n.is_finite()),
+      time: parse_time(value),
+    }
+  }
+}
+
+// a date covers everything up to the next one with the same precision,
+// so 2024 is the whole year and 2024-05-03 the whole day. times are UTC
+//   2024, 2024-05, 2024-05-03, 2024-05-03T10:30, 2024-05-03T10:30:00+02:00
+//   -30d, -12h, -2w, -6m, -1y relative to now
+pub fn parse_time(value: &str) -> Option<DateSpan> {
+  let value = value.trim();
+  if let Some(span) = parse_relative(value) {
+    return Some(span);
+  }
+  let day = |date: NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default());
+  let parts: Vec<&str> = value.split('-').collect();
+  let all_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+  match parts.as_slice() {
+    [y] if y.len() == 4 && all_digits(y) => {
+      let start = day(NaiveDate::from_ymd_opt(y.parse().ok()?, 1, 1)?);
+      let end = start.checked_add_months(Months::new(12))?;
+      return Some(DateSpan { start, end });
+    }
+    [y, m] if y.len() == 4 && all_digits(y) && m.len() == 2 && all_digits(m) => {
+      let start = day(NaiveDate::from_ymd_opt(y.parse().ok()?, m.parse().ok()?, 1)?);
+      let end = start.checked_add_months(Months::new(1))?;
+      return Some(DateSpan { start, end });
+    }
+    _ => ()
+  }
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    let start = day(date);
+    return Some(DateSpan { start, end: start + Duration::days(1) });
+  }
+  for (format, len) in [("%Y-%m-%dT%H:%M", 60), ("%Y-%m-%d %H:%M", 60), ("%Y-%m-%dT%H:%M:%S", 1), ("%Y-%m-%d %H:%M:%S", 1)] {
+    if let Ok(at) = NaiveDateTime::parse_from_str(value, format) {
+      let start = Utc.from_utc_datetime(&at);
+      return Some(DateSpan { start, end: start + Duration::seconds(len) });
+    }
+  }
+  let at = DateTime::parse_from_rfc3339(value).ok()?.with_timezone(&Utc);
+  Some(DateSpan { start: at, end: at + Duration::seconds(1) })
+}
+
+// -30d and friends, the span is the single second they point at
+fn parse_relative(value: &str) -> Option<DateSpan> {
+  let amount = value.strip_prefix('-')?;
+  let unit = amount.chars().last()?;
+  let n: u32 = amount[..amount.len() - unit.len_utf8()].parse().ok()?;
+  let now = Utc::now();
+  let at = match unit {
+    'h' => now - Duration::hours(n as i64),
+    'd' => now - Duration::days(n as i64),
+    'w' => now - Duration::weeks(n as i64),
+    'm' => now.checked_sub_months(Months::new(n))?,
+    'y' => now.checked_sub_months(Months::new(n.checked_mul(12)?))?,
+    _ => return None
+  };
+  Some(DateSpan { start: at, end: at + Duration::seconds(1) })
+}
+
+// validates the values of a comparison before any sql is built
+pub fn check_comparison(op: Operator, value: &str, upper: Option<&str>) -> Result<(), &'static str> {
+  if !op.is_ordered() { return Ok(()); }
+  let lower = Operand::parse(value);
+  if lower.number.is_none() && lower.time.is_none() {
+    return Err("Expected a number or a date");
+  }
+  if op != Operator::Between { return Ok(()); }
+  let upper = match upper {
+    Some(upper) => Operand::parse(upper),
+    None => return Err("Expected a range like 1..10")
+  };
+  let numbers = lower.number.is_some() && upper.number.is_some();
+  let times = lower.time.is_some() && upper.time.is_some();
+  if !numbers && !times {
+    return Err("Both ends of a range need to be numbers or dates");
+  }
+  Ok(())
+}
+
+// year:1900..1950 is a Between, quoted values are never ranges
+fn split_range(op: Operator, value: String, literal: bool, pos: usize) -> (Operator, String, Option<String>, usize) {
+  if literal || !matches!(op, Operator::Match | Operator::Eq) {
+    return (op, value, None, pos);
+  }
+  match value.split_once("..") {
+    Some((lower, upper)) if !lower.is_empty() && !upper.is_empty() => {
+      let upper_pos = pos + lower.chars().count() + 2;
+      (Operator::Between, lower.to_owned(), Some(upper.to_owned()), upper_pos)
+    }
+    _ => (op, value, None, pos)
+  }
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  at: usize,
+  len: usize,
+}
+
+pub fn parse(input: &str) -> Result<Expr, EavError> {
+  let tokens = tokenize(input)?;
+  if tokens.is_empty() {
+    return Err(query_error("Search query is empty", 0));
+  }
+  let mut parser = Parser { tokens, at: 0, len: input.chars().count() };
+  let expr = parser.parse_or()?;
+  // parse_or only stops early on a ')' without a matching '('
+  if let Some(token) = parser.peek() {
+    return Err(query_error("Unexpected ')'", token.pos));
+  }
+  Ok(expr)
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.at)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.at).cloned();
+    if token.is_some() { self.at += 1; }
+    token
+  }
+
+  fn peek_keyword(&self, keyword: &str) -> bool {
+    matches!(self.peek(), Some(Token { kind: TokenKind::Word(w), .. }) if w == keyword)
+  }
+
+  // quoted text right after the current token, as in title:"some text"
+  fn next_glued_quote(&mut self) -> Option<(String, usize)> {
+    match self.peek() {
+      Some(Token { kind: TokenKind::Quoted(text), glued: true, pos }) => {
+        let res = (text.clone(), *pos);
+        self.at += 1;
+        Some(res)
+      }
+      _ => None
+    }
+  }
+
+  fn parse_or(&mut self) -> Result<Expr, EavError> {
+    let mut left = self.parse_and()?;
+    while self.peek_keyword("OR") {
+      self.next();
+      let right = self.parse_and()?;
+      left = Expr::Or(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+  }
+
+  fn parse_and(&mut self) -> Result<Expr, EavError> {
+    let mut left = self.parse_not()?;
+    loop {
+      if self.peek_keyword("AND") {
+        self.next();
+      } else {
+        match self.peek() {
+          None | Some(Token { kind: TokenKind::Close, .. }) => break,
+          Some(Token { kind: TokenKind::Word(w), .. }) if w == "OR" => break,
+          _ => ()
+        }
+      }
+      let right = self.parse_not()?;
+      left = Expr::And(Box::new(left), Box::new(right));
+    }
+    Ok(left)
+  }
+
+  fn parse_not(&mut self) -> Result<Expr, EavError> {
+    if self.peek_keyword("NOT") {
+      self.next();
+      return Ok(Expr::Not(Box::new(self.parse_not()?)));
+    }
+    self.parse_term()
+  }
+
+  fn parse_term(&mut self) -> Result<Expr, EavError> {
+    let token = match self.next() {
+      Some(token) => token,
+      None => return Err(query_error("Expected a search term", self.len))
+    };
+    match token.kind {
+      TokenKind::Open => {
+        let inner = self.parse_or()?;
+        match self.next() {
+          Some(Token { kind: TokenKind::Close, .. }) => Ok(inner),
+          _ => Err(query_error("Missing closing parenthesis", token.pos))
+        }
+      }
+      TokenKind::Close => Err(query_error("Unexpected ')'", token.pos)),
+      TokenKind::Quoted(text) => {
+        // attribute names with spaces can be quoted: "release date">2000
+        if let Some(Token { kind: TokenKind::Word(w), glued: true, pos }) = self.peek().cloned() {
+          let chars: Vec<char> = w.chars().collect();
+          if let Some((op, op_len)) = split_op(&chars) {
+            self.next();
+            let value: String = chars[op_len..].iter().collect();
+            return self.parse_filter(text, false, op, value, pos + op_len, pos + chars.len());
+          }
+        }
+        Ok(Expr::Text { text, literal: true, pos: token.pos })
+      }
+      TokenKind::Word(w) if w == "AND" || w == "OR" => Err(query_error("Expected a search term", token.pos)),
+      TokenKind::Word(w) => self.parse_word(w, token.pos)
+    }
+  }
+
+  fn parse_word(&mut self, word: String, pos: usize) -> Result<Expr, EavError> {
+    // -term excludes a single term
+    if word == "-" {
+      return Ok(Expr::Not(Box::new(self.parse_term()?)));
+    }
+    if let Some(rest) = word.strip_prefix('-') {
+      return Ok(Expr::Not(Box::new(self.parse_word(rest.to_owned(), pos + 1)?)));
+    }
+    match split_filter(&word) {
+      Some((key, op, value, value_at)) => {
+        self.parse_filter(key, true, op, value, pos + value_at, pos + word.chars().count())
+      }
+      None => Ok(Expr::Text { text: word, literal: false, pos })
+    }
+  }
+
+  // `keywords` is off for quoted keys, so an attribute called "type" can still be searched
+  fn parse_filter(
+    &mut self, key: String, keywords: bool, op: Operator, value: String, value_pos: usize, end: usize
+  ) -> Result<Expr, EavError> {
+    let (value, literal, value_pos) = if value.is_empty() {
+      match self.next_glued_quote() {
+        Some((text, pos)) => (text, true, pos),
+        None => return Err(query_error(&format!("Expected a value after '{}'", key), end))
+      }
+    } else {
+      (value, false, value_pos)
+    };
+    let keyword = if keywords { key.to_lowercase() } else { String::new() };
+    match keyword.as_str() {
+      "has" => match op {
+        Operator::Match => Ok(Expr::Has { attr: value }),
+        _ => Err(query_error("has: only takes an attribute name", value_pos))
+      },
+      "type" => match op {
+        Operator::Match | Operator::Eq => Ok(Expr::Type { name: value }),
+        Operator::Ne => Ok(Expr::Not(Box::new(Expr::Type { name: value }))),
+        _ => Err(query_error("type: can only be compared with ':' or '!='", value_pos))
+      },
+      "created" => {
+        let (op, value, upper, upper_pos) = split_range(op, value, literal, value_pos);
+        let span = match parse_time(&value) {
+          Some(span) => span,
+          None => return Err(query_error("Expected a date like 2024-01-31 or -30d", value_pos))
+        };
+        let upper = match upper {
+          Some(upper) => match parse_time(&upper) {
+            Some(span) => Some(span),
+            None => return Err(query_error("Expected a date like 2024-01-31 or -30d", upper_pos))
+          },
+          None => None
+        };
+        Ok(Expr::Created { op, span, upper })
+      }
+      _ => {
+        let (op, value, upper, upper_pos) = split_range(op, value, literal, value_pos);
+        if let Err(message) = check_comparison(op, &value, upper.as_deref()) {
+          // point at the end that is wrong
+          let pos = match upper.as_deref().map(Operand::parse) {
+            Some(u) if u.number.is_none() && u.time.is_none() => upper_pos,
+            _ => value_pos
+          };
+          return Err(query_error(message, pos));
+        }
+        Ok(Expr::Attr { attr: key, op, value, upper, literal, pos: value_pos })
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(text: &str, literal: bool, pos: usize) -> Expr {
+    Expr::Text { text: text.to_owned(), literal, pos }
+  }
+
+  fn attr(attr: &str, op: Operator, value: &str, upper: Option<&str>, pos: usize) -> Expr {
+    Expr::Attr { attr: attr.to_owned(), op, value: value.to_owned(), upper: upper.map(str::to_owned), literal: false, pos }
+  }
+
+  fn and(a: Expr, b: Expr) -> Expr { Expr::And(Box::new(a), Box::new(b)) }
+  fn or(a: Expr, b: Expr) -> Expr { Expr::Or(Box::new(a), Box::new(b)) }
+  fn not(a: Expr) -> Expr { Expr::Not(Box::new(a)) }
+
+  fn at(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+  }
+
+  #[test]
+  fn and_binds_tighter_than_or() {
+    let cases = [
+      ("a b OR c", or(and(text("a", false, 0), text("b", false, 2)), text("c", false, 7))),
+      ("a OR b c", or(text("a", false, 0), and(text("b", false, 5), text("c", false, 7)))),
+      ("a AND (b OR c)", and(text("a", false, 0), or(text("b", false, 7), text("c", false, 12)))),
+      ("NOT a b", and(not(text("a", false, 4)), text("b", false, 6))),
+      ("-has:cover x", and(not(Expr::Has { attr: "cover".to_owned() }), text("x", false, 11))),
+    ];
+    for (query, expected) in cases {
+      assert_eq!(parse(query).unwrap(), expected, "{}", query);
+    }
+  }
+
+  #[test]
+  fn quotes_are_literal() {
+    let cases = [
+      (r#""the hobbit""#, text("the hobbit", true, 0)),
+      (r#""say \"hi\"""#, text(r#"say "hi""#, true, 0)),
+      (r#"title:"a b""#, Expr::Attr {
+        attr: "title".to_owned(), op: Operator::Match, value: "a b".to_owned(), upper: None, literal: true, pos: 6
+      }),
+      (r#""release date">2000"#, attr("release date", Operator::Gt, "2000", None, 15)),
+      // quoted values are never ranges
+      (r#"year:"1..2""#, Expr::Attr {
+        attr: "year".to_owned(), op: Operator::Match, value: "1..2".to_owned(), upper: None, literal: true, pos: 5
+      }),
+      // quoted keys aren't keywords
+      (r#""type":book"#, attr("type", Operator::Match, "book", None, 7)),
+    ];
+    for (query, expected) in cases {
+      assert_eq!(parse(query).unwrap(), expected, "{}", query);
+    }
+  }
+
+  #[test]
+  fn filters() {
+    let cases = [
+      ("author:herbert", attr("author", Operator::Match, "herbert", None, 7)),
+      ("year>=1965", attr("year", Operator::Ge, "1965", None, 6)),
+      ("year:>1965", attr("year", Operator::Gt, "1965", None, 6)),
+      ("year!=1965", attr("year", Operator::Ne, "1965", None, 6)),
+      ("year:1900..1950", attr("year", Operator::Between, "1900", Some("1950"), 5)),
+      ("type:book", Expr::Type { name: "book".to_owned() }),
+      ("type!=book", not(Expr::Type { name: "book".to_owned() })),
+    ];
+    for (query, expected) in cases {
+      assert_eq!(parse(query).unwrap(), expected, "{}", query);
+    }
+  }
+
+  #[test]
+  fn ranges_and_partial_dates() {
+    let span = |start: &str, end: &str| DateSpan { start: at(start), end: at(end) };
+    let cases = [
+      ("price=12.5", attr("price", Operator::Eq, "12.5", None, 6)),
+      ("released<=-30d", attr("released", Operator::Le, "-30d", None, 10)),
+      ("released:2024-05", attr("released", Operator::Match, "2024-05", None, 9)),
+      ("released:2020..2024-06", attr("released", Operator::Between, "2020", Some("2024-06"), 9)),
+      ("created:2024-05", Expr::Created {
+        op: Operator::Match, span: span("2024-05-01T00:00:00Z", "2024-06-01T00:00:00Z"), upper: None
+      }),
+      ("created:2020..2023", Expr::Created {
+        op: Operator::Between,
+        span: span("2020-01-01T00:00:00Z", "2021-01-01T00:00:00Z"),
+        upper: Some(span("2023-01-01T00:00:00Z", "2024-01-01T00:00:00Z"))
+      }),
+    ];
+    for (query, expected) in cases {
+      assert_eq!(parse(query).unwrap(), expected, "{}", query);
+    }
+    match parse("created:>=-7d").unwrap() {
+      Expr::Created { op: Operator::Ge, span, upper: None } => {
+        assert!((Utc::now() - Duration::days(7) - span.start).num_seconds().abs() <= 1);
+      }
+      other => panic!("{:?}", other)
+    }
+  }
+
+  #[test]
+  fn errors_point_at_the_problem() {
+    let cases = [
+      ("", "Search query is empty", 0),
+      (r#"a "open"#, "Unterminated quote", 2),
+      ("(a OR b", "Missing closing parenthesis", 0),
+      ("a ) b", "Unexpected ')'", 2),
+      ("a AND OR b", "Expected a search term", 6),
+      ("a AND", "Expected a search term", 5),
+      ("title:", "Expected a value after 'title'", 6),
+      ("has>x", "has: only takes an attribute name", 4),
+      ("type>x", "type: can only be compared with ':' or '!='", 5),
+      ("created:soon", "Expected a date like 2024-01-31 or -30d", 8),
+      ("created:2024..later", "Expected a date like 2024-01-31 or -30d", 14),
+      ("year>abc", "Expected a number or a date", 5),
+      ("year:1..x", "Both ends of a range need to be numbers or dates", 8),
+    ];
+    for (query, message, position) in cases {
+      let e = parse(query).unwrap_err();
+      assert_eq!(e.code, ErrorCode::InvalidQuery, "{}", query);
+      assert_eq!((e.message.as_str(), e.position), (message, Some(position)), "{}", query);
+    }
+  }
+
+  #[test]
+  fn dates_cover_their_precision() {
+    let cases = [
+      ("2024", "2024-01-01T00:00:00Z", "2025-01-01T00:00:00Z"),
+      ("2024-02", "2024-02-01T00:00:00Z", "2024-03-01T00:00:00Z"),
+      ("2024-12", "2024-12-01T00:00:00Z", "2025-01-01T00:00:00Z"),
+      ("2024-02-29", "2024-02-29T00:00:00Z", "2024-03-01T00:00:00Z"),
+      ("2024-05-03T10:30", "2024-05-03T10:30:00Z", "2024-05-03T10:31:00Z"),
+      ("2024-05-03 10:30:15", "2024-05-03T10:30:15Z", "2024-05-03T10:30:16Z"),
+      ("2024-05-03T10:30:00+02:00", "2024-05-03T08:30:00Z", "2024-05-03T08:30:01Z"),
+    ];
+    for (value, start, end) in cases {
+      assert_eq!(parse_time(value), Some(DateSpan { start: at(start), end: at(end) }), "{}", value);
+    }
+    for value in ["", "24", "2024-13", "2023-02-29", "2024/05/03", "-5x", "soon"] {
+      assert_eq!(parse_time(value), None, "{}", value);
+    }
+  }
+
+  #[test]
+  fn relative_dates_count_back_from_now() {
+    let now = Utc::now();
+    let cases = [("-12h", Duration::hours(12)), ("-30d", Duration::days(30)), ("-2w", Duration::weeks(2))];
+    for (value, back) in cases {
+      let span = parse_time(value).unwrap();
+      let drift = (now - back - span.start).num_seconds().abs();
+      assert!(drift <= 1, "{} is {}s off", value, drift);
+      assert_eq!(span.end - span.start, Duration::seconds(1));
+    }
+    assert!(parse_time("-1y").unwrap().start < parse_time("-6m").unwrap().start);
+  }
+
+  #[test]
+  fn comparisons_need_numbers_or_dates() {
+    let cases = [
+      (Operator::Match, "anything", None, Ok(())),
+      (Operator::Ne, "anything", None, Ok(())),
+      (Operator::Gt, "12.5", None, Ok(())),
+      (Operator::Le, "2024-05", None, Ok(())),
+      (Operator::Lt, "abc", None, Err("Expected a number or a date")),
+      (Operator::Between, "1", None, Err("Expected a range like 1..10")),
+      (Operator::Between, "1", Some("10"), Ok(())),
+      (Operator::Between, "2020", Some("2024-06"), Ok(())),
+      (Operator::Between, "1", Some("2024-06"), Err("Both ends of a range need to be numbers or dates")),
+    ];
+    for (op, value, upper, expected) in cases {
+      assert_eq!(check_comparison(op, value, upper), expected, "{:?} {} {:?}", op, value, upper);
+    }
+  }
+}
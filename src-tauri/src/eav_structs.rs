@@ -7,6 +7,7 @@ pub struct EavEntityType {
   pub id: u32,
   pub created_at: DateTime<Utc>,
   pub entity_type: String,
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
@@ -15,6 +16,7 @@ pub struct EavEntity {
   pub created_at: DateTime<Utc>,
   pub entity: String,
   pub entity_type_id: u32,
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
@@ -25,6 +27,10 @@ pub struct EavAttribute {
   pub entity_type_id: u32,
   pub value_type: String,
   pub allow_multiple: Option<bool>,
+  pub deleted_at: Option<DateTime<Utc>>,
+  // ref attrs only: the category the referenced entities have to be in and an OnDelete rule
+  pub ref_entity_type_id: Option<u32>,
+  pub on_delete: Option<String>,
 }
 
 #[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
@@ -38,6 +44,7 @@ pub struct EavValue {
   pub value_float: Option<f32>,
   pub value_time: Option<DateTime<Utc>>,
   pub value_bool: Option<bool>,
+  pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Default, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
@@ -52,6 +59,7 @@ pub struct EavView {
   pub attr: Option<String>,
   pub value_type: Option<String>,
   pub allow_multiple: Option<bool>,
+  pub ref_entity_type_id: Option<u32>,
   // value related
   pub value_id: Option<u32>,
   pub created_at: Option<DateTime<Utc>>,
@@ -60,6 +68,435 @@ pub struct EavView {
   pub value_float: Option<f32>,
   pub value_time: Option<DateTime<Utc>>,
   pub value_bool: Option<bool>,
+  // name of the entity a ref value points to, None while it's in the trash
+  pub ref_entity: Option<String>,
+}
+
+// rows touched by a delete, restore or purge, including everything it cascaded to
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeleteSummary {
+  pub entity_types: u64,
+  pub entities: u64,
+  pub attrs: u64,
+  pub values: u64,
+}
+
+// value an entity keeps when its attribute stops allowing multiple values, the others go to the trash
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeepValue { Oldest, Newest }
+
+// what deleting an entity does to the ref values pointing at it: block refuses the delete,
+// nullify empties the values and cascade deletes the entities holding them as well
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnDelete { #[default] Block, Nullify, Cascade }
+
+impl OnDelete {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      OnDelete::Block => "block",
+      OnDelete::Nullify => "nullify",
+      OnDelete::Cascade => "cascade",
+    }
+  }
+
+  // rule stored on an attr, block when missing
+  pub fn of(attr: &EavAttribute) -> Self {
+    match attr.on_delete.as_deref() {
+      Some("nullify") => OnDelete::Nullify,
+      Some("cascade") => OnDelete::Cascade,
+      _ => OnDelete::Block
+    }
+  }
+}
+
+// top level item in the trash bin, rows trashed along with their parent are not listed
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct TrashItem {
+  pub kind: String,
+  pub id: u32,
+  pub name: Option<String>,
+  pub parent: Option<String>,
+  pub deleted_at: DateTime<Utc>,
+}
+
+// one change to a row, old_data/new_data hold the row as json before and after the change
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct EavHistory {
+  pub id: u32,
+  pub changed_at: DateTime<Utc>,
+  pub action: String,
+  pub target: String,
+  pub target_id: u32,
+  pub entity_type_id: Option<u32>,
+  pub entity_id: Option<u32>,
+  pub old_data: Option<String>,
+  pub new_data: Option<String>,
+}
+
+// what an undo or redo changed back, counted per table with the history entries it wrote
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UndoReport {
+  pub command: String,
+  pub entity_types: u64,
+  pub entities: u64,
+  pub attrs: u64,
+  pub values: u64,
+  pub changes: Vec<EavHistory>,
+}
+
+impl UndoReport {
+  pub fn new(command: &str, changes: Vec<EavHistory>) -> Self {
+    let count = |target: &str| changes.iter().filter(|c| c.target == target).count() as u64;
+    UndoReport {
+      command: command.to_owned(),
+      entity_types: count(EavEntityType::TARGET),
+      entities: count(EavEntity::TARGET),
+      attrs: count(EavAttribute::TARGET),
+      values: count(EavValue::TARGET),
+      changes,
+    }
+  }
+}
+
+// which slice of a list to return, a `cursor` from a previous page continues right after its last item
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PageRequest {
+  pub page: u32,
+  pub per_page: u32,
+  pub cursor: Option<String>,
+}
+
+impl PageRequest {
+  pub const DEFAULT_PER_PAGE: u32 = 100;
+  pub const MAX_PER_PAGE: u32 = 1000;
+
+  pub fn new(page: Option<u32>, per_page: Option<u32>, cursor: Option<String>) -> Self {
+    PageRequest {
+      page: page.unwrap_or(1).max(1),
+      per_page: per_page.unwrap_or(Self::DEFAULT_PER_PAGE).clamp(1, Self::MAX_PER_PAGE),
+      cursor: cursor.filter(|c| !c.is_empty()),
+    }
+  }
+}
+
+impl Default for PageRequest {
+  fn default() -> Self { PageRequest::new(None, None, None) }
+}
+
+// what entity lists are ordered by, attribute values sort by their type with missing values last
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+  Entity,
+  CreatedAt,
+  Attr(String),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SortKey {
+  pub by: SortBy,
+  #[serde(default)]
+  pub desc: bool,
+}
+
+// one page of a list, `total` counts every matching row across all pages
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Page<T> {
+  pub items: Vec<T>,
+  pub total: u64,
+  pub page: u32,
+  pub per_page: u32,
+  pub has_more: bool,
+  pub next_cursor: Option<String>,
+}
+
+// value in the column type of its attribute, plain json values in the table view
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum EavCell {
+  Bool(bool),
+  Int(i32),
+  Float(f32),
+  Time(DateTime<Utc>),
+  // number with the unit kept in value_str
+  Measure { value: Box<EavCell>, unit: String },
+  // referenced entity, `entity` is None while it's in the trash
+  Ref { id: u32, entity: Option<String> },
+  Str(String),
+}
+
+impl EavCell {
+  // None when the column for the value type is empty, refs come without the name of their entity
+  pub fn from_value(value_type: &str, value: &EavValue) -> Option<Self> {
+    let with_unit = |number: EavCell| match value.value_str.as_deref().map(str::trim) {
+      Some(unit) if !unit.is_empty() => EavCell::Measure { value: Box::new(number), unit: unit.to_owned() },
+      _ => number
+    };
+    match value_type {
+      "str" => value.value_str.clone().map(EavCell::Str),
+      "int" => value.value_int.map(EavCell::Int).map(with_unit),
+      "float" => value.value_float.map(EavCell::Float).map(with_unit),
+      "time" => value.value_time.map(EavCell::Time),
+      "bool" => value.value_bool.map(EavCell::Bool),
+      "ref" => value.value_int.map(|id| EavCell::Ref { id: id as u32, entity: None }),
+      _ => None
+    }
+  }
+}
+
+// cells of allow_multiple attributes are always lists, other cells a single value or null
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum TableCell {
+  Many(Vec<EavCell>),
+  One(EavCell),
+  Empty,
+}
+
+// one entity with a cell per column of the table
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TableRow {
+  pub entity: EavEntity,
+  pub cells: Vec<TableCell>,
+}
+
+// entity type pivoted to one row per entity and one column per attribute, paged like entity lists
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EavTable {
+  pub entity_type: EavEntityType,
+  pub columns: Vec<EavAttribute>,
+  #[serde(flatten)]
+  pub rows: Page<TableRow>,
+}
+
+// csv column filling an attribute, attr_id is None when the import creates the attribute
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnMapping {
+  pub column: usize,
+  pub attr: String,
+  pub value_type: String,
+  #[serde(default)]
+  pub allow_multiple: bool,
+  pub attr_id: Option<u32>,
+}
+
+// first rows of a csv file with the proposed mapping, columns left out of `columns` are skipped
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CsvPreview {
+  pub delimiter: String,
+  pub separator: String,
+  pub headers: Vec<String>,
+  pub rows: Vec<Vec<String>>,
+  pub total_rows: u64,
+  pub entity_column: usize,
+  pub columns: Vec<ColumnMapping>,
+}
+
+// problem with one row of an import, `line` is the line in the file (the header is line 1)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ImportError {
+  pub line: Option<u64>,
+  pub column: Option<String>,
+  pub message: String,
+}
+
+// what an import wrote, nothing is kept on a dry run or when any row failed
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ImportReport {
+  pub dry_run: bool,
+  pub committed: bool,
+  pub rows: u64,
+  pub entities: u64,
+  pub attrs: u64,
+  pub values: u64,
+  pub errors: Vec<ImportError>,
+}
+
+// rows written to an archive, trashed ones included
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveSummary {
+  pub entity_types: u64,
+  pub entities: u64,
+  pub attrs: u64,
+  pub values: u64,
+}
+
+// what restoring an archive added, rows clashing with existing names are skipped with their values and
+// listed in `conflicts`, the rest is kept unless it's a dry run
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveReport {
+  pub dry_run: bool,
+  pub committed: bool,
+  pub entity_types: u64,
+  pub entities: u64,
+  pub attrs: u64,
+  pub values: u64,
+  pub skipped: u64,
+  pub conflicts: Vec<ImportError>,
+}
+
+// what happens to values that can't be converted to an attribute's new value type
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConvertPolicy {
+  // the value goes to the trash as it was
+  Trash,
+  // the value stays without content
+  Null,
+  // nothing is converted
+  Abort,
+}
+
+// value that doesn't fit the new value type, `value` is its current content as text
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConvertFailure {
+  pub value_id: u32,
+  pub entity_id: u32,
+  pub entity: String,
+  pub value: String,
+  pub message: String,
+  pub in_trash: bool,
+}
+
+// what converting an attribute to another value type did, nothing is kept on a dry run
+// or when the policy is abort and any value failed
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ConvertReport {
+  pub dry_run: bool,
+  pub committed: bool,
+  pub converted: u64,
+  pub trashed: u64,
+  pub emptied: u64,
+  pub failures: Vec<ConvertFailure>,
+}
+
+// attribute of a moved entity's category and where its values go, `to` None leaves them behind
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AttrMapping {
+  pub from: u32,
+  pub to: Option<u32>,
+}
+
+// how a move resolved a source attribute, explicitly or by name, `created` when the move adds it to the target
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AttrMove {
+  pub from: u32,
+  pub attr: String,
+  pub to: Option<u32>,
+  pub target: Option<String>,
+  pub created: bool,
+}
+
+// value a move leaves behind in the trash, `value` is its content as text
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DroppedValue {
+  pub value_id: u32,
+  pub attr: String,
+  pub value: String,
+  pub reason: String,
+}
+
+// what clone_entity copies: the values of `attrs`, or all of them when None. numbers of the `increment`
+// attrs go up by one in the copy, the name is made up from the original's when there is none
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CloneOptions {
+  pub name: Option<String>,
+  pub attrs: Option<Vec<u32>>,
+  #[serde(default)]
+  pub increment: Vec<u32>,
+}
+
+// what moving an entity to another category did, nothing is kept on a dry run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MoveReport {
+  pub dry_run: bool,
+  pub committed: bool,
+  pub entity: EavEntity,
+  pub mapping: Vec<AttrMove>,
+  pub values: u64,
+  pub attrs: u64,
+  pub dropped: Vec<DroppedValue>,
+}
+
+// value a single value attribute keeps when both merged entities have a different one
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum MergeStrategy { KeepTarget, KeepSource, KeepNewest }
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AttrStrategy {
+  pub attr_id: u32,
+  pub strategy: MergeStrategy,
+}
+
+// differing values of a single value attribute, `target` and `source` are their contents as text
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MergeConflict {
+  pub attr_id: u32,
+  pub attr: String,
+  pub target: String,
+  pub source: String,
+  pub kept_source: bool,
+}
+
+// what merging an entity into another did. `moved` values went over to the target, `duplicates` were
+// already there and went to the trash with the source, `relinked` ref values now point at the target.
+// `self_refs` are refs between the two that would have pointed the target at itself, they go to the trash.
+// nothing is kept on a dry run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MergeReport {
+  pub dry_run: bool,
+  pub committed: bool,
+  pub entity: EavEntity,
+  pub moved: u64,
+  pub duplicates: u64,
+  pub relinked: u64,
+  pub self_refs: u64,
+  pub conflicts: Vec<MergeConflict>,
+}
+
+// rows that get written to the history table
+pub trait Tracked: serde::Serialize + serde::de::DeserializeOwned + Send + Sync {
+  const TARGET: &'static str;
+  fn id(&self) -> u32;
+  fn entity_type_id(&self) -> Option<u32>;
+  fn entity_id(&self) -> Option<u32>;
+  fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>);
+}
+
+impl Tracked for EavEntityType {
+  const TARGET: &'static str = "entity_type";
+  fn id(&self) -> u32 { self.id }
+  fn entity_type_id(&self) -> Option<u32> { Some(self.id) }
+  fn entity_id(&self) -> Option<u32> { None }
+  fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) { self.deleted_at = deleted_at }
+}
+
+impl Tracked for EavEntity {
+  const TARGET: &'static str = "entity";
+  fn id(&self) -> u32 { self.id }
+  fn entity_type_id(&self) -> Option<u32> { Some(self.entity_type_id) }
+  fn entity_id(&self) -> Option<u32> { Some(self.id) }
+  fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) { self.deleted_at = deleted_at }
+}
+
+impl Tracked for EavAttribute {
+  const TARGET: &'static str = "attr";
+  fn id(&self) -> u32 { self.id }
+  fn entity_type_id(&self) -> Option<u32> { Some(self.entity_type_id) }
+  fn entity_id(&self) -> Option<u32> { None }
+  fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) { self.deleted_at = deleted_at }
+}
+
+impl Tracked for EavValue {
+  const TARGET: &'static str = "value";
+  fn id(&self) -> u32 { self.id }
+  fn entity_type_id(&self) -> Option<u32> { None }
+  fn entity_id(&self) -> Option<u32> { Some(self.entity_id) }
+  fn set_deleted_at(&mut self, deleted_at: Option<DateTime<Utc>>) { self.deleted_at = deleted_at }
 }
 
 impl EavView {
@@ -73,13 +510,37 @@ impl EavView {
       attr: None,
       value_type: None,
       allow_multiple: None,
+      ref_entity_type_id: None,
       value_id: None,
       created_at: None,
       value_str: None,
       value_int: None,
       value_float: None,
       value_time: None,
-      value_bool: None
+      value_bool: None,
+      ref_entity: None
+    }
+  }
+
+  pub fn from_value(entity_type: &EavEntityType, entity: &EavEntity, attr: &EavAttribute, value: &EavValue) -> Self {
+    EavView {
+      entity_type_id: Some(entity_type.id),
+      entity_type: Some(entity_type.entity_type.clone()),
+      entity_id: Some(entity.id),
+      entity: Some(entity.entity.clone()),
+      attr_id: Some(attr.id),
+      attr: Some(attr.attr.clone()),
+      value_type: Some(attr.value_type.clone()),
+      allow_multiple: attr.allow_multiple,
+      ref_entity_type_id: attr.ref_entity_type_id,
+      value_id: Some(value.id),
+      created_at: Some(value.created_at),
+      value_str: value.value_str.clone(),
+      value_int: value.value_int,
+      value_float: value.value_float,
+      value_time: value.value_time,
+      value_bool: value.value_bool,
+      ref_entity: None,
     }
   }
 
@@ -90,6 +551,7 @@ impl EavView {
       attr: Some(attr.attr),
       value_type: Some(attr.value_type),
       allow_multiple: attr.allow_multiple,
+      ref_entity_type_id: attr.ref_entity_type_id,
       ..Default::default()
     }
   }
@@ -2,21 +2,52 @@
 // #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use async_std::sync::Mutex;
-use eav_structs::{EavAttribute, EavEntity, EavEntityType, EavValue, EavView};
-use std::process::Command;
-use tauri::{RunEvent, State};
+use chrono::{DateTime, Utc};
+use config::{DbBackend, DbConfig, ServerConfig};
+use db_server::DbServer;
+use eav_error::{EavError, ErrorCode};
+use eav_structs::{
+    ArchiveReport, ArchiveSummary, AttrMapping, AttrStrategy, CloneOptions, ColumnMapping, ConvertPolicy, ConvertReport,
+    CsvPreview, DeleteSummary, EavAttribute, EavEntity, EavEntityType, EavHistory, EavTable, EavValue, EavView, ImportReport,
+    KeepValue, MergeReport, MoveReport, OnDelete, Page, PageRequest, SortKey, TrashItem, UndoReport
+};
+use tauri::{Manager, RunEvent, State};
 
+mod config;
 mod db_interface;
+mod db_mysql;
+mod db_server;
+mod db_sqlite;
+mod db_store;
+mod eav_archive;
+mod eav_backup;
+mod eav_duplicates;
+mod eav_error;
+mod eav_export;
+mod eav_import;
+mod eav_query;
 mod eav_structs;
-use db_interface::{DBInterface, Operator};
+use db_interface::{DBInterface, TrashKind};
+use eav_backup::BackupInfo;
+use eav_duplicates::{DuplicateGroup, DuplicateOptions};
+use eav_export::{ExportFormat, ExportOptions, ExportSummary};
+use eav_import::CsvOptions;
 
 struct TState {
-    pub db: Mutex<DBInterface>
+    pub db: Mutex<DBInterface>,
+    pub server: Mutex<Option<DbServer>>
 }
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
-async fn connect(state: State<'_, TState>) -> Result<String, String> {
+async fn connect(state: State<'_, TState>) -> Result<String, EavError> {
+    // wait for a managed server to accept connections first
+    if let Some(server) = state.server.lock().await.as_mut() {
+        if let Err(e) = server.wait_ready().await {
+            println!("Database server not ready: {:?}", e);
+            return Err(e);
+        }
+    }
     let mut dbi = state.db.lock().await;
     match dbi.connect().await {
         Ok(msg) => {
@@ -25,32 +56,36 @@ async fn connect(state: State<'_, TState>) -> Result<String, String> {
         }
         Err(e) => {
             println!("Failed to connect to DB: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn fetch_entity_types(state: State<'_, TState>) -> Result<Vec<EavEntityType>, String> {
+async fn fetch_entity_types(state: State<'_, TState>) -> Result<Vec<EavEntityType>, EavError> {
     let dbi = state.db.lock().await;
     match dbi.fetch_entity_types().await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to fetch entity types: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn fetch_entities(state: State<'_, TState>, entity_type_id: u32, page: Option<u32>) -> Result<Vec<EavEntity>, String> {
+async fn fetch_entities(
+    state: State<'_, TState>, entity_type_id: u32, sort: Option<Vec<SortKey>>,
+    page: Option<u32>, per_page: Option<u32>, cursor: Option<String>
+) -> Result<Page<EavEntity>, EavError> {
     let dbi = state.db.lock().await;
-    let pg = page.unwrap_or(1);
-    match dbi.fetch_entities(entity_type_id, pg).await {
+    let pg = PageRequest::new(page, per_page, cursor);
+    let sort = sort.unwrap_or_default();
+    match dbi.fetch_entities(entity_type_id, &sort, &pg).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to fetch entities: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
@@ -58,26 +93,26 @@ async fn fetch_entities(state: State<'_, TState>, entity_type_id: u32, page: Opt
 #[tauri::command]
 async fn fetch_attrs(
     state: State<'_, TState>, entity_type_id: u32, multi_only: bool
-) -> Result<Vec<EavAttribute>, String> {
+) -> Result<Vec<EavAttribute>, EavError> {
     let dbi = state.db.lock().await;
     match dbi.fetch_attrs(entity_type_id, multi_only).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to fetch attributes: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn fetch_values(state: State<'_, TState>, entity_id: u32, page: Option<u32>) -> Result<Vec<EavView>, String> {
+async fn fetch_values(state: State<'_, TState>, entity_id: u32, page: Option<u32>) -> Result<Vec<EavView>, EavError> {
     let dbi = state.db.lock().await;
     let pg = page.unwrap_or(1);
     let mut views = match dbi.fetch_views_by_entity_id(entity_id, pg).await {
         Ok(v) => v,
         Err(e) => {
             println!("Failed to fetch views: {:?}", e);
-            return Err(e.to_string());
+            return Err(e);
         }
     };
     // append unfilled fields
@@ -85,14 +120,14 @@ async fn fetch_values(state: State<'_, TState>, entity_id: u32, page: Option<u32
         Ok(v) => v,
         Err(e) => {
             println!("Failed to fetch entity: {:?}", e);
-            return Err(e.to_string());
+            return Err(e);
         }
     };
     let attrs = match dbi.fetch_attrs(entity.entity_type_id, false).await {
         Ok(v) => v,
         Err(e) => {
             println!("Failed to fetch attrs: {:?}", e);
-            return Err(e.to_string());
+            return Err(e);
         }
     };
     for a in attrs {
@@ -116,207 +151,556 @@ async fn fetch_values(state: State<'_, TState>, entity_id: u32, page: Option<u32
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn create_entity_type(state: State<'_, TState>, entity_type: String) -> Result<EavEntityType, String> {
-    let dbi = state.db.lock().await;
+async fn create_entity_type(state: State<'_, TState>, entity_type: String) -> Result<EavEntityType, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.create_entity_type(&entity_type).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to create entity type: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn create_entity(state: State<'_, TState>, entity_type: String, entity: String) -> Result<EavEntity, String> {
-    let dbi = state.db.lock().await;
+async fn create_entity(state: State<'_, TState>, entity_type: String, entity: String) -> Result<EavEntity, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.create_entity(&entity_type, &entity).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to create entity: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
 async fn create_attr(
-    state: State<'_, TState>, entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool
-) -> Result<EavAttribute, String> {
-    let dbi = state.db.lock().await;
-    match dbi.create_attr(entity_type_id, attr_name, attr_type, allow_multiple).await {
+    state: State<'_, TState>, entity_type_id: u32, attr_name: &str, attr_type: &str, allow_multiple: bool,
+    ref_entity_type_id: Option<u32>, on_delete: Option<OnDelete>
+) -> Result<EavAttribute, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.create_attr(entity_type_id, attr_name, attr_type, allow_multiple, ref_entity_type_id, on_delete).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to create attr: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn create_value(state: State<'_, TState>, input: EavValue) -> Result<EavValue, String> {
-    let dbi = state.db.lock().await;
+async fn create_value(state: State<'_, TState>, input: EavValue) -> Result<EavValue, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.create_value(input).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to create value: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-async fn update_value(state: State<'_, TState>, input: EavValue) -> Result<EavValue, String> {
-    let dbi = state.db.lock().await;
+async fn update_value(state: State<'_, TState>, input: EavValue) -> Result<EavValue, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.update_value(input).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to update value: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn delete_entity_type(state: State<'_, TState>, id: u32) -> Result<String, String> {
-    let dbi = state.db.lock().await;
+async fn rename_entity_type(state: State<'_, TState>, id: u32, name: String) -> Result<EavEntityType, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.rename_entity_type(id, &name).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to rename entity type: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn rename_entity(state: State<'_, TState>, id: u32, name: String) -> Result<EavEntity, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.rename_entity(id, &name).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to rename entity: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn rename_attr(state: State<'_, TState>, id: u32, name: String) -> Result<EavAttribute, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.rename_attr(id, &name).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to rename attr: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// `keep` picks the value that stays when turning allow_multiple off, without it entities with several values fail the change
+#[tauri::command]
+async fn set_attr_allow_multiple(
+    state: State<'_, TState>, id: u32, allow_multiple: bool, keep: Option<KeepValue>
+) -> Result<EavAttribute, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.set_attr_allow_multiple(id, allow_multiple, keep).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to set allow_multiple: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// category a ref attr points into, None for any, and what deleting a referenced entity does
+#[tauri::command(rename_all = "snake_case")]
+async fn set_attr_ref(
+    state: State<'_, TState>, id: u32, ref_entity_type_id: Option<u32>, on_delete: OnDelete
+) -> Result<EavAttribute, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.set_attr_ref(id, ref_entity_type_id, on_delete).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to set attr ref: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// a dry run reports the values that don't convert without changing anything
+#[tauri::command]
+async fn convert_attr_type(
+    state: State<'_, TState>, id: u32, value_type: String, on_failure: ConvertPolicy, dry_run: bool
+) -> Result<ConvertReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.convert_attr_type(id, &value_type, on_failure, dry_run).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to convert attribute: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn delete_entity_type(state: State<'_, TState>, id: u32) -> Result<DeleteSummary, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.delete_entity_type(id).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to delete entity: {:?}", e);
-            Err(e.to_string())
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn clone_entity(state: State<'_, TState>, id: u32, options: CloneOptions) -> Result<EavEntity, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.clone_entity(id, &options).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to clone entity: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// source attrs missing from `mapping` go to the target attr with the same name
+#[tauri::command]
+async fn move_entity(
+    state: State<'_, TState>, id: u32, entity_type_id: u32, mapping: Vec<AttrMapping>, create_missing: bool, dry_run: bool
+) -> Result<MoveReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.move_entity(id, entity_type_id, &mapping, create_missing, dry_run).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to move entity: {:?}", e);
+            Err(e)
         }
     }
 }
 
+// single value attrs missing from `strategies` keep the target's value
 #[tauri::command]
-async fn delete_entity(state: State<'_, TState>, id: u32) -> Result<String, String> {
+async fn merge_entities(
+    state: State<'_, TState>, source_id: u32, target_id: u32, strategies: Vec<AttrStrategy>, dry_run: bool
+) -> Result<MergeReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.merge_entities(source_id, target_id, &strategies, dry_run).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to merge entities: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn find_duplicates(
+    state: State<'_, TState>, entity_type_id: u32, options: Option<DuplicateOptions>
+) -> Result<Vec<DuplicateGroup>, EavError> {
     let dbi = state.db.lock().await;
+    match dbi.find_duplicates(entity_type_id, &options.unwrap_or_default()).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to find duplicates: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn delete_entity(state: State<'_, TState>, id: u32) -> Result<DeleteSummary, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.delete_entity(id).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to delete entity: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn delete_attr(state: State<'_, TState>, id: u32) -> Result<String, String> {
-    let dbi = state.db.lock().await;
+async fn delete_attr(state: State<'_, TState>, id: u32) -> Result<DeleteSummary, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.delete_attr(id).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to delete attr: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn delete_value(state: State<'_, TState>, id: u32) -> Result<String, String> {
-    let dbi = state.db.lock().await;
+async fn delete_value(state: State<'_, TState>, id: u32) -> Result<DeleteSummary, EavError> {
+    let mut dbi = state.db.lock().await;
     match dbi.delete_value(id).await {
         Ok(v) => Ok(v),
         Err(e) => {
             println!("Failed to delete value: {:?}", e);
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
+// structured search, parse errors carry the character position in the query
 #[tauri::command]
-async fn search_entity(state: State<'_, TState>, regex: String, extended: bool, page: Option<u32>) -> Result<Vec<EavEntity>, String> {
+async fn search(
+    state: State<'_, TState>, query: String, sort: Option<Vec<SortKey>>,
+    page: Option<u32>, per_page: Option<u32>, cursor: Option<String>
+) -> Result<Page<EavEntity>, EavError> {
     let dbi = state.db.lock().await;
-    let pg = page.unwrap_or(1);
-    if extended {
-        return match dbi.search_entity_with_alt_title(regex, pg).await {
-            Ok(v) => Ok(v),
-            Err(e) => {
-                println!("Failed to fetch entities: {:?}", e);
-                Err(e.to_string())
-            }
+    let pg = PageRequest::new(page, per_page, cursor);
+    let sort = sort.unwrap_or_default();
+    match dbi.search(&query, &sort, &pg).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to search: {:?}", e);
+            Err(e)
         }
     }
-    match dbi.search_entity(regex, pg).await {
+}
+
+// entities of a type pivoted to one row each, `query` filters them with the search syntax
+#[tauri::command]
+async fn fetch_table(
+    state: State<'_, TState>, entity_type_id: u32, query: Option<String>, sort: Option<Vec<SortKey>>,
+    page: Option<u32>, per_page: Option<u32>, cursor: Option<String>
+) -> Result<EavTable, EavError> {
+    let dbi = state.db.lock().await;
+    let pg = PageRequest::new(page, per_page, cursor);
+    let sort = sort.unwrap_or_default();
+    match dbi.fetch_table(entity_type_id, query.as_deref(), &sort, &pg).await {
         Ok(v) => Ok(v),
         Err(e) => {
-            println!("Failed to fetch entities: {:?}", e);
-            Err(e.to_string())
+            println!("Failed to fetch table: {:?}", e);
+            Err(e)
         }
     }
 }
 
 #[tauri::command]
-async fn search_entity_with_attr_value(
-    state: State<'_, TState>, attr: String, val: String, page: Option<u32>
-) -> Result<Vec<EavEntity>, String> {
+async fn fetch_entity_history(
+    state: State<'_, TState>, entity_id: u32, page: Option<u32>, per_page: Option<u32>, cursor: Option<String>
+) -> Result<Page<EavHistory>, EavError> {
     let dbi = state.db.lock().await;
-    let pg = page.unwrap_or(1);
-    match dbi.search_entity_with_attr_value(attr, val, pg).await {
+    let pg = PageRequest::new(page, per_page, cursor);
+    match dbi.fetch_entity_history(entity_id, &pg).await {
         Ok(v) => Ok(v),
         Err(e) => {
-            println!("Failed to fetch entities: {:?}", e);
-            Err(e.to_string())
+            println!("Failed to fetch history: {:?}", e);
+            Err(e)
         }
     }
 }
 
+// ref values of other entities pointing at the entity
 #[tauri::command]
-async fn search_entity_without_attr(state: State<'_, TState>, attr: String, page: Option<u32>) -> Result<Vec<EavEntity>, String> {
+async fn fetch_backlinks(state: State<'_, TState>, entity_id: u32, page: Option<u32>) -> Result<Vec<EavView>, EavError> {
     let dbi = state.db.lock().await;
-    let pg = page.unwrap_or(1);
-    match dbi.search_entity_without_attr(attr, pg).await {
+    match dbi.fetch_backlinks(entity_id, page.unwrap_or(1)).await {
         Ok(v) => Ok(v),
         Err(e) => {
-            println!("Failed to fetch entities: {:?}", e);
-            Err(e.to_string())
+            println!("Failed to fetch backlinks: {:?}", e);
+            Err(e)
         }
     }
 }
 
+// values of the entity as they were at `at`
 #[tauri::command]
-async fn search_entity_with_attr_value_comparison(
-    state: State<'_, TState>, attr: String, val: String, op: String, page: Option<u32>
-) -> Result<Vec<EavEntity>, String> {
+async fn fetch_values_at(state: State<'_, TState>, entity_id: u32, at: DateTime<Utc>) -> Result<Vec<EavView>, EavError> {
     let dbi = state.db.lock().await;
-    let pg = page.unwrap_or(1);
-    let optr: Operator = match op.as_str() {
-        ">" => Operator::GREATER,
-        "<" => Operator::LESSER,
+    match dbi.fetch_views_at(entity_id, at).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to fetch values at {}: {:?}", at, e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn fetch_trash(
+    state: State<'_, TState>, page: Option<u32>, per_page: Option<u32>, cursor: Option<String>
+) -> Result<Page<TrashItem>, EavError> {
+    let dbi = state.db.lock().await;
+    let pg = PageRequest::new(page, per_page, cursor);
+    match dbi.fetch_trash(&pg).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to fetch trash: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn restore_from_trash(state: State<'_, TState>, kind: String, id: u32) -> Result<DeleteSummary, EavError> {
+    let mut dbi = state.db.lock().await;
+    let trash_kind = match kind.as_str() {
+        "entity_type" => TrashKind::EntityType,
+        "entity" => TrashKind::Entity,
+        "attr" => TrashKind::Attr,
+        "value" => TrashKind::Value,
         _ => {
-            return Err("ERR: OperatorNotValid".to_owned());
+            return Err(EavError::new(ErrorCode::InvalidArgument, "Unknown trash item kind").with_field("kind"));
         }
     };
-    match dbi.search_entity_with_attr_value_comparison(attr, val, optr, pg).await {
+    match dbi.restore_from_trash(trash_kind, id).await {
         Ok(v) => Ok(v),
         Err(e) => {
-            println!("Failed to fetch entities: {:?}", e);
-            Err(e.to_string())
+            println!("Failed to restore from trash: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn purge_trash(state: State<'_, TState>, max_age_days: Option<u32>) -> Result<DeleteSummary, EavError> {
+    let dbi = state.db.lock().await;
+    match dbi.purge_trash(max_age_days).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to purge trash: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// reverts the last data-modifying command, cascaded deletes come back with it
+#[tauri::command]
+async fn undo(state: State<'_, TState>) -> Result<UndoReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.undo().await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to undo: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn redo(state: State<'_, TState>) -> Result<UndoReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.redo().await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to redo: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// writes an entity type, or the entities matching `query`, to a csv or xlsx file
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn export_table(
+    state: State<'_, TState>, entity_type_id: Option<u32>, query: Option<String>, sort: Option<Vec<SortKey>>, path: String,
+    format: Option<String>, separator: Option<String>, units: Option<bool>, time_format: Option<String>
+) -> Result<ExportSummary, EavError> {
+    let format = ExportFormat::new(format.as_deref(), &path)?;
+    let options = ExportOptions::new(separator, units, time_format)?;
+    let sort = sort.unwrap_or_default();
+    let dbi = state.db.lock().await;
+    match dbi.export_table(entity_type_id, query.as_deref(), &sort, &path, format, options).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to export: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// reads the csv text and proposes which attribute each column fills
+#[tauri::command]
+async fn preview_csv(
+    state: State<'_, TState>, entity_type_id: u32, text: String, delimiter: Option<String>, separator: Option<String>
+) -> Result<CsvPreview, EavError> {
+    let options = CsvOptions::new(delimiter, separator)?;
+    let dbi = state.db.lock().await;
+    match dbi.preview_csv(entity_type_id, &text, &options).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to preview csv: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+// invoke arguments are flat, so the file options and the mapping all end up as parameters
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn import_csv(
+    state: State<'_, TState>, entity_type_id: u32, text: String, delimiter: Option<String>, separator: Option<String>,
+    entity_column: usize, columns: Vec<ColumnMapping>, dry_run: Option<bool>
+) -> Result<ImportReport, EavError> {
+    let options = CsvOptions::new(delimiter, separator)?;
+    let mut dbi = state.db.lock().await;
+    match dbi.import_csv(entity_type_id, &text, &options, entity_column, columns, dry_run.unwrap_or(false)).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to import csv: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn export_archive(state: State<'_, TState>, path: String) -> Result<ArchiveSummary, EavError> {
+    let dbi = state.db.lock().await;
+    match dbi.export_archive(&path).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to export archive: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn import_archive(state: State<'_, TState>, path: String, dry_run: Option<bool>) -> Result<ArchiveReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.import_archive(&path, dry_run.unwrap_or(false)).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to import archive: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn create_backup(state: State<'_, TState>) -> Result<BackupInfo, EavError> {
+    let dbi = state.db.lock().await;
+    match dbi.create_backup().await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to create backup: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn fetch_backups(state: State<'_, TState>) -> Result<Vec<BackupInfo>, EavError> {
+    let dbi = state.db.lock().await;
+    match dbi.fetch_backups().await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to fetch backups: {:?}", e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+async fn restore_backup(state: State<'_, TState>, name: String) -> Result<ArchiveReport, EavError> {
+    let mut dbi = state.db.lock().await;
+    match dbi.restore_backup(&name).await {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            println!("Failed to restore backup: {:?}", e);
+            Err(e)
         }
     }
 }
 
 fn main() {
-    // launch SQL server
-    let mut cmd = Command::new("C:\\Program Files\\MySQL\\MySQL Server 8.0\\bin\\mysqld.exe")
-        .arg("--console").spawn().expect("Command Err");
+    // launch SQL server (embedded sqlite does not need one)
+    let db_config = DbConfig::from_env();
+    let server = match db_config.backend {
+        DbBackend::MySql => match DbServer::start(ServerConfig::from_env(&db_config), &db_config.url) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                println!("Failed to launch database server: {:?}", e);
+                None
+            }
+        },
+        DbBackend::Sqlite => None
+    };
     // configure tauri
     tauri::Builder::default()
-        .manage(TState { db: Mutex::new(DBInterface::new()) })
+        .manage(TState { db: Mutex::new(DBInterface::new()), server: Mutex::new(server) })
         .invoke_handler(tauri::generate_handler![
             connect, fetch_entity_types, fetch_entities, fetch_attrs, fetch_values,
             create_entity_type, create_entity, create_attr, create_value, update_value, 
             delete_entity_type, delete_entity, delete_attr, delete_value, 
-            search_entity, search_entity_with_attr_value, search_entity_without_attr,
-            search_entity_with_attr_value_comparison,
+            rename_entity_type, rename_entity, rename_attr, set_attr_allow_multiple, set_attr_ref,
+            convert_attr_type, clone_entity, move_entity, merge_entities, find_duplicates,
+            search, fetch_table,
+            fetch_entity_history, fetch_values_at, fetch_backlinks,
+            fetch_trash, restore_from_trash, purge_trash,
+            undo, redo,
+            export_table, preview_csv, import_csv,
+            export_archive, import_archive, create_backup, fetch_backups, restore_backup,
         ])
         .build(tauri::generate_context!())
         .expect("Error building app")
-        .run(move |_app_handle, event| match event {
+        .run(move |app_handle, event| match event {
             RunEvent::ExitRequested { .. } => {
-                cmd.kill().expect("Failed to close SQL");
-                println!("Successfully closed SQL connection");
+                let state = app_handle.state::<TState>();
+                tauri::async_runtime::block_on(async {
+                    if let Some(server) = state.server.lock().await.as_mut() {
+                        server.shutdown().await;
+                    }
+                });
             }
             _ => ()
         });
@@ -6,7 +6,16 @@ import store from "./store";
 import EavTable from "./lib/eavTable";
 import MenuBar from "./lib/menuBar";
 import SearchBar from "./lib/searchBar";
+import ErrorBar from "./lib/errorBar";
 import Help from "./lib/helpModal";
+import Trash from "./lib/trashModal";
+import Import from "./lib/importModal";
+import Export from "./lib/exportModal";
+import Archive from "./lib/archiveModal";
+import Backups from "./lib/backupModal";
+import Move from "./lib/moveModal";
+import Merge from "./lib/mergeModal";
+import Duplicates from "./lib/duplicatesModal";
 
 function App() {
   
@@ -21,8 +30,17 @@ function App() {
       <div className="container-center">
         <MenuBar />
         <SearchBar />
+        <ErrorBar />
         <EavTable />
         <Help />
+        <Trash />
+        <Import />
+        <Export />
+        <Archive />
+        <Backups />
+        <Move />
+        <Duplicates />
+        <Merge />
       </div>
     </Provider>
   );
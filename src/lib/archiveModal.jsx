@@ -0,0 +1,95 @@
+import { useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+import { open, save } from "@tauri-apps/api/dialog";
+
+import {
+  exportArchive,
+  fetchEntities,
+  fetchEntityTypes,
+  importArchive,
+  toggleShowArchive,
+} from "../store/eav";
+
+const FILTERS = [{ name: "Archive", extensions: ["jsonl"] }];
+
+function Archive() {
+  const dispatch = useDispatch();
+  const showArchive = useSelector((state) => state.eav.showArchive);
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+  const [summary, setSummary] = useState(null);
+  // file picked for a restore, kept so a dry run can be followed by the real one
+  const [path, setPath] = useState(null);
+  const [report, setReport] = useState(null);
+
+  async function runExport() {
+    setSummary(null);
+    const target = await save({ defaultPath: "archive.jsonl", filters: FILTERS });
+    if (!target) return;
+    const res = await dispatch(exportArchive({ path: target }));
+    if (res.error) return;
+    setSummary({ ...res.payload, path: target });
+  }
+
+  async function pickFile() {
+    const picked = await open({ multiple: false, filters: FILTERS });
+    if (!picked) return;
+    setPath(picked);
+    setReport(null);
+  }
+
+  async function runImport(dryRun) {
+    const res = await dispatch(importArchive({ path, dryRun }));
+    if (res.error) return;
+    setReport(res.payload);
+    if (res.payload.committed) {
+      dispatch(fetchEntityTypes());
+      if (activeTab?.id) dispatch(fetchEntities({ id: activeTab.id, page: 1 }));
+    }
+  }
+
+  function closeModal() {
+    setSummary(null);
+    setPath(null);
+    setReport(null);
+    dispatch(toggleShowArchive());
+  }
+
+  if (showArchive) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Archive</h3>
+        <p>Saves every category, entry and value, including the trash, to one file.</p>
+        <button onClick={runExport}>Save Archive</button>
+        {summary && (
+          <p>
+            Saved {summary.entity_types} categories, {summary.entities} entities, {summary.attrs} attributes
+            and {summary.values} values to {summary.path}
+          </p>
+        )}
+        <h3>Restore</h3>
+        <p>Adds the content of an archive to this database. Names that are already taken are skipped.</p>
+        <button onClick={pickFile}>Choose File</button>
+        {path && <span> {path}</span>}
+        {report && (
+          <div className="import-report">
+            <p>
+              {report.committed ? "Restored" : "Ready to restore"}{" "}
+              {report.entity_types} categories, {report.entities} entities, {report.attrs} attributes,{" "}
+              {report.values} values{report.skipped > 0 ? ", " + report.skipped + " skipped" : ""}
+            </p>
+            {report.conflicts.map((c, i) => (
+              <div key={"conflict-" + i}>Line {c.line}: {c.message}</div>
+            ))}
+          </div>
+        )}
+        <br />
+        <button onClick={() => runImport(true)} disabled={!path}>Dry Run</button>
+        <button onClick={() => runImport(false)} disabled={!path || report?.committed}>Restore</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Archive;
@@ -0,0 +1,109 @@
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  createBackup,
+  fetchBackups,
+  fetchEntityTypes,
+  restoreBackup,
+  toggleShowBackups,
+} from "../store/eav";
+
+const REASONS = {
+  manual: "Manual",
+  start: "App start",
+  delete_entity_type: "Before deleting a category",
+  purge_trash: "Before emptying the trash",
+  restore_backup: "Before restoring a backup",
+};
+
+function formatSize(bytes) {
+  if (bytes < 1024) return bytes + " B";
+  if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(1) + " KB";
+  return (bytes / 1024 / 1024).toFixed(1) + " MB";
+}
+
+function Backups() {
+  const dispatch = useDispatch();
+  const showBackups = useSelector((state) => state.eav.showBackups);
+  const [backups, setBackups] = useState([]);
+  // backup waiting for the restore to be confirmed
+  const [pending, setPending] = useState(null);
+  const [report, setReport] = useState(null);
+
+  async function loadBackups() {
+    const res = await dispatch(fetchBackups());
+    if (!res.error) setBackups(res.payload);
+  }
+
+  useEffect(() => {
+    if (showBackups) loadBackups();
+    else {
+      setPending(null);
+      setReport(null);
+    }
+  }, [showBackups])
+
+  async function backUp() {
+    setReport(null);
+    const res = await dispatch(createBackup());
+    if (!res.error) loadBackups();
+  }
+
+  async function restore() {
+    const res = await dispatch(restoreBackup(pending));
+    setPending(null);
+    if (res.error) return;
+    setReport(res.payload);
+    dispatch(fetchEntityTypes());
+    loadBackups();
+  }
+
+  if (showBackups) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Backups</h3>
+        <button onClick={backUp}>Back Up Now</button>
+        {report && (
+          <p>
+            Restored {report.entity_types} categories, {report.entities} entities, {report.attrs} attributes
+            and {report.values} values
+          </p>
+        )}
+        {backups.length < 1 && <p>No backups yet</p>}
+        {backups.length > 0 && (
+          <table className="import-mapping">
+            <thead>
+              <tr><th>Taken</th><th>Why</th><th>Size</th><th></th></tr>
+            </thead>
+            <tbody>
+              {backups.map(b => (
+                <tr key={b.name}>
+                  <td>{new Date(b.created_at).toLocaleString()}</td>
+                  <td>{REASONS[b.reason] ?? b.reason}</td>
+                  <td>{formatSize(b.size)}</td>
+                  <td>
+                    {pending === b.name ? (
+                      <>
+                        Replace everything?
+                        <button onClick={restore}>Yes</button>
+                        <button onClick={() => setPending(null)}>No</button>
+                      </>
+                    ) : (
+                      <button onClick={() => setPending(b.name)}>Restore</button>
+                    )}
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        )}
+        <br />
+        <button onClick={() => dispatch(toggleShowBackups())}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Backups;
@@ -0,0 +1,102 @@
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  fetchAttrsOfType,
+  findDuplicates,
+  openMerge,
+  toggleShowDuplicates,
+} from "../store/eav";
+
+function Duplicates() {
+  const dispatch = useDispatch();
+  const showDuplicates = useSelector((state) => state.eav.showDuplicates);
+  const showMerge = useSelector((state) => state.eav.showMerge);
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+  const [attrs, setAttrs] = useState([]);
+  const [maxDistance, setMaxDistance] = useState(3);
+  const [minSimilarity, setMinSimilarity] = useState(0.8);
+  // names of the attributes that have to agree, where both entities have one
+  const [keyAttrs, setKeyAttrs] = useState([]);
+  const [groups, setGroups] = useState(null);
+
+  async function loadAttrs() {
+    const res = await dispatch(fetchAttrsOfType(activeTab.id));
+    if (!res.error) setAttrs(res.payload);
+  }
+
+  useEffect(() => {
+    if (showDuplicates && activeTab?.id) loadAttrs();
+    else {
+      setAttrs([]);
+      setKeyAttrs([]);
+      setGroups(null);
+    }
+  }, [showDuplicates])
+
+  // merged entities drop out of the groups
+  useEffect(() => {
+    if (showDuplicates && !showMerge && groups) search();
+  }, [showMerge])
+
+  async function search() {
+    const res = await dispatch(findDuplicates({
+      entityTypeId: activeTab.id,
+      options: { max_distance: Number(maxDistance), min_similarity: Number(minSimilarity), key_attrs: keyAttrs },
+    }));
+    if (!res.error) setGroups(res.payload);
+  }
+
+  function toggleKey(attr, checked) {
+    setKeyAttrs(checked ? [...keyAttrs, attr] : keyAttrs.filter(a => a !== attr));
+  }
+
+  function closeModal() {
+    dispatch(toggleShowDuplicates());
+  }
+
+  if (showDuplicates && activeTab?.id) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Duplicates in {activeTab.entity_type}</h3>
+        <label htmlFor="max_distance">Edits apart at most </label>
+        <input type="number" name="max_distance" min="0" value={maxDistance} onChange={e => setMaxDistance(e.target.value)} />
+        <label htmlFor="min_similarity"> Similarity at least </label>
+        <input type="number" name="min_similarity" min="0" max="1" step="0.05" value={minSimilarity}
+          onChange={e => setMinSimilarity(e.target.value)} />
+        {attrs.length > 0 && <p>Have to agree on</p>}
+        {attrs.map(a => (
+          <div key={"key-" + a.id} style={{ display: "flex", alignItems: "center" }}>
+            <label htmlFor={"key-" + a.id}>{a.attr}</label>
+            <input type="checkbox" name={"key-" + a.id} checked={keyAttrs.includes(a.attr)}
+              onChange={e => toggleKey(a.attr, e.target.checked)} />
+          </div>
+        ))}
+        <br />
+        <button onClick={search}>Search</button>
+        {groups && groups.length < 1 && <p>No duplicates found</p>}
+        {groups && groups.map(g => {
+          const [target, ...others] = g.entities;
+          return (
+            <div className="import-report" key={"group-" + target.id}>
+              <p>{Math.round(g.score * 100)}% alike: {g.entities.map(e => e.entity).join(", ")}</p>
+              {g.pairs.map(p => (
+                <div key={"pair-" + p.a + "-" + p.b}>{p.reasons.join(", ")}</div>
+              ))}
+              {others.map(e => (
+                <button key={"merge-" + e.id} onClick={() => dispatch(openMerge({ source: e, target }))}>
+                  Merge {e.entity} into {target.entity}
+                </button>
+              ))}
+            </div>
+          )
+        })}
+        <br />
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Duplicates;
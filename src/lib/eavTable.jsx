@@ -3,14 +3,16 @@ import { useSelector } from 'react-redux';
 import EntityContainer from "./entityContainer";
 import EntityTypeTabs from "./entityTypeTabs";
 import FormModal from "./formModal";
+import TableView from "./tableView";
 
 function EavTable() {
   const loading = useSelector((state) => state.eav.loading);
+  const showTable = useSelector((state) => state.eav.showTable);
 
   return (
     <div className="eav-table">
       <EntityTypeTabs />
-      <EntityContainer />
+      {showTable ? <TableView /> : <EntityContainer />}
       <FormModal />
       {loading && (
         <div className="loading-overlay">
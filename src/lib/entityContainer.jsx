@@ -9,13 +9,24 @@ import {
   fnsWithPaginationEnum,
   openForm,
   resetScrollToTop,
-  searchAttrValue,
-  searchAttrValueComparison,
-  searchEntity,
+  scrollToTop,
+  search,
   setActiveEntity,
   setFormInput,
+  setSort,
+  toggleShowMerge,
+  toggleShowMove,
 } from "../store/eav";
 
+// sort options, attribute options are added for the active entity type
+const SORT_OPTIONS = {
+  default: [],
+  name: [{ by: "entity", desc: false }],
+  nameDesc: [{ by: "entity", desc: true }],
+  newest: [{ by: "created_at", desc: true }],
+  oldest: [{ by: "created_at", desc: false }],
+};
+
 const EntityContainer = () => {
   const dispatch = useDispatch();
   const entities = useSelector((state) => state.eav.entities);
@@ -24,6 +35,8 @@ const EntityContainer = () => {
   const activeEntity = useSelector((state) => state.eav.activeEntity);
   const showDelete = useSelector((state) => state.eav.showDelete);
   const resetScroll = useSelector((state) => state.eav.resetScroll);
+  const attrs = useSelector((state) => state.eav.attrs);
+  const sort = useSelector((state) => state.eav.sort);
   const scrollRef = useRef(null);
 
   function fetchData(id) {
@@ -44,6 +57,16 @@ const EntityContainer = () => {
     }
   }
 
+  function openRename(entity) {
+    dispatch(setFormInput(entity));
+    dispatch(openForm("renameEntity"));
+  }
+
+  function openClone(entity) {
+    dispatch(setFormInput(entity));
+    dispatch(openForm("cloneEntity"));
+  }
+
   function displayNoEntry() {
     if (entities.length < 1) return true;
     if (entities.length === 1) {
@@ -67,14 +90,8 @@ const EntityContainer = () => {
       case fnsWithPaginationEnum.fetchEntities:
         dispatch(fetchEntities({ id: meta.id, page: meta.page + 1 }));
         break;
-      case fnsWithPaginationEnum.searchEntity:
-        dispatch(searchEntity({ regex: meta.regex, extended: meta.extended, page: meta.page + 1 }));
-        break;
-      case fnsWithPaginationEnum.searchAttrValue:
-        dispatch(searchAttrValue({ attr: meta.attr, val: meta.val, page: meta.page + 1 }));
-        break;
-      case fnsWithPaginationEnum.searchAttrValueComparison:
-        dispatch(searchAttrValueComparison({ attr: meta.attr, val: meta.val, op: meta.op, page: meta.page + 1 }));
+      case fnsWithPaginationEnum.search:
+        dispatch(search({ query: meta.query, page: meta.page + 1 }));
         break;
       default:
         console.log("No fn found in meta", meta);
@@ -82,6 +99,27 @@ const EntityContainer = () => {
     }
   }
 
+  function sortValue() {
+    const [key] = sort;
+    if (!key) return "default";
+    if (key.by?.attr) return (key.desc ? "attrDesc:" : "attr:") + key.by.attr;
+    const [name] = Object.entries(SORT_OPTIONS).find(([, v]) => v[0]?.by === key.by && v[0]?.desc === key.desc) ?? [];
+    return name ?? "default";
+  }
+
+  function changeSort(e) {
+    const value = e.target.value;
+    let spec = SORT_OPTIONS[value] ?? [];
+    if (value.startsWith("attr:")) spec = [{ by: { attr: value.slice(5) }, desc: false }];
+    if (value.startsWith("attrDesc:")) spec = [{ by: { attr: value.slice(9) }, desc: true }];
+    dispatch(setSort(spec));
+    // reload the current list from the first page
+    const meta = store.getState()?.eav?.entityMeta;
+    dispatch(scrollToTop());
+    if (meta?.fn === fnsWithPaginationEnum.fetchEntities) dispatch(fetchEntities({ id: meta.id, page: 1 }));
+    if (meta?.fn === fnsWithPaginationEnum.search) dispatch(search({ query: meta.query, page: 1 }));
+  }
+
   function onScroll(e) {
     const dist = e.target.scrollTop;
     const fullH = e.target.scrollHeight - e.target.clientHeight;
@@ -112,6 +150,17 @@ const EntityContainer = () => {
       <div className="btn-ctn">
         <button onClick={() => dispatch(openForm("entity"))}>+ entity</button>
         <button onClick={() => dispatch(openForm("attr"))}>+ attribute</button>
+        <select value={sortValue()} onChange={changeSort}>
+          <option value="default">Sort: default</option>
+          <option value="name">Name A-Z</option>
+          <option value="nameDesc">Name Z-A</option>
+          <option value="newest">Newest first</option>
+          <option value="oldest">Oldest first</option>
+          {activeTab && attrs.map(a => [
+            <option key={"asc-" + a.id} value={"attr:" + a.attr}>{a.attr} ascending</option>,
+            <option key={"desc-" + a.id} value={"attrDesc:" + a.attr}>{a.attr} descending</option>,
+          ])}
+        </select>
       </div>
       {displayNoEntry() ? (
         <div className="eav-entry">
@@ -128,6 +177,14 @@ const EntityContainer = () => {
               <button onClick={() => fetchData(e.id)}>
                 Details
               </button>
+              {activeEntity?.id === e.id && (
+                <>
+                  <button onClick={() => openRename(e)}>Rename</button>
+                  <button onClick={() => openClone(e)}>Clone</button>
+                  <button onClick={() => dispatch(toggleShowMove())}>Move</button>
+                  <button onClick={() => dispatch(toggleShowMerge())}>Merge</button>
+                </>
+              )}
               {showDelete && (
                 <button onClick={() => confirmDeleteEntity(e.id)} className="square">
                   X
@@ -1,8 +1,22 @@
-import { useSelector } from "react-redux";
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
 import ValueRow from "./valueRow";
+import { fetchBacklinks } from "../store/eav";
 
 const EntityData = () => {
+  const dispatch = useDispatch();
   const valuesData = useSelector((state) => state.eav.values);
+  const activeEntity = useSelector((state) => state.eav.activeEntity);
+  // values of other entities referring to this one
+  const [backlinks, setBacklinks] = useState([]);
+
+  useEffect(() => {
+    setBacklinks([]);
+    if (!activeEntity?.id) return;
+    dispatch(fetchBacklinks(activeEntity.id)).then(res => {
+      if (!res.error) setBacklinks(res.payload);
+    });
+  }, [activeEntity?.id])
 
   if (valuesData.length < 1) return (
     <div className="value-container">No attributes found</div>
@@ -15,6 +29,16 @@ const EntityData = () => {
           <ValueRow key={key} data={v} />
         )
       })}
+      {backlinks.length > 0 && (
+        <div className="grid" style={{ textAlign: "left" }}>
+          <div>Referenced by</div>
+          <div>
+            {backlinks.map(b => (
+              <div key={"backlink-" + b.value_id}>{b.entity} ({b.entity_type}, {b.attr})</div>
+            ))}
+          </div>
+        </div>
+      )}
     </div>
   )
 }
@@ -4,6 +4,7 @@ import { useDispatch, useSelector } from "react-redux";
 import {
   clearValues,
   connect,
+  fetchAttrs,
   fetchEntities,
   fetchEntityTypes,
   setActiveEnType,
@@ -24,6 +25,7 @@ const EntityTypeTabs = () => {
     dispatch(clearValues());
     dispatch(scrollToTop());
     dispatch(fetchEntities({ id }));
+    dispatch(fetchAttrs(id));
     dispatch(setActiveEnType(id));
   }
 
@@ -0,0 +1,21 @@
+import { useDispatch, useSelector } from "react-redux";
+
+import { clearError } from "../store/eav";
+
+function ErrorBar() {
+  const dispatch = useDispatch();
+  const error = useSelector((state) => state.eav.error);
+
+  if (!error) return null;
+  return (
+    <div className="error-bar">
+      <span>
+        {error.message}
+        {!!error.field && <i> ({error.field})</i>}
+      </span>
+      <button onClick={() => dispatch(clearError())}>x</button>
+    </div>
+  )
+}
+
+export default ErrorBar;
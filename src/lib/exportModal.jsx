@@ -0,0 +1,81 @@
+import { useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+import { save } from "@tauri-apps/api/dialog";
+
+import {
+  exportTable,
+  fnsWithPaginationEnum,
+  toggleShowExport,
+} from "../store/eav";
+
+function Export() {
+  const dispatch = useDispatch();
+  const showExport = useSelector((state) => state.eav.showExport);
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+  const meta = useSelector((state) => state.eav.entityMeta);
+  const [format, setFormat] = useState("csv");
+  const [separator, setSeparator] = useState("; ");
+  const [units, setUnits] = useState(true);
+  // strftime pattern, empty keeps full dates
+  const [timeFormat, setTimeFormat] = useState("");
+  const [summary, setSummary] = useState(null);
+
+  const searching = !activeTab && meta?.fn === fnsWithPaginationEnum.search && !!meta.query;
+  const title = activeTab ? activeTab.entity_type : searching ? "search results" : null;
+
+  async function runExport() {
+    setSummary(null);
+    const path = await save({
+      defaultPath: (activeTab?.entity_type ?? "search") + "." + format,
+      filters: [{ name: format.toUpperCase(), extensions: [format] }],
+    });
+    if (!path) return;
+    const res = await dispatch(exportTable({ path, format, separator, units, timeFormat }));
+    if (res.error) return;
+    setSummary(res.payload);
+  }
+
+  function closeModal() {
+    setSummary(null);
+    dispatch(toggleShowExport());
+  }
+
+  if (showExport) return (
+    <div className="modal-container">
+      <div className="help-body">
+        <h3>Export {title ?? ""}</h3>
+        {!title && <p>Open a category or search for entries to export them.</p>}
+        {!!title && (
+          <div className="export-options">
+            <label>
+              Format
+              <select value={format} onChange={e => setFormat(e.target.value)}>
+                <option value="csv">CSV</option>
+                <option value="xlsx">Excel (XLSX)</option>
+              </select>
+            </label>
+            <label>
+              Join multiple values with
+              <input value={separator} onChange={e => setSeparator(e.target.value)} />
+            </label>
+            <label>
+              <input type="checkbox" checked={units} onChange={e => setUnits(e.target.checked)} />
+              Add units to numbers
+            </label>
+            <label>
+              Date format
+              <input value={timeFormat} placeholder="%Y-%m-%d" onChange={e => setTimeFormat(e.target.value)} />
+            </label>
+          </div>
+        )}
+        {summary && <p>Saved {summary.rows} rows to {summary.path}</p>}
+        <br />
+        <button onClick={runExport} disabled={!title}>Export</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Export;
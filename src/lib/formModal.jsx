@@ -8,7 +8,16 @@ import {
   closeForm,
   deleteEntity,
   deleteEntityType,
+  cloneEntity,
+  convertAttrType,
   deleteValue,
+  fetchAttrs,
+  fetchValues,
+  renameAttr,
+  renameEntity,
+  renameEntityType,
+  setAttrAllowMultiple,
+  setAttrRef,
   setFormInput,
 } from "../store/eav";
 
@@ -17,17 +26,59 @@ const FormModal = () => {
   const formType = useSelector((state) => state.eav.formType);
   const formInput = useSelector((state) => state.eav.formInput);
   const activeEnType = useSelector((state) => state.eav.activeEnType);
+  const activeEntity = useSelector((state) => state.eav.activeEntity);
+  const attrs = useSelector((state) => state.eav.attrs);
+  const entityTypes = useSelector((state) => state.eav.entityTypes);
   const [fields, setFields] = useState({});
   const [title, setTitle] = useState("");
   const [err, setErr] = useState("");
+  // dry run of a value type change
+  const [conversion, setConversion] = useState(null);
 
   function close() {
     setFields({});
     setTitle("");
     setErr("");
+    setConversion(null);
     dispatch(closeForm());
   }
 
+  // renames and allow_multiple are separate commands, only what changed is sent
+  async function submitAttrEdit(form) {
+    const attr = attrs.find(a => a.id === Number(form.id));
+    if (!attr) return setErr("No attribute selected");
+    if (!form.attr) return setErr("No name specified");
+    let regex = new RegExp(/^[-_0-9a-z]+$/i);
+    if (!regex.test(form.attr)) return setErr("Attribute name only accepts a-z, 0-9, -, _");
+    // converting first, an aborted conversion leaves the other settings alone too
+    if (form.value_type !== attr.value_type) {
+      const res = await dispatch(convertAttrType({
+        id: attr.id, valueType: form.value_type, onFailure: form.on_failure, dryRun: false
+      }));
+      if (res.error) return setErr(res.payload?.message ?? "Conversion failed");
+      if (!res.payload.committed) {
+        setConversion(res.payload);
+        return setErr("Nothing was converted");
+      }
+      dispatch(fetchAttrs(attr.entity_type_id));
+    }
+    if (form.attr !== attr.attr) {
+      const res = await dispatch(renameAttr({ id: attr.id, name: form.attr }));
+      if (res.error) return setErr(res.payload?.message ?? "Rename failed");
+    }
+    if (form.allow_multiple !== !!attr.allow_multiple) {
+      const res = await dispatch(setAttrAllowMultiple({ id: attr.id, allowMultiple: form.allow_multiple, keep: form.keep }));
+      if (res.error) return setErr(res.payload?.message ?? "Update failed");
+    }
+    const refEntityTypeId = Number(form.ref_entity_type_id) || null;
+    if (attr.value_type === "ref" && (refEntityTypeId !== (attr.ref_entity_type_id ?? null) || form.on_delete !== attr.on_delete)) {
+      const res = await dispatch(setAttrRef({ id: attr.id, refEntityTypeId, onDelete: form.on_delete }));
+      if (res.error) return setErr(res.payload?.message ?? "Update failed");
+    }
+    if (activeEntity) dispatch(fetchValues(activeEntity.id));
+    close();
+  }
+
   function handleSubmit(e) {
     e.preventDefault();
     if (formType === "delEntityType") {
@@ -49,6 +100,33 @@ const FormModal = () => {
       return;
     }
     const form = { ...fields };
+    if (formType === "renameEntityType") {
+      if (!form.name) return setErr("No name specified");
+      dispatch(renameEntityType({ id: formInput.id, name: form.name }));
+      close();
+      return;
+    }
+    if (formType === "renameEntity") {
+      if (!form.name) return setErr("No name specified");
+      dispatch(renameEntity({ id: formInput.id, name: form.name }));
+      close();
+      return;
+    }
+    if (formType === "editAttr") {
+      submitAttrEdit(form);
+      return;
+    }
+    if (formType === "cloneEntity") {
+      const options = {
+        name: form.name.trim() || null,
+        // null copies every attribute, also when those of the entity's category aren't loaded
+        attrs: cloneAttrs().length > 0 ? cloneAttrs().filter(a => form.copy[a.id]).map(a => a.id) : null,
+        increment: cloneAttrs().filter(a => form.copy[a.id] && form.increment[a.id]).map(a => a.id),
+      };
+      dispatch(cloneEntity({ id: formInput.id, options }));
+      close();
+      return;
+    }
     if (formType === "entityType") {
       if (!form.entity_type) return setErr("No name specified");
       dispatch(addEntityType(form));
@@ -77,6 +155,38 @@ const FormModal = () => {
     setErr("");
   }
 
+  // picking an attribute to edit fills in its current settings
+  function selectAttr(e) {
+    const attr = attrs.find(a => a.id === Number(e.target.value));
+    setFields(attr ? {
+      id: attr.id, attr: attr.attr, allow_multiple: !!attr.allow_multiple, keep: "",
+      value_type: attr.value_type, on_failure: "abort",
+      ref_entity_type_id: attr.ref_entity_type_id ?? "", on_delete: attr.on_delete ?? "block"
+    } : {});
+    setErr("");
+    setConversion(null);
+  }
+
+  function handleConversionInput(e) {
+    handleInput(e);
+    setConversion(null);
+  }
+
+  async function checkConversion() {
+    const res = await dispatch(convertAttrType({
+      id: fields.id, valueType: fields.value_type, onFailure: fields.on_failure, dryRun: true
+    }));
+    if (!res.error) setConversion(res.payload);
+  }
+
+  function cloneAttrs() {
+    return attrs.filter(a => a.entity_type_id === formInput?.entity_type_id);
+  }
+
+  function toggleCloneOption(option, id) {
+    setFields(prev => ({ ...prev, [option]: { ...prev[option], [id]: !prev[option]?.[id] } }));
+  }
+
   function handleCheckInput(e) {
     const { name, checked } = e.target;
     setFields(prev => ({ ...prev, [name]: checked }));
@@ -97,6 +207,26 @@ const FormModal = () => {
         setTitle("New Attribute");
         setFields({ attr: "", value_type: "", allow_multiple: false });
         break;
+      case "renameEntityType":
+        setTitle("Rename Category");
+        setFields({ name: formInput?.entity_type ?? "" });
+        break;
+      case "renameEntity":
+        setTitle("Rename Entity");
+        setFields({ name: formInput?.entity ?? "" });
+        break;
+      case "cloneEntity":
+        setTitle("Clone " + (formInput?.entity ?? "Entity"));
+        setFields({
+          name: "",
+          copy: Object.fromEntries(cloneAttrs().map(a => [a.id, true])),
+          increment: {},
+        });
+        break;
+      case "editAttr":
+        setTitle("Edit Attribute");
+        setFields({});
+        break;
       case "delEntity":
         setTitle("Delete Entity?");
         break;
@@ -130,6 +260,118 @@ const FormModal = () => {
     )
   }
 
+  function renderRenameFields() {
+    return (
+      <>
+        <label htmlFor="name">Name</label>
+        <input type="text" name="name" value={fields.name ?? ""} onChange={handleInput} />
+      </>
+    )
+  }
+
+  function renderCloneFields() {
+    return (
+      <>
+        <label htmlFor="name">Name</label>
+        <input type="text" name="name" placeholder="numbered after the original" value={fields.name ?? ""} onChange={handleInput} />
+        {cloneAttrs().map(a => (
+          <div key={"clone-" + a.id} style={{ display: "flex", alignItems: "center" }}>
+            <label>{a.attr}</label>
+            <input type="checkbox" checked={!!fields.copy?.[a.id]} onChange={() => toggleCloneOption("copy", a.id)} />
+            {["int", "float"].includes(a.value_type) && (
+              <>
+                <label>+1</label>
+                <input type="checkbox" checked={!!fields.increment?.[a.id]} disabled={!fields.copy?.[a.id]}
+                  onChange={() => toggleCloneOption("increment", a.id)} />
+              </>
+            )}
+          </div>
+        ))}
+      </>
+    )
+  }
+
+  // target category and delete rule of entity reference attributes
+  function renderRefFields() {
+    return (
+      <>
+        <label htmlFor="ref_entity_type_id">Refers To</label>
+        <select name="ref_entity_type_id" value={fields.ref_entity_type_id ?? ""} onChange={handleInput}>
+          <option value="">any category</option>
+          {entityTypes.map(et => <option key={"ref-type-" + et.id} value={et.id}>{et.entity_type}</option>)}
+        </select>
+        <label htmlFor="on_delete">Deleting a referenced entity</label>
+        <select name="on_delete" value={fields.on_delete ?? "block"} onChange={handleInput}>
+          <option value="block">is refused</option>
+          <option value="nullify">empties the references</option>
+          <option value="cascade">deletes the referencing entities too</option>
+        </select>
+      </>
+    )
+  }
+
+  function renderEditAttrFields() {
+    const attr = attrs.find(a => a.id === fields.id);
+    return (
+      <>
+        <label htmlFor="id">Attribute</label>
+        <select name="id" value={fields.id ?? ""} onChange={selectAttr}>
+          <option value=""></option>
+          {attrs.map(a => <option key={"attr-" + a.id} value={a.id}>{a.attr}</option>)}
+        </select>
+        {!!attr && (
+          <>
+            <label htmlFor="attr">Name</label>
+            <input type="text" name="attr" value={fields.attr} onChange={handleInput} />
+            <label htmlFor="value_type">Value Type</label>
+            <select name="value_type" value={fields.value_type} onChange={handleConversionInput} disabled={attr.value_type === "ref"}>
+              <option value="str">String</option>
+              <option value="int">Integer</option>
+              <option value="float">Float</option>
+              <option value="time">Time</option>
+              <option value="bool">Boolean</option>
+              {attr.value_type === "ref" && <option value="ref">Entity Reference</option>}
+            </select>
+            {attr.value_type === "ref" && renderRefFields()}
+            {fields.value_type !== attr.value_type && (
+              <>
+                <label htmlFor="on_failure">Values that don't convert</label>
+                <select name="on_failure" value={fields.on_failure} onChange={handleConversionInput}>
+                  <option value="abort">cancel the conversion</option>
+                  <option value="null">are emptied</option>
+                  <option value="trash">go to the trash</option>
+                </select>
+                <button type="button" onClick={checkConversion}>Check Values</button>
+                {conversion && (
+                  <div className="import-report">
+                    <p>{conversion.converted} values convert, {conversion.failures.length} don't</p>
+                    {conversion.failures.map(f => (
+                      <div key={"failure-" + f.value_id}>{f.entity} ({f.value}{f.in_trash ? ", in the trash" : ""}): {f.message}</div>
+                    ))}
+                  </div>
+                )}
+              </>
+            )}
+            <div style={{ display: "flex", alignItems: "center" }}>
+              <label htmlFor="allow_multiple">Allow Multiple</label>
+              <input type="checkbox" name="allow_multiple" checked={fields.allow_multiple} onChange={handleCheckInput} />
+            </div>
+            {!!attr.allow_multiple && !fields.allow_multiple && (
+              <>
+                <label htmlFor="keep">Entities with several values keep</label>
+                <select name="keep" value={fields.keep} onChange={handleInput}>
+                  <option value="">nothing, cancel if there are any</option>
+                  <option value="oldest">the oldest value</option>
+                  <option value="newest">the newest value</option>
+                </select>
+              </>
+            )}
+          </>
+        )}
+      </>
+    )
+  }
+
   function renderAttrFields() {
     return (
       <>
@@ -143,7 +385,9 @@ const FormModal = () => {
           <option value="float">Float</option>
           <option value="time">Time</option>
           <option value="bool">Boolean</option>
+          <option value="ref">Entity Reference</option>
         </select>
+        {fields.value_type === "ref" && renderRefFields()}
         <div style={{ display: "flex", alignItems: "center" }}>
           <label htmlFor="allow_multiple">Allow Multiple</label>
           <input type="checkbox" name="allow_multiple" onChange={handleCheckInput} />
@@ -160,10 +404,15 @@ const FormModal = () => {
         {formType === "entityType" && renderEntityTypeFields()}
         {formType === "entity" && renderEntityFields()}
         {formType === "attr" && renderAttrFields()}
+        {["renameEntityType", "renameEntity"].includes(formType) && renderRenameFields()}
+        {formType === "editAttr" && renderEditAttrFields()}
+        {formType === "cloneEntity" && renderCloneFields()}
         <div className="btn-ctn">
           <button onClick={close}>Close</button>
           <button type="submit">
-            {["delEntityType","delEntity","delValue"].includes(formType) ? "Confirm" : "Add"}
+            {["delEntityType","delEntity","delValue"].includes(formType) ? "Confirm"
+              : ["renameEntityType","renameEntity","editAttr"].includes(formType) ? "Save"
+              : formType === "cloneEntity" ? "Clone" : "Add"}
           </button>
         </div>
         {!!err && (
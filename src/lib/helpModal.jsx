@@ -15,22 +15,114 @@ function Help() {
       <div className="help-body">
         <h3>Using search</h3>
         <p>
-          Search any valid regex statement for entity names.
-          By default, any attributes named "alt_name" will also be queried.
-          If only the original entity name should be included in the results,
-          add a ! to the start of the query. This will also disable complex
-          regex matching.
+          Words are matched as regex against entity names and any "alt_title" 
+          attributes. Text in "double quotes" is matched exactly as written.
         </p>
         <p>
-          Attribute values can be searched with "attr: value", and numerical 
-          attributes can be compared with "attr {">"} value" or "attr {"<"} value".
+          Attribute values can be searched with "attr:value", or "attr=value" for
+          an exact match, and numbers and dates can be compared with 
+          "attr{">"}n", "attr{">="}n", "attr{"<"}n", "attr{"<="}n" and "attr!=n", or 
+          limited to a range with "attr:1900..1950". Use "has:attr" for entities 
+          with a value for an attribute, "type:name" for a category and 
+          "created:{">"}2024-01-31" for the creation date.
+        </p>
+        <p>
+          Dates can be written in full (2024-05-31), as a month (2024-05) or a year 
+          (2024), which covers the whole month or year, or relative to now as 
+          -12h, -30d, -2w, -6m or -1y, as in "created:{">"}-30d".
+        </p>
+        <p>
+          Terms can be combined with AND, OR, NOT and parentheses. Terms next to 
+          each other must all match, and a leading - excludes a term, as in "-has:isbn".
+        </p>
+        <p>
+          Entries and search results can be sorted by name, creation date or the
+          values of an attribute of the open category. Numbers and dates are sorted 
+          by value, and entries without a value are always listed last.
+        </p>
+        <h3>Importing</h3>
+        <p>
+          Import CSV in the edit menu adds one entity per line of a CSV file to the 
+          open category. The first line names the columns, which are matched to 
+          attributes with the same name, and new attributes are created for the 
+          rest with a guessed type. A dry run checks every line without saving 
+          anything, and an import with errors is not saved either.
+        </p>
+        <p>
+          Rename Category and Edit Attribute in the edit menu, and the rename button 
+          next to an open entity, change names in place. An attribute can also stop 
+          allowing multiple values, either keeping the oldest or newest value of 
+          each entity and moving the others to the trash, or not at all while any 
+          entity still has more than one.
+        </p>
+        <p>
+          Edit Attribute can also change the value type. Text is read as numbers or 
+          dates, units like "12 kg" are kept, and check values lists whatever 
+          doesn't fit before anything changes. Values in the trash are converted 
+          too. Values that don't convert can cancel the whole conversion, be 
+          emptied, or go to the trash as they were.
+        </p>
+        <p>
+          The clone button next to an open entity copies it with the attributes 
+          picked. A name ending in a number counts up, so Berserk 3 becomes 
+          Berserk 4, and numbers marked +1 like a volume go up by one in the copy.
+        </p>
+        <p>
+          The move button next to an open entity puts it in another category. Its 
+          values go to the attributes with the same name unless another one is 
+          picked, missing attributes can be created on the way, and a dry run lists 
+          the values that would be left behind in the trash.
+        </p>
+        <p>
+          The merge button folds an open entity into another one of the same 
+          category and moves it to the trash. Values the other one lacks are added, 
+          and where both have a different value for an attribute that takes only 
+          one, a dry run lists them so either side or the newest can be kept.
+        </p>
+        <p>
+          An attribute of the Entity Reference type points at another entity, 
+          optionally only those of one category, and shows its current name. 
+          Deleting an entity that is referenced is refused, empties the references, 
+          or deletes the referencing entities as well, as picked for the attribute. 
+          An open entity lists the entities that refer to it, and merging one moves 
+          its references over to the entity it is merged into.
+        </p>
+        <p>
+          Find duplicates in the edit menu groups entities whose names, or alt_title 
+          values, are the same once case, accents, punctuation and articles like 
+          "The" are left out, or only a few letters apart. Attributes picked to agree 
+          keep entities apart where both have a different value, and each group 
+          offers to merge the others into its oldest entity.
+        </p>
+        <p>
+          Export in the file menu saves the open category, or the results of the 
+          last search, as a CSV or Excel file with one row per entry and one 
+          column per attribute.
+        </p>
+        <p>
+          Archive in the file menu saves the whole database, trash included, to a 
+          single file that can be restored into this or another database. Entries 
+          whose name is already taken are skipped and listed, everything else is 
+          added to the existing categories.
+        </p>
+        <p>
+          Backups in the file menu lists the snapshots taken when the app starts and 
+          before a category is deleted, the trash is emptied or a backup is restored. 
+          Restoring a backup replaces everything with its content, and the changes 
+          made since then can't be undone afterwards.
         </p>
         <h3>Deleting entries</h3>
         <p>
           Delete buttons are appended to values, entities, and category tabs by
           enabling deletion through the file menu. Deleting categories will also 
-          delete all associated values, attributes, and entities. Deletions are 
-          <b>final</b> and cannot be undone.
+          delete all associated values, attributes, and entities. Deleted entries 
+          are moved to the trash (file menu) where they can be restored, and are 
+          removed <b>permanently</b> after 30 days or when the trash is emptied.
+        </p>
+        <p>
+          Undo and redo in the file menu step back and forth through the changes 
+          made since the app was opened, including everything a deletion removed. 
+          Entries that have been removed from the trash cannot be brought back.
         </p>
         <br />
         <button onClick={closeModal}>Close</button>
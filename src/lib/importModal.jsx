@@ -0,0 +1,163 @@
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  fetchAttrs,
+  fetchEntities,
+  importCsv,
+  previewCsv,
+  toggleShowImport,
+} from "../store/eav";
+
+const VALUE_TYPES = ["str", "int", "float", "time", "bool"];
+
+function Import() {
+  const dispatch = useDispatch();
+  const showImport = useSelector((state) => state.eav.showImport);
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+  const attrs = useSelector((state) => state.eav.attrs);
+  const [text, setText] = useState("");
+  const [separator, setSeparator] = useState(";");
+  const [preview, setPreview] = useState(null);
+  const [entityColumn, setEntityColumn] = useState(0);
+  // one entry per csv column, columns without an attr name are skipped
+  const [mapping, setMapping] = useState([]);
+  const [report, setReport] = useState(null);
+
+  useEffect(() => {
+    if (!showImport) {
+      setText("");
+      setPreview(null);
+      setReport(null);
+    }
+  }, [showImport])
+
+  async function loadPreview(content) {
+    setReport(null);
+    const res = await dispatch(previewCsv({ id: activeTab.id, text: content, separator }));
+    if (res.error) return;
+    const p = res.payload;
+    setPreview(p);
+    setEntityColumn(p.entity_column);
+    setMapping(p.headers.map((_, i) =>
+      p.columns.find(c => c.column === i) ?? { column: i, attr: "", value_type: "str", allow_multiple: false, attr_id: null }
+    ));
+  }
+
+  async function openFile(e) {
+    const file = e.target.files?.[0];
+    if (!file) return;
+    const content = await file.text();
+    setText(content);
+    loadPreview(content);
+  }
+
+  // attributes that exist keep their type, typing another name makes it a new attribute
+  function changeColumn(i, change) {
+    setMapping(mapping.map((m, j) => {
+      if (j !== i) return m;
+      const next = { ...m, ...change };
+      if (change.attr !== undefined) {
+        const existing = attrs.find(a => a.attr === change.attr.trim());
+        next.attr_id = existing?.id ?? null;
+        if (existing) {
+          next.value_type = existing.value_type;
+          next.allow_multiple = !!existing.allow_multiple;
+        }
+      }
+      return next;
+    }));
+  }
+
+  async function runImport(dryRun) {
+    const columns = mapping.filter(m => m.column !== entityColumn && m.attr.trim() !== "");
+    const res = await dispatch(importCsv({
+      id: activeTab.id, text, delimiter: preview.delimiter, separator, entityColumn, columns, dryRun
+    }));
+    if (res.error) return;
+    setReport(res.payload);
+    if (res.payload.committed) {
+      dispatch(fetchAttrs(activeTab.id));
+      dispatch(fetchEntities({ id: activeTab.id, page: 1 }));
+    }
+  }
+
+  function closeModal() {
+    dispatch(toggleShowImport());
+  }
+
+  if (showImport && activeTab) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Import CSV into {activeTab.entity_type}</h3>
+        <p>
+          The first line has to name the columns. Cells of attributes with multiple values are split on
+          <input className="separator" value={separator} onChange={e => setSeparator(e.target.value)} />
+        </p>
+        <input type="file" accept=".csv,.tsv,.txt,text/csv" onChange={openFile} />
+        {preview && (
+          <>
+            <p>{preview.total_rows} rows, separated by "{preview.delimiter === "\t" ? "tab" : preview.delimiter}"</p>
+            <table className="import-mapping">
+              <thead>
+                <tr><th>Column</th><th>Attribute</th><th>Type</th><th>Multiple</th><th>Sample</th></tr>
+              </thead>
+              <tbody>
+                {mapping.map((m, i) => (
+                  <tr key={"col-" + i}>
+                    <td>
+                      <label>
+                        <input type="radio" checked={entityColumn === i} onChange={() => setEntityColumn(i)} />
+                        {preview.headers[i] || "column " + (i + 1)}
+                      </label>
+                    </td>
+                    {entityColumn === i ? (
+                      <td colSpan={3}><i>Entity name</i></td>
+                    ) : (
+                      <>
+                        <td>
+                          <input value={m.attr} placeholder="skip" onChange={e => changeColumn(i, { attr: e.target.value })} />
+                        </td>
+                        <td>
+                          <select value={m.value_type} disabled={!!m.attr_id}
+                            onChange={e => changeColumn(i, { value_type: e.target.value })}>
+                            {VALUE_TYPES.map(t => <option key={t} value={t}>{t}</option>)}
+                          </select>
+                        </td>
+                        <td>
+                          <input type="checkbox" checked={m.allow_multiple} disabled={!!m.attr_id}
+                            onChange={e => changeColumn(i, { allow_multiple: e.target.checked })} />
+                        </td>
+                      </>
+                    )}
+                    <td>{preview.rows.slice(0, 3).map(r => r[i]).filter(c => !!c).join(", ")}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </>
+        )}
+        {report && (
+          <div className="import-report">
+            <p>
+              {report.committed ? "Imported" : report.dry_run && report.errors.length < 1 ? "Ready to import" : "Nothing imported,"}
+              {" "}{report.entities} entities, {report.values} values, {report.attrs} new attributes
+            </p>
+            {report.errors.map((e, i) => (
+              <div key={"err-" + i}>
+                {e.line ? "Line " + e.line : ""}{e.column ? " (" + e.column + ")" : ""}: {e.message}
+              </div>
+            ))}
+          </div>
+        )}
+        <br />
+        <button onClick={() => runImport(true)} disabled={!preview}>Dry Run</button>
+        <button onClick={() => runImport(false)} disabled={!preview || report?.committed}>Import</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Import;
@@ -1,12 +1,30 @@
 import { useState } from "react";
 import { useDispatch, useSelector } from "react-redux";
 
-import { connect, openForm, toggleShowDel, toggleShowHelp } from "../store/eav";
+import {
+  connect,
+  fetchEntities,
+  fetchEntityTypes,
+  openForm,
+  redo,
+  setFormInput,
+  toggleShowArchive,
+  toggleShowBackups,
+  toggleShowDel,
+  toggleShowDuplicates,
+  toggleShowExport,
+  toggleShowHelp,
+  toggleShowImport,
+  toggleShowTable,
+  toggleShowTrash,
+  undo,
+} from "../store/eav";
 
 function MenuBar() {
   const dispatch = useDispatch();
   const connected = useSelector((state) => state.eav.connected);
   const showDelete = useSelector((state) => state.eav.showDelete);
+  const showTable = useSelector((state) => state.eav.showTable);
   const activeTab = useSelector((state) => state.eav.activeEnType);
   const [openFile, setOpenFile] = useState(false);
   const [openEdit, setOpenEdit] = useState(false);
@@ -38,6 +56,11 @@ function MenuBar() {
     dispatch(openForm("entityType"));
   }
 
+  function renameCategory() {
+    dispatch(setFormInput(activeTab));
+    dispatch(openForm("renameEntityType"));
+  }
+
   function toggleDeletion() {
     dispatch(toggleShowDel());
   }
@@ -46,6 +69,18 @@ function MenuBar() {
     dispatch(toggleShowHelp());
   }
 
+  function toggleTrash() {
+    dispatch(toggleShowTrash());
+  }
+
+  async function revert(action) {
+    const res = await dispatch(action());
+    if (res.error) return;
+    // reverted rows may belong to any tab
+    dispatch(fetchEntityTypes());
+    if (activeTab?.id) dispatch(fetchEntities({ id: activeTab.id, page: 1 }));
+  }
+
   return (
     <nav>
       <button onClick={() => openSubmenu("file")}>File</button>
@@ -58,9 +93,15 @@ function MenuBar() {
         <div className="submenu">
           <button onClick={reconnect}>Reconnect</button>
           <button onClick={newEntityMenu}>New Category</button>
+          <button onClick={() => revert(undo)}>Undo</button>
+          <button onClick={() => revert(redo)}>Redo</button>
           <button onClick={toggleDeletion}>
             {showDelete ? "Disable Deletion" : "Enable Deletion"}
           </button>
+          <button onClick={() => dispatch(toggleShowExport())}>Export</button>
+          <button onClick={() => dispatch(toggleShowArchive())}>Archive</button>
+          <button onClick={() => dispatch(toggleShowBackups())}>Backups</button>
+          <button onClick={toggleTrash}>Trash</button>
           <button onClick={toggleHelp}>Help</button>
         </div>
       )}
@@ -68,6 +109,13 @@ function MenuBar() {
         <div className="submenu" style={{left: "45px" }}>
           <button onClick={() => dispatch(openForm("attr"))}> + Attribute</button>
           <button onClick={() => dispatch(openForm("entity"))}> + Entity</button>
+          <button onClick={renameCategory}>Rename Category</button>
+          <button onClick={() => dispatch(openForm("editAttr"))}>Edit Attribute</button>
+          <button onClick={() => dispatch(toggleShowTable())}>
+            {showTable ? "List View" : "Table View"}
+          </button>
+          <button onClick={() => dispatch(toggleShowDuplicates())}>Find Duplicates</button>
+          <button onClick={() => dispatch(toggleShowImport())}>Import CSV</button>
         </div>
       )}
     </nav>
@@ -0,0 +1,143 @@
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  mergeEntities,
+  toggleShowMerge,
+} from "../store/eav";
+
+const STRATEGIES = {
+  keep_target: "keep the target's",
+  keep_source: "take this one's",
+  keep_newest: "keep the newest",
+};
+
+function Merge() {
+  const dispatch = useDispatch();
+  const showMerge = useSelector((state) => state.eav.showMerge);
+  const activeEntity = useSelector((state) => state.eav.activeEntity);
+  const entities = useSelector((state) => state.eav.entities);
+  const mergePair = useSelector((state) => state.eav.mergePair);
+  // kept apart from activeEntity, which is cleared once the entity is merged
+  const [source, setSource] = useState(null);
+  const [targetId, setTargetId] = useState("");
+  // attr id -> strategy, attrs left out keep the target's value
+  const [strategies, setStrategies] = useState({});
+  const [report, setReport] = useState(null);
+  // the report doesn't reflect the strategies since they changed
+  const [stale, setStale] = useState(false);
+
+  useEffect(() => {
+    if (showMerge && mergePair) {
+      setSource(mergePair.source);
+      setTargetId(String(mergePair.target.id));
+      runMerge(true, mergePair.target.id, mergePair.source);
+    }
+    else if (showMerge) setSource(activeEntity);
+    else {
+      setSource(null);
+      setTargetId("");
+      setStrategies({});
+      setReport(null);
+      setStale(false);
+    }
+  }, [showMerge])
+
+  async function runMerge(dryRun, target = Number(targetId), from = source) {
+    const res = await dispatch(mergeEntities({
+      sourceId: from.id,
+      targetId: target,
+      strategies: Object.entries(strategies).map(([attrId, strategy]) => ({ attr_id: Number(attrId), strategy })),
+      dryRun,
+    }));
+    if (res.error) return;
+    setReport(res.payload);
+    setStale(false);
+  }
+
+  // a dry run right away shows the conflicts
+  function changeTarget(e) {
+    const value = e.target.value;
+    setTargetId(value);
+    setStrategies({});
+    setReport(null);
+    if (value) runMerge(true, Number(value));
+  }
+
+  function changeStrategy(attrId, strategy) {
+    setStrategies({ ...strategies, [attrId]: strategy });
+    setStale(true);
+  }
+
+  function closeModal() {
+    dispatch(toggleShowMerge());
+  }
+
+  // a target picked from the duplicates may not be on the loaded page
+  function targets() {
+    const sameType = entities.filter(e => e.id !== source.id && e.entity_type_id === source.entity_type_id);
+    const preset = mergePair?.target;
+    if (preset && !sameType.some(e => e.id === preset.id)) sameType.push(preset);
+    return sameType;
+  }
+
+  if (showMerge && source) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Merge {source.entity}</h3>
+        <label htmlFor="target">Into </label>
+        <select name="target" value={targetId} onChange={changeTarget} disabled={report?.committed}>
+          <option value=""></option>
+          {targets().map(e => (
+            <option key={"target-" + e.id} value={e.id}>{e.entity}</option>
+          ))}
+        </select>
+        {report && (
+          <>
+            {report.conflicts.length > 0 && (
+              <table className="import-mapping">
+                <thead>
+                  <tr><th>Attribute</th><th>Target</th><th>This one</th><th></th></tr>
+                </thead>
+                <tbody>
+                  {report.conflicts.map(c => (
+                    <tr key={"conflict-" + c.attr_id}>
+                      <td>{c.attr}</td>
+                      <td>{c.target}</td>
+                      <td>{c.source}</td>
+                      <td>
+                        <select value={strategies[c.attr_id] ?? "keep_target"} onChange={e => changeStrategy(c.attr_id, e.target.value)}
+                          disabled={report.committed}>
+                          {Object.entries(STRATEGIES).map(([value, label]) => (
+                            <option key={value} value={value}>{label}</option>
+                          ))}
+                        </select>
+                        {!stale && (c.kept_source ? " (this one)" : " (target)")}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            )}
+            {!stale && (
+              <div className="import-report">
+                <p>
+                  {report.committed ? "Merged" : "Ready to merge"}: {report.moved} values move over,
+                  {" "}{report.duplicates} are already there
+                  {report.self_refs > 0 && <>, {report.self_refs} references between the two go to the trash</>}
+                </p>
+              </div>
+            )}
+          </>
+        )}
+        <br />
+        <button onClick={() => runMerge(true)} disabled={!targetId || report?.committed}>Dry Run</button>
+        <button onClick={() => runMerge(false)} disabled={!targetId || report?.committed}>Merge</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Merge;
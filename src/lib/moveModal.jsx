@@ -0,0 +1,147 @@
+import { useEffect, useState } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  fetchAttrsOfType,
+  moveEntity,
+  toggleShowMove,
+} from "../store/eav";
+
+// "" follows the attribute with the same name, "none" leaves the values behind
+const SAME_NAME = "";
+const LEAVE = "none";
+
+function Move() {
+  const dispatch = useDispatch();
+  const showMove = useSelector((state) => state.eav.showMove);
+  const activeEntity = useSelector((state) => state.eav.activeEntity);
+  const entityTypes = useSelector((state) => state.eav.entityTypes);
+  // kept apart from activeEntity, which is cleared once the entity has moved
+  const [entity, setEntity] = useState(null);
+  const [target, setTarget] = useState("");
+  const [targetAttrs, setTargetAttrs] = useState([]);
+  // source attr id -> SAME_NAME, LEAVE or a target attr id
+  const [choices, setChoices] = useState({});
+  const [createMissing, setCreateMissing] = useState(false);
+  const [report, setReport] = useState(null);
+  // the report doesn't reflect the choices since they changed
+  const [stale, setStale] = useState(false);
+
+  useEffect(() => {
+    if (showMove) setEntity(activeEntity);
+    else {
+      setEntity(null);
+      setTarget("");
+      setTargetAttrs([]);
+      setChoices({});
+      setCreateMissing(false);
+      setReport(null);
+      setStale(false);
+    }
+  }, [showMove])
+
+  function mapping() {
+    return Object.entries(choices)
+      .filter(([, to]) => to !== SAME_NAME)
+      .map(([from, to]) => ({ from: Number(from), to: to === LEAVE ? null : Number(to) }));
+  }
+
+  async function runMove(dryRun, entityTypeId = Number(target), attrMapping = mapping()) {
+    const res = await dispatch(moveEntity({ id: entity.id, entityTypeId, mapping: attrMapping, createMissing, dryRun }));
+    if (res.error) return;
+    setReport(res.payload);
+    setStale(false);
+  }
+
+  // a dry run right away shows which attributes match by name
+  async function changeTarget(e) {
+    const value = e.target.value;
+    setTarget(value);
+    setChoices({});
+    setReport(null);
+    if (!value) return setTargetAttrs([]);
+    const res = await dispatch(fetchAttrsOfType(Number(value)));
+    if (!res.error) setTargetAttrs(res.payload);
+    runMove(true, Number(value), []);
+  }
+
+  function changeChoice(from, value) {
+    setChoices({ ...choices, [from]: value });
+    setStale(true);
+  }
+
+  function describe(m) {
+    if (m.created) return "new attribute";
+    if (m.to) return m.target;
+    return "left behind";
+  }
+
+  function closeModal() {
+    dispatch(toggleShowMove());
+  }
+
+  if (showMove && entity) return (
+    <div className="modal-container">
+      <div className="help-body import-body">
+        <h3>Move {entity.entity}</h3>
+        <label htmlFor="target">To category </label>
+        <select name="target" value={target} onChange={changeTarget} disabled={report?.committed}>
+          <option value=""></option>
+          {entityTypes.filter(t => t.id !== entity.entity_type_id).map(t => (
+            <option key={"type-" + t.id} value={t.id}>{t.entity_type}</option>
+          ))}
+        </select>
+        {report && (
+          <>
+            <table className="import-mapping">
+              <thead>
+                <tr><th>Attribute</th><th>Goes to</th><th>Last run</th></tr>
+              </thead>
+              <tbody>
+                {report.mapping.map(m => (
+                  <tr key={"attr-" + m.from}>
+                    <td>{m.attr}</td>
+                    <td>
+                      <select value={choices[m.from] ?? SAME_NAME} onChange={e => changeChoice(m.from, e.target.value)}>
+                        <option value={SAME_NAME}>same name</option>
+                        <option value={LEAVE}>leave behind</option>
+                        {targetAttrs.map(a => (
+                          <option key={"target-" + a.id} value={a.id}>{a.attr} ({a.value_type})</option>
+                        ))}
+                      </select>
+                    </td>
+                    <td>{!stale && describe(m)}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+            <div style={{ display: "flex", alignItems: "center" }}>
+              <label htmlFor="create_missing">Create missing attributes</label>
+              <input type="checkbox" name="create_missing" checked={createMissing}
+                onChange={e => { setCreateMissing(e.target.checked); setStale(true); }} />
+            </div>
+            {!stale && (
+              <div className="import-report">
+                <p>
+                  {report.committed ? "Moved" : "Ready to move"} {report.values} values
+                  {report.attrs > 0 && ", " + report.attrs + " new attributes"}
+                  {report.dropped.length > 0 && ", left in the trash:"}
+                </p>
+                {report.dropped.map(d => (
+                  <div key={"dropped-" + d.value_id}>{d.attr} ({d.value}): {d.reason}</div>
+                ))}
+              </div>
+            )}
+          </>
+        )}
+        <br />
+        <button onClick={() => runMove(true)} disabled={!target || report?.committed}>Dry Run</button>
+        <button onClick={() => runMove(false)} disabled={!target || report?.committed}>Move</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Move;
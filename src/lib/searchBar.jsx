@@ -1,7 +1,7 @@
 import { useState } from "react";
 import { useDispatch } from "react-redux";
 
-import { scrollToTop, searchAttrValue, searchAttrValueComparison, searchEntity } from "../store/eav";
+import { scrollToTop, search } from "../store/eav";
 
 function SearchBar() {
   const dispatch = useDispatch();
@@ -19,24 +19,8 @@ function SearchBar() {
   }
 
   function handleSubmit() {
-    const comparisonRegex = /^[A-Za-z0-9_]+? [<>] /i;
-    if (comparisonRegex.test(v)) {
-      const [attr, op, val] = v.split(" ");
-      if (isNaN(Number(val))) return console.error("Value is not a number");
-      dispatch(scrollToTop());
-      dispatch(searchAttrValueComparison({ attr, val, op }));
-      return;
-    }
-    const attrRegex = /^[A-Za-z0-9_]+?:/i;
-    if (attrRegex.test(v)) {
-      const [attr, val] = v.split(": ");
-      dispatch(scrollToTop());
-      dispatch(searchAttrValue({ attr, val }));
-      return;
-    }
-    const extendRegex = /^![A-Za-z0-9().?!/&-_ ]+?$/i;
     dispatch(scrollToTop());
-    dispatch(searchEntity({ regex: v, extended: !extendRegex.test(v) }));
+    dispatch(search({ query: v }));
   }
 
   return (
@@ -0,0 +1,83 @@
+import { useEffect } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import { fetchTable, setSort } from "../store/eav";
+
+// cells are plain values, lists for attributes with multiple values, numbers with a unit are { value, unit }
+// and entity references { id, entity }
+function formatCell(cell, column) {
+  if (cell === null || cell === undefined) return "";
+  if (Array.isArray(cell)) return cell.map(c => formatCell(c, column)).join(", ");
+  if (column.value_type === "ref") return cell.entity ?? "#" + cell.id;
+  if (typeof cell === "object") return formatCell(cell.value, column) + " " + cell.unit;
+  if (column.value_type === "bool") return cell ? "yes" : "no";
+  if (column.value_type === "time") return new Date(cell).toLocaleString();
+  return String(cell);
+}
+
+const TableView = () => {
+  const dispatch = useDispatch();
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+  const table = useSelector((state) => state.eav.table);
+  const sort = useSelector((state) => state.eav.sort);
+
+  useEffect(() => {
+    if (activeTab?.id) dispatch(fetchTable({ id: activeTab.id }));
+    // eslint-disable-next-line
+  }, [activeTab?.id, sort])
+
+  // clicking a header sorts by it, clicking it again reverses the order
+  function sortBy(by) {
+    const [key] = sort;
+    const same = JSON.stringify(key?.by) === JSON.stringify(by);
+    dispatch(setSort([{ by, desc: same ? !key.desc : false }]));
+  }
+
+  function sortMark(by) {
+    const [key] = sort;
+    if (JSON.stringify(key?.by) !== JSON.stringify(by)) return "";
+    return key.desc ? " ▼" : " ▲";
+  }
+
+  if (!activeTab) return (
+    <div className="entry-container">
+      <div className="eav-entry"><div className="label">No tab selected</div></div>
+    </div>
+  )
+  return (
+    <div className="entry-container table-view">
+      <table>
+        <thead>
+          <tr>
+            <th onClick={() => sortBy("entity")}>{activeTab.entity_type}{sortMark("entity")}</th>
+            {table.columns.map(c => (
+              <th key={"col-" + c.id} onClick={() => sortBy({ attr: c.attr })}>
+                {c.attr}{sortMark({ attr: c.attr })}
+              </th>
+            ))}
+          </tr>
+        </thead>
+        <tbody>
+          {table.rows.map(r => (
+            <tr key={"row-" + r.entity.id}>
+              <td>{r.entity.entity}</td>
+              {table.columns.map((c, i) => (
+                <td key={"cell-" + r.entity.id + "-" + c.id}>{formatCell(r.cells[i], c)}</td>
+              ))}
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      <div className="table-footer">
+        {table.rows.length} of {table.total}
+        {table.nextCursor && (
+          <button onClick={() => dispatch(fetchTable({ id: activeTab.id, cursor: table.nextCursor }))}>
+            Load more
+          </button>
+        )}
+      </div>
+    </div>
+  )
+}
+
+export default TableView;
@@ -0,0 +1,74 @@
+import { useEffect } from "react";
+import { useDispatch, useSelector } from "react-redux";
+
+import {
+  fetchEntities,
+  fetchEntityTypes,
+  fetchTrash,
+  purgeTrash,
+  restoreFromTrash,
+  toggleShowTrash,
+} from "../store/eav";
+
+const kindLabels = {
+  entity_type: "Category",
+  entity: "Entity",
+  attr: "Attribute",
+  value: "Value",
+}
+
+function Trash() {
+  const dispatch = useDispatch();
+  const showTrash = useSelector((state) => state.eav.showTrash);
+  const trash = useSelector((state) => state.eav.trash);
+  const activeTab = useSelector((state) => state.eav.activeEnType);
+
+  useEffect(() => {
+    if (showTrash) dispatch(fetchTrash());
+  }, [showTrash])
+
+  async function restore(item) {
+    const res = await dispatch(restoreFromTrash({ kind: item.kind, id: item.id }));
+    if (res.error) return;
+    // restored rows may belong to the open tab
+    dispatch(fetchEntityTypes());
+    if (activeTab?.id) dispatch(fetchEntities({ id: activeTab.id, page: 1 }));
+  }
+
+  async function emptyTrash() {
+    if (!window.confirm("Permanently delete everything in the trash?")) return;
+    await dispatch(purgeTrash(0));
+    dispatch(fetchTrash());
+  }
+
+  function closeModal() {
+    dispatch(toggleShowTrash());
+  }
+
+  if (showTrash) return (
+    <div className="modal-container">
+      <div className="help-body">
+        <h3>Trash</h3>
+        {trash.length < 1 && <p>The trash is empty.</p>}
+        {trash.map((item) => (
+          <div className="trash-row" key={item.kind + item.id}>
+            <span>
+              <b>{kindLabels[item.kind] ?? item.kind}</b> {item.name}
+              {!!item.parent && <i> ({item.parent})</i>}
+            </span>
+            <span>
+              {new Date(item.deleted_at).toLocaleString()}
+              <button onClick={() => restore(item)}>Restore</button>
+            </span>
+          </div>
+        ))}
+        <br />
+        <button onClick={emptyTrash} disabled={trash.length < 1}>Empty Trash</button>
+        <button onClick={closeModal}>Close</button>
+      </div>
+    </div>
+  )
+  return null;
+}
+
+export default Trash;
@@ -1,15 +1,19 @@
 import { useEffect, useState } from "react";
 import { useDispatch, useSelector } from "react-redux";
 
-import { addValue, updateValue, openForm, setFormInput } from "../store/eav";
+import { addValue, fetchEntitiesOfType, updateValue, openForm, setFormInput } from "../store/eav";
 
 const ValueRow = ({ data }) => {
   const dispatch = useDispatch();
   const showDelete = useSelector((state) => state.eav.showDelete);
+  const entityTypes = useSelector((state) => state.eav.entityTypes);
   const [isEditing, setIsEditing] = useState(false);
   const [fvalue, setFValue] = useState("");
   const [unit, setUnit] = useState("");
   const [dvalue, setDValue] = useState(null);
+  // category and entities to pick from for entity references
+  const [refType, setRefType] = useState(data.ref_entity_type_id ?? "");
+  const [refOptions, setRefOptions] = useState([]);
 
   function submitValue() {
     if (!fvalue && fvalue !== 0 && fvalue !== false) return setIsEditing(false);
@@ -30,6 +34,10 @@ const ValueRow = ({ data }) => {
       case "bool":
         form.value_bool = Boolean(fvalue);
         break;
+      case "ref":
+        form.value_int = Number(fvalue);
+        form.ref_entity = refOptions.find(e => e.id === Number(fvalue))?.entity ?? data.ref_entity;
+        break;
       default:
         break;
     }
@@ -37,7 +45,7 @@ const ValueRow = ({ data }) => {
     if (!data.value_id) dispatch(addValue(form));
     else dispatch(updateValue(form));
     // clean up
-    setDValue(fvalue);
+    setDValue(data.value_type === "ref" ? form.ref_entity : fvalue);
     setIsEditing(false);
   }
 
@@ -52,6 +60,19 @@ const ValueRow = ({ data }) => {
     setFValue(checked);
   }
 
+  async function loadRefOptions(entityTypeId) {
+    setRefType(entityTypeId);
+    setRefOptions([]);
+    if (!entityTypeId) return;
+    const res = await dispatch(fetchEntitiesOfType(Number(entityTypeId)));
+    if (!res.error) setRefOptions(res.payload);
+  }
+
+  function startEditing() {
+    if (data.value_type === "ref" && refType && refOptions.length < 1) loadRefOptions(refType);
+    setIsEditing(true);
+  }
+
   function confirmDeleteValue(id) {
     dispatch(openForm("delValue"));
     dispatch(setFormInput({ id: id }));
@@ -109,6 +130,14 @@ const ValueRow = ({ data }) => {
           setDValue("-");
         }
         break;
+      case "ref":
+        if (data.value_int) {
+          setFValue(data.value_int);
+          setDValue(data.ref_entity ?? "#" + data.value_int);
+        } else {
+          setDValue("-");
+        }
+        break;
       default:
         break;
     }
@@ -134,6 +163,18 @@ const ValueRow = ({ data }) => {
           {data.value_type === "bool" && (
             <input type="checkbox" name="fvalue" checked={fvalue} onChange={handleCheck} />
           )}
+          {data.value_type === "ref" && !data.ref_entity_type_id && (
+            <select name="ref_type" value={refType} onChange={e => loadRefOptions(e.target.value)}>
+              <option value="">category</option>
+              {entityTypes.map(et => <option key={"ref-type-" + et.id} value={et.id}>{et.entity_type}</option>)}
+            </select>
+          )}
+          {data.value_type === "ref" && (
+            <select name="fvalue" value={fvalue} onChange={handleInput}>
+              <option value=""></option>
+              {refOptions.map(e => <option key={"ref-" + e.id} value={e.id}>{e.entity}</option>)}
+            </select>
+          )}
           {data.value_type === "int" || data.value_type === "float" && (
             <input type="text" placeholder="unit" name="unit" className="subfield" value={unit} onChange={handleInput} />
           )}
@@ -147,7 +188,7 @@ const ValueRow = ({ data }) => {
           )}
         </div>
       ) : (
-        <div className="value-display-container" onClick={() => setIsEditing(true)}>
+        <div className="value-display-container" onClick={startEditing}>
           {dvalue} {unit}
         </div>
       )}
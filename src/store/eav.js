@@ -1,16 +1,21 @@
 import { createAsyncThunk, createSlice } from '@reduxjs/toolkit';
 import { invoke } from "@tauri-apps/api/tauri";
 
+// list commands return { items, total, page, per_page, has_more, next_cursor }
 const PER_PAGE = 100;
 export const fnsWithPaginationEnum = {
   none: null,
   fetchEntities: "fetchEntities",
-  searchEntity: "searchEntity",
-  searchAttrValue: "searchAttrValue",
-  searchAttrValueComparison: "searchAttrValueComparison",
+  search: "search",
   fetchValues: "fetchValues", // not an entity fetch
 }
 
+// backend errors are { code, message, field }, wrap anything else the same way
+function toError(e) {
+  if (e?.code) return e;
+  return { code: "Client", message: String(e?.message ?? e), field: null };
+}
+
 export const connect = createAsyncThunk(
   'eav/connect',
   async (_, { rejectWithValue }) => {
@@ -20,7 +25,7 @@ export const connect = createAsyncThunk(
       return true;
     } catch (e) {
       console.error("Connection failed -", e);
-      return rejectWithValue(false);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -33,20 +38,21 @@ export const fetchEntityTypes = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
 
 export const fetchEntities = createAsyncThunk(
   'eav/fetchEntities',
-  async ({ id, page }, { rejectWithValue }) => {
+  async ({ id, page }, { rejectWithValue, getState }) => {
     try {
-      const res = await invoke("fetch_entities", { entityTypeId: id, page });
+      const sort = getState()?.eav?.sort ?? [];
+      const res = await invoke("fetch_entities", { entityTypeId: id, sort, page, perPage: PER_PAGE });
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   },
   {
@@ -57,6 +63,35 @@ export const fetchEntities = createAsyncThunk(
   }
 )
 
+// one row per entity with a cell per attribute, later pages continue from the cursor
+export const fetchTable = createAsyncThunk(
+  'eav/fetchTable',
+  async ({ id, cursor }, { rejectWithValue, getState }) => {
+    try {
+      const sort = getState()?.eav?.sort ?? [];
+      const res = await invoke("fetch_table", { entityTypeId: id, sort, cursor, perPage: PER_PAGE });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// attributes of an entity type, used to offer sorting by their values
+export const fetchAttrs = createAsyncThunk(
+  'eav/fetchAttrs',
+  async (entityTypeId, { rejectWithValue }) => {
+    try {
+      const res = await invoke("fetch_attrs", { entityTypeId, multiOnly: false });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
 export const fetchValues = createAsyncThunk(
   'eav/fetchValues',
   async (entityId, { rejectWithValue }) => {
@@ -65,7 +100,7 @@ export const fetchValues = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   },
   {
@@ -80,18 +115,21 @@ export const addAttribute = createAsyncThunk(
   'eav/addAttribute',
   async (input, { rejectWithValue }) => {
     try {
-      const { attr, value_type, entity_type_id, allow_multiple } = input;
+      const { attr, value_type, entity_type_id, allow_multiple, ref_entity_type_id, on_delete } = input;
       if (!attr || !value_type || !entity_type_id) throw new Error("Missing required inputs");
       const res = await invoke("create_attr", { 
         entity_type_id, 
         attr_name: attr, 
         attr_type: value_type, 
         allow_multiple,
+        // entity references only, any category when empty
+        ref_entity_type_id: value_type === "ref" && ref_entity_type_id ? Number(ref_entity_type_id) : null,
+        on_delete: value_type === "ref" ? on_delete || "block" : null,
       });
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -106,7 +144,7 @@ export const addEntity = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -121,7 +159,7 @@ export const addEntityType = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -140,7 +178,7 @@ export const addValue = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -159,7 +197,181 @@ export const updateValue = createAsyncThunk(
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const renameEntityType = createAsyncThunk(
+  'eav/renameEntityType',
+  async ({ id, name }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("rename_entity_type", { id, name });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const renameEntity = createAsyncThunk(
+  'eav/renameEntity',
+  async ({ id, name }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("rename_entity", { id, name });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const renameAttr = createAsyncThunk(
+  'eav/renameAttr',
+  async ({ id, name }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("rename_attr", { id, name });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// keep is "oldest" or "newest", without it turning multiple values off fails while an entity has several
+export const setAttrAllowMultiple = createAsyncThunk(
+  'eav/setAttrAllowMultiple',
+  async ({ id, allowMultiple, keep }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("set_attr_allow_multiple", { id, allowMultiple, keep: keep || null });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// onDelete is "block", "nullify" or "cascade", a null refEntityTypeId lets the attribute point at any entity
+export const setAttrRef = createAsyncThunk(
+  'eav/setAttrRef',
+  async ({ id, refEntityTypeId, onDelete }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("set_attr_ref", { id, ref_entity_type_id: refEntityTypeId || null, on_delete: onDelete });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const convertAttrType = createAsyncThunk(
+  'eav/convertAttrType',
+  async ({ id, valueType, onFailure, dryRun }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("convert_attr_type", { id, valueType, onFailure, dryRun });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const cloneEntity = createAsyncThunk(
+  'eav/cloneEntity',
+  async ({ id, options }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("clone_entity", { id, options });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const mergeEntities = createAsyncThunk(
+  'eav/mergeEntities',
+  async ({ sourceId, targetId, strategies, dryRun }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("merge_entities", { sourceId, targetId, strategies, dryRun });
+      return { ...res, sourceId };
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const findDuplicates = createAsyncThunk(
+  'eav/findDuplicates',
+  async ({ entityTypeId, options }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("find_duplicates", { entityTypeId, options });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const moveEntity = createAsyncThunk(
+  'eav/moveEntity',
+  async ({ id, entityTypeId, mapping, createMissing, dryRun }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("move_entity", { id, entityTypeId, mapping, createMissing, dryRun });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// entity reference values of other entities pointing at the entity
+export const fetchBacklinks = createAsyncThunk(
+  'eav/fetchBacklinks',
+  async (entityId, { rejectWithValue }) => {
+    try {
+      const res = await invoke("fetch_backlinks", { entityId });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// attributes of another category than the open one, the store keeps them out of state.attrs
+export const fetchAttrsOfType = createAsyncThunk(
+  'eav/fetchAttrsOfType',
+  async (entityTypeId, { rejectWithValue }) => {
+    try {
+      const res = await invoke("fetch_attrs", { entityTypeId, multiOnly: false });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// entities to pick as the target of an entity reference, kept out of state.entities too
+export const fetchEntitiesOfType = createAsyncThunk(
+  'eav/fetchEntitiesOfType',
+  async (entityTypeId, { rejectWithValue }) => {
+    try {
+      const res = await invoke("fetch_entities", { entityTypeId, sort: [{ by: "entity", desc: false }], page: 1, perPage: 1000 });
+      return res.items;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -173,7 +385,7 @@ export const deleteEntityType = createAsyncThunk(
       return id;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -187,7 +399,7 @@ export const deleteEntity = createAsyncThunk(
       return id;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -201,21 +413,23 @@ export const deleteValue = createAsyncThunk(
       return id;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   }
 )
 
-export const searchEntity = createAsyncThunk(
-  'eav/searchEntity',
-  async ({ regex, extended, page }, { rejectWithValue }) => {
+// query syntax is parsed by the backend, see eav_query.rs
+export const search = createAsyncThunk(
+  'eav/search',
+  async ({ query, page }, { rejectWithValue, getState }) => {
     try {
-      if (!regex) return [];
-      const res = await invoke("search_entity", { regex, extended, page });
+      if (!query?.trim()) return { items: [], total: 0, page: 1, per_page: PER_PAGE, has_more: false };
+      const sort = getState()?.eav?.sort ?? [];
+      const res = await invoke("search", { query, sort, page, perPage: PER_PAGE });
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
   },
   {
@@ -226,47 +440,187 @@ export const searchEntity = createAsyncThunk(
   }
 )
 
-export const searchAttrValue = createAsyncThunk(
-  'eav/searchAttrValue',
-  async ({ attr, val, page }, { rejectWithValue }) => {
+export const fetchTrash = createAsyncThunk(
+  'eav/fetchTrash',
+  async (_, { rejectWithValue }) => {
     try {
-      if (!attr || !val) return [];
-      let res = await invoke("search_entity_with_attr_value", { attr, val, page });
-      if (["FALSE", "False", "false", "No", "no", "n", "NULL", "null"].includes(val)) {
-        // also search for null values
-        const append = await invoke("search_entity_without_attr", { attr, page });
-        res = [...res, ...append];
-      }
+      const res = await invoke("fetch_trash");
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
-  },
-  {
-    condition: (_, { getState }) => {
-      const loading = getState()?.eav?.loading ?? true;
-      if (loading) return false;
+  }
+)
+
+export const restoreFromTrash = createAsyncThunk(
+  'eav/restoreFromTrash',
+  async ({ kind, id }, { rejectWithValue }) => {
+    try {
+      if (!kind || !id) throw new Error("No item provided");
+      await invoke("restore_from_trash", { kind, id });
+      return { kind, id };
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
     }
   }
 )
 
-export const searchAttrValueComparison = createAsyncThunk(
-  'eav/searchAttrValueComparison',
-  async ({ attr, val, op, page }, { rejectWithValue }) => {
+export const purgeTrash = createAsyncThunk(
+  'eav/purgeTrash',
+  async (maxAgeDays, { rejectWithValue }) => {
     try {
-      if (!attr || !val || !op) return [];
-      let res = await invoke("search_entity_with_attr_value_comparison", { attr, val, op, page });
+      const res = await invoke("purge_trash", { maxAgeDays });
       return res;
     } catch (e) {
       console.error("API failed -", e);
-      return rejectWithValue(null);
+      return rejectWithValue(toError(e));
     }
-  },
-  {
-    condition: (_, { getState }) => {
-      const loading = getState()?.eav?.loading ?? true;
-      if (loading) return false;
+  }
+)
+
+// writes the open category, or the last search when no category is open, to a csv or xlsx file
+export const exportTable = createAsyncThunk(
+  'eav/exportTable',
+  async ({ path, format, separator, units, timeFormat }, { rejectWithValue, getState }) => {
+    try {
+      const { activeEnType, entityMeta, sort } = getState().eav;
+      const query = entityMeta.fn === fnsWithPaginationEnum.search ? entityMeta.query : null;
+      const res = await invoke("export_table", {
+        entityTypeId: activeEnType?.id ?? null, query: activeEnType ? null : query, sort,
+        path, format, separator, units, timeFormat
+      });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// proposes an attribute for each csv column, the text is the whole file
+export const previewCsv = createAsyncThunk(
+  'eav/previewCsv',
+  async ({ id, text, delimiter, separator }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("preview_csv", { entityTypeId: id, text, delimiter, separator });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// returns { committed, entities, attrs, values, errors }, nothing is written on a dry run or any row error
+export const importCsv = createAsyncThunk(
+  'eav/importCsv',
+  async ({ id, text, delimiter, separator, entityColumn, columns, dryRun }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("import_csv", {
+        entityTypeId: id, text, delimiter, separator, entityColumn, columns, dryRun
+      });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// saves every row, trash included, to a json lines archive
+export const exportArchive = createAsyncThunk(
+  'eav/exportArchive',
+  async ({ path }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("export_archive", { path });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// returns { committed, entity_types, entities, attrs, values, skipped, conflicts }, nothing is written on a dry run
+export const importArchive = createAsyncThunk(
+  'eav/importArchive',
+  async ({ path, dryRun }, { rejectWithValue }) => {
+    try {
+      const res = await invoke("import_archive", { path, dryRun });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// backups are listed newest first as { name, created_at, reason, size }
+export const fetchBackups = createAsyncThunk(
+  'eav/fetchBackups',
+  async (_, { rejectWithValue }) => {
+    try {
+      const res = await invoke("fetch_backups");
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const createBackup = createAsyncThunk(
+  'eav/createBackup',
+  async (_, { rejectWithValue }) => {
+    try {
+      const res = await invoke("create_backup");
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// replaces the whole database with the backup, undo history is cleared
+export const restoreBackup = createAsyncThunk(
+  'eav/restoreBackup',
+  async (name, { rejectWithValue }) => {
+    try {
+      const res = await invoke("restore_backup", { name });
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+// undo/redo return a report of the rows that were changed back
+export const undo = createAsyncThunk(
+  'eav/undo',
+  async (_, { rejectWithValue }) => {
+    try {
+      const res = await invoke("undo");
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
+    }
+  }
+)
+
+export const redo = createAsyncThunk(
+  'eav/redo',
+  async (_, { rejectWithValue }) => {
+    try {
+      const res = await invoke("redo");
+      return res;
+    } catch (e) {
+      console.error("API failed -", e);
+      return rejectWithValue(toError(e));
     }
   }
 )
@@ -278,14 +632,31 @@ export const eavSlice = createSlice({
     connected: false,
     entityTypes: [],
     entities: [],
+    attrs: [],
     values: [],
+    // [{ by: "entity" | "created_at" | { attr: name }, desc }], applied to entity lists and search
+    sort: [],
     formType: null,
     formInput: {},
     activeEnType: null,
     activeEntity: null,
     showDelete: false,
     showHelp: false,
+    showTrash: false,
+    showImport: false,
+    showExport: false,
+    showArchive: false,
+    showBackups: false,
+    showMove: false,
+    showMerge: false,
+    // { source, target } entities the merge modal opens with, instead of the active entity
+    mergePair: null,
+    showDuplicates: false,
+    showTable: false,
+    table: { columns: [], rows: [], total: 0, nextCursor: null },
+    trash: [],
     resetScroll: false,
+    error: null,
     entityMeta: {
       fn: fnsWithPaginationEnum.none,
       page: 1,
@@ -328,11 +699,49 @@ export const eavSlice = createSlice({
     toggleShowHelp: (state) => {
       state.showHelp = !state.showHelp;
     },
+    toggleShowTrash: (state) => {
+      state.showTrash = !state.showTrash;
+    },
+    toggleShowImport: (state) => {
+      state.showImport = !state.showImport;
+    },
+    toggleShowExport: (state) => {
+      state.showExport = !state.showExport;
+    },
+    toggleShowArchive: (state) => {
+      state.showArchive = !state.showArchive;
+    },
+    toggleShowBackups: (state) => {
+      state.showBackups = !state.showBackups;
+    },
+    toggleShowMove: (state) => {
+      state.showMove = !state.showMove;
+    },
+    toggleShowMerge: (state) => {
+      state.showMerge = !state.showMerge;
+      state.mergePair = null;
+    },
+    openMerge: (state, action) => {
+      state.mergePair = action.payload;
+      state.showMerge = true;
+    },
+    toggleShowDuplicates: (state) => {
+      state.showDuplicates = !state.showDuplicates;
+    },
     scrollToTop: (state) => {
       state.resetScroll = true;
     },
     resetScrollToTop: (state) => {
       state.resetScroll = false;
+    },
+    clearError: (state) => {
+      state.error = null;
+    },
+    setSort: (state, action) => {
+      state.sort = action.payload;
+    },
+    toggleShowTable: (state) => {
+      state.showTable = !state.showTable;
     }
   },
   extraReducers: (builder) => {
@@ -363,17 +772,38 @@ export const eavSlice = createSlice({
       state.entityMeta = {
         fn: fnsWithPaginationEnum.fetchEntities,
         id: action.meta.arg?.id,
-        page: action.meta.arg?.page || 1,
+        page: action.payload.page,
+        total: action.payload.total,
+        end: !action.payload.has_more,
       }
-      if (action.payload.length < PER_PAGE) state.entityMeta.end = true;
       // update entities data
-      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload];
-      else state.entities = action.payload;
+      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload.items];
+      else state.entities = action.payload.items;
       state.activeEntity = null;
     }).addCase(fetchEntities.rejected, (state) => {
       state.loading = false;
       state.entities = [];
     });
+    builder.addCase(fetchTable.pending, (state) => {
+      state.loading = true;
+    }).addCase(fetchTable.fulfilled, (state, action) => {
+      state.loading = false;
+      const rows = action.meta.arg?.cursor ? [...state.table.rows, ...action.payload.items] : action.payload.items;
+      state.table = {
+        columns: action.payload.columns,
+        rows,
+        total: action.payload.total,
+        nextCursor: action.payload.has_more ? action.payload.next_cursor : null,
+      };
+    }).addCase(fetchTable.rejected, (state) => {
+      state.loading = false;
+      state.table = { columns: [], rows: [], total: 0, nextCursor: null };
+    });
+    builder.addCase(fetchAttrs.fulfilled, (state, action) => {
+      state.attrs = action.payload;
+    }).addCase(fetchAttrs.rejected, (state) => {
+      state.attrs = [];
+    });
     builder.addCase(fetchValues.pending, (state) => {
       state.loading = true;
     }).addCase(fetchValues.fulfilled, (state, action) => {
@@ -446,6 +876,8 @@ export const eavSlice = createSlice({
         state.values[idx].value_float = action.payload.value_float;
         state.values[idx].value_time = action.payload.value_time;
         state.values[idx].value_bool = action.payload.value_bool;
+        // the name of a referenced entity comes from the form
+        state.values[idx].ref_entity = action.meta.arg.ref_entity;
         if (newRow) state.values.splice(idx+1, 0, newRow);
       }
     }).addCase(addValue.rejected, (state) => {
@@ -466,6 +898,7 @@ export const eavSlice = createSlice({
         state.values[idx].value_float = action.payload.value_float;
         state.values[idx].value_time = action.payload.value_time;
         state.values[idx].value_bool = action.payload.value_bool;
+        state.values[idx].ref_entity = action.meta.arg.ref_entity;
       }
     }).addCase(updateValue.rejected, (state) => {
       state.loading = false;
@@ -482,6 +915,89 @@ export const eavSlice = createSlice({
     }).addCase(deleteEntityType.rejected, (state) => {
       state.loading = false;
     });
+    builder.addCase(renameEntityType.pending, (state) => {
+      state.loading = true;
+    }).addCase(renameEntityType.fulfilled, (state, action) => {
+      state.loading = false;
+      state.entityTypes = state.entityTypes.map(t => t.id === action.payload.id ? action.payload : t);
+      if (state.activeEnType?.id === action.payload.id) state.activeEnType = action.payload;
+    }).addCase(renameEntityType.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(renameEntity.pending, (state) => {
+      state.loading = true;
+    }).addCase(renameEntity.fulfilled, (state, action) => {
+      state.loading = false;
+      state.entities = state.entities.map(e => e.id === action.payload.id ? action.payload : e);
+      if (state.activeEntity?.id === action.payload.id) state.activeEntity = action.payload;
+    }).addCase(renameEntity.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(renameAttr.pending, (state) => {
+      state.loading = true;
+    }).addCase(renameAttr.fulfilled, (state, action) => {
+      state.loading = false;
+      state.attrs = state.attrs.map(a => a.id === action.payload.id ? action.payload : a);
+      state.values = state.values.map(v => v.attr_id === action.payload.id ? { ...v, attr: action.payload.attr } : v);
+    }).addCase(renameAttr.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(setAttrRef.pending, (state) => {
+      state.loading = true;
+    }).addCase(setAttrRef.fulfilled, (state, action) => {
+      state.loading = false;
+      state.attrs = state.attrs.map(a => a.id === action.payload.id ? action.payload : a);
+    }).addCase(setAttrRef.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(setAttrAllowMultiple.pending, (state) => {
+      state.loading = true;
+    }).addCase(setAttrAllowMultiple.fulfilled, (state, action) => {
+      state.loading = false;
+      state.attrs = state.attrs.map(a => a.id === action.payload.id ? action.payload : a);
+    }).addCase(setAttrAllowMultiple.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(convertAttrType.pending, (state) => {
+      state.loading = true;
+    }).addCase(convertAttrType.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(convertAttrType.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(cloneEntity.pending, (state) => {
+      state.loading = true;
+    }).addCase(cloneEntity.fulfilled, (state, action) => {
+      state.loading = false;
+      state.entities.push(action.payload);
+    }).addCase(cloneEntity.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(mergeEntities.pending, (state) => {
+      state.loading = true;
+    }).addCase(mergeEntities.fulfilled, (state, action) => {
+      state.loading = false;
+      const { committed, sourceId } = action.payload;
+      if (!committed) return;
+      // the source is in the trash now
+      state.entities = state.entities.filter(e => e.id !== sourceId);
+      if (state.activeEntity?.id === sourceId) state.activeEntity = null;
+    }).addCase(mergeEntities.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(moveEntity.pending, (state) => {
+      state.loading = true;
+    }).addCase(moveEntity.fulfilled, (state, action) => {
+      state.loading = false;
+      const { committed, entity } = action.payload;
+      if (!committed) return;
+      // the entity leaves the open category
+      if (state.activeEnType) state.entities = state.entities.filter(e => e.id !== entity.id);
+      else state.entities = state.entities.map(e => e.id === entity.id ? entity : e);
+      if (state.activeEntity?.id === entity.id) state.activeEntity = null;
+    }).addCase(moveEntity.rejected, (state) => {
+      state.loading = false;
+    });
     builder.addCase(deleteEntity.pending, (state) => {
       state.loading = true;
     }).addCase(deleteEntity.fulfilled, (state, action) => {
@@ -506,69 +1022,124 @@ export const eavSlice = createSlice({
     }).addCase(deleteValue.rejected, (state) => {
       state.loading = false;
     });
-    builder.addCase(searchEntity.pending, (state) => {
+    builder.addCase(search.pending, (state) => {
       state.loading = true;
       state.activeEnType = null;
-    }).addCase(searchEntity.fulfilled, (state, action) => {
+      state.showTable = false;
+    }).addCase(search.fulfilled, (state, action) => {
       state.loading = false;
       // update meta data
       state.entityMeta = {
-        fn: fnsWithPaginationEnum.searchEntity,
-        regex: action.meta.arg?.regex,
-        extended: action.meta.arg?.extended,
-        page: action.meta.arg?.page || 1,
+        fn: fnsWithPaginationEnum.search,
+        query: action.meta.arg?.query,
+        page: action.payload.page,
+        total: action.payload.total,
+        end: !action.payload.has_more,
       }
-      if (action.payload.length < PER_PAGE) state.entityMeta.end = true;
       // update entities data
-      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload];
-      else state.entities = action.payload;
+      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload.items];
+      else state.entities = action.payload.items;
       state.activeEntity = null;
-    }).addCase(searchEntity.rejected, (state) => {
+    }).addCase(search.rejected, (state) => {
       state.loading = false;
       state.entities = [];
     });
-    builder.addCase(searchAttrValue.pending, (state) => {
+    builder.addCase(fetchTrash.pending, (state) => {
       state.loading = true;
-      state.activeEnType = null;
-    }).addCase(searchAttrValue.fulfilled, (state, action) => {
+    }).addCase(fetchTrash.fulfilled, (state, action) => {
       state.loading = false;
-      // update meta data
-      state.entityMeta = {
-        fn: fnsWithPaginationEnum.searchAttrValue,
-        attr: action.meta.arg?.attr, 
-        val: action.meta.arg?.val, 
-        page: action.meta.arg?.page || 1,
-      }
-      if (action.payload.length < PER_PAGE) state.entityMeta.end = true;
-      // update entities data
-      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload];
-      else state.entities = action.payload;
-      state.activeEntity = null;
-    }).addCase(searchAttrValue.rejected, (state) => {
+      state.trash = action.payload.items;
+    }).addCase(fetchTrash.rejected, (state) => {
       state.loading = false;
-      state.entities = [];
+      state.trash = [];
     });
-    builder.addCase(searchAttrValueComparison.pending, (state) => {
+    builder.addCase(restoreFromTrash.pending, (state) => {
       state.loading = true;
-      state.activeEnType = null;
-    }).addCase(searchAttrValueComparison.fulfilled, (state, action) => {
+    }).addCase(restoreFromTrash.fulfilled, (state, action) => {
       state.loading = false;
-      // update meta data
-      state.entityMeta = {
-        fn: fnsWithPaginationEnum.searchAttrValueComparison,
-        attr: action.meta.arg?.attr,
-        val: action.meta.arg?.val,
-        op: action.meta.arg?.op,
-        page: action.meta.arg?.page || 1,
-      }
-      if (action.payload.length < PER_PAGE) state.entityMeta.end = true;
-      // update entities data
-      if (action.meta.arg?.page > 1) state.entities = [...state.entities, ...action.payload];
-      else state.entities = action.payload;
-      state.activeEntity = null;
-    }).addCase(searchAttrValueComparison.rejected, (state) => {
+      state.trash = state.trash.filter(x => 
+        x.kind !== action.payload.kind || x.id !== action.payload.id
+      );
+    }).addCase(restoreFromTrash.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(purgeTrash.pending, (state) => {
+      state.loading = true;
+    }).addCase(purgeTrash.fulfilled, (state) => {
       state.loading = false;
+    }).addCase(purgeTrash.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(exportTable.pending, (state) => {
+      state.loading = true;
+    }).addCase(exportTable.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(exportTable.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(previewCsv.pending, (state) => {
+      state.loading = true;
+    }).addCase(previewCsv.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(previewCsv.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(importCsv.pending, (state) => {
+      state.loading = true;
+    }).addCase(importCsv.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(importCsv.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(exportArchive.pending, (state) => {
+      state.loading = true;
+    }).addCase(exportArchive.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(exportArchive.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(importArchive.pending, (state) => {
+      state.loading = true;
+    }).addCase(importArchive.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(importArchive.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(createBackup.pending, (state) => {
+      state.loading = true;
+    }).addCase(createBackup.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(createBackup.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(restoreBackup.pending, (state) => {
+      state.loading = true;
+    }).addCase(restoreBackup.fulfilled, (state) => {
+      state.loading = false;
+      state.activeEnType = null;
+      state.activeEntity = null;
       state.entities = [];
+      state.attrs = [];
+      state.values = [];
+    }).addCase(restoreBackup.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(undo.pending, (state) => {
+      state.loading = true;
+    }).addCase(undo.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(undo.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addCase(redo.pending, (state) => {
+      state.loading = true;
+    }).addCase(redo.fulfilled, (state) => {
+      state.loading = false;
+    }).addCase(redo.rejected, (state) => {
+      state.loading = false;
+    });
+    builder.addMatcher((action) => action.type.endsWith("/rejected") && !!action.payload?.code, (state, action) => {
+      state.error = action.payload;
     });
   }
 });
@@ -584,8 +1155,20 @@ export const {
   setActiveEntity,
   toggleShowDel,
   toggleShowHelp,
+  toggleShowTrash,
+  toggleShowImport,
+  toggleShowExport,
+  toggleShowArchive,
+  toggleShowBackups,
+  toggleShowMove,
+  toggleShowMerge,
+  openMerge,
+  toggleShowDuplicates,
   scrollToTop,
   resetScrollToTop,
+  clearError,
+  setSort,
+  toggleShowTable,
 } = eavSlice.actions;
 
 export default eavSlice.reducer;
\ No newline at end of file